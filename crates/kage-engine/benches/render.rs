@@ -0,0 +1,54 @@
+use criterion::{Criterion, criterion_group, criterion_main};
+use kage_engine::{Typeface, kage::Kage, polygons::Polygons};
+
+#[path = "../tests/common/mod.rs"]
+mod common;
+
+use common::corpus_kage;
+
+fn render_all(kage: &Kage, names: &[&str], polygons: &mut Polygons) -> usize {
+    let mut count = 0;
+    for name in names {
+        polygons.clear();
+        kage.make_glyph_with_component_name(polygons, name);
+        count += polygons.len();
+    }
+    count
+}
+
+fn bench_typefaces(c: &mut Criterion) {
+    let mut group = c.benchmark_group("render");
+    for (label, typeface, use_curve) in [
+        ("ming", Typeface::Ming, false),
+        ("ming-curve", Typeface::Ming, true),
+        ("gothic", Typeface::Gothic, false),
+        ("rounded-gothic", Typeface::RoundedGothic, false),
+        ("textbook", Typeface::Textbook, false),
+    ] {
+        let (kage, names) = corpus_kage(typeface, use_curve);
+        let mut polygons = Polygons::new();
+        group.bench_function(label, |b| {
+            b.iter(|| render_all(&kage, &names, &mut polygons))
+        });
+    }
+    group.finish();
+}
+
+fn bench_cache(c: &mut Criterion) {
+    let (mut kage, names) = corpus_kage(Typeface::Ming, false);
+    kage.set_component_cache(true);
+    let mut polygons = Polygons::new();
+    c.bench_function("render/ming-cached", |b| {
+        b.iter(|| render_all(&kage, &names, &mut polygons))
+    });
+}
+
+fn bench_output(c: &mut Criterion) {
+    let (kage, names) = corpus_kage(Typeface::Ming, false);
+    let mut polygons = Polygons::new();
+    kage.make_glyph_with_component_name(&mut polygons, names[names.len() - 1]);
+    c.bench_function("output/svg", |b| b.iter(|| polygons.generate_svg(false)));
+}
+
+criterion_group!(benches, bench_typefaces, bench_cache, bench_output);
+criterion_main!(benches);
@@ -7,24 +7,20 @@ fn main() {
     let newest_data = fs::read_to_string("./data/dump_newest_only.txt").unwrap();
     let all_version_data = fs::read_to_string("./data/dump_all_versions.txt").unwrap();
     let names = set_full_components(&mut kage, &newest_data, &all_version_data);
+    // popular components are referenced by thousands of glyphs
+    kage.set_component_cache(true);
 
-    let mut polygons = Polygons::new();
-
-    for (idx, name) in names.iter().enumerate() {
-        let sub_dir_num = idx / 10000;
-        if idx % 10000 == 0 {
-            fs::create_dir_all(format!("./results/{:03}", sub_dir_num)).unwrap()
-        }
+    for sub_dir_num in 0..names.len().div_ceil(10000) {
+        fs::create_dir_all(format!("./results/{:03}", sub_dir_num)).unwrap();
+    }
 
-        polygons.clear();
-        kage.make_glyph_with_component_name(&mut polygons, name);
-        let result = polygons.generate_svg(false);
+    let write = |idx: usize, name: &str, polygons: &Polygons| {
         fs::write(
-            format!("./results/{:03}/{:07}_{}.svg", sub_dir_num, idx, name),
-            result,
+            format!("./results/{:03}/{:07}_{}.svg", idx / 10000, idx, name),
+            polygons.generate_svg(false),
         )
-        .unwrap();
-    }
+    };
+    kage.render_batch(names.iter().copied(), 0, &write).unwrap();
 }
 
 fn set_full_components<'a>(
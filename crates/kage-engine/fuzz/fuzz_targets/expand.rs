@@ -0,0 +1,36 @@
+//! Expands glyph data referencing arbitrary components.
+//!
+//! The input is read like `tests/corpus/glyphs.txt`: a line `name<TAB>data`
+//! adds a component, any other line is glyph data. Every glyph and
+//! component is expanded, with and without the component cache.
+
+#![no_main]
+
+use kage_engine::{Typeface, kage::Kage, line::Line};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &str| {
+    let mut kage = Kage::new(Typeface::Ming, false);
+    let mut glyphs = Vec::new();
+    for line in data.lines() {
+        match line.split_once('\t') {
+            Some((name, data)) => {
+                kage.components.push(name, data);
+                glyphs.push(data);
+            }
+            None => glyphs.push(line),
+        }
+    }
+
+    for cache in [false, true] {
+        kage.set_component_cache(cache);
+        for glyph in &glyphs {
+            let lines = kage.get_each_expanded_line(glyph);
+            assert!(
+                !lines
+                    .iter()
+                    .any(|line| matches!(line, Line::ComponentReferenceLine(_)))
+            );
+        }
+    }
+});
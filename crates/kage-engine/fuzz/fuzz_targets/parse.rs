@@ -0,0 +1,15 @@
+//! Parses arbitrary glyph data line by line and writes it back.
+
+#![no_main]
+
+use kage_engine::line::{Line, LineVecTrait};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &str| {
+    let lines: Vec<Line> = data.split('$').map(Line::new).collect();
+    let kage = lines.generate_kage();
+
+    // what is written back parses to the same lines
+    let reparsed: Vec<Line> = kage.split('$').map(Line::new).collect();
+    assert_eq!(reparsed.generate_kage(), kage);
+});
@@ -0,0 +1,45 @@
+//! Draws glyph data referencing arbitrary components in both typefaces,
+//! with and without curves, and checks that no point drawn is NaN or
+//! infinite.
+//!
+//! The input is read like `tests/corpus/glyphs.txt`: a line `name<TAB>data`
+//! adds a component, any other line is glyph data.
+
+#![no_main]
+
+use kage_engine::{Typeface, kage::Kage, polygons::Polygons};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &str| {
+    for (typeface, use_curve) in [
+        (Typeface::Ming, false),
+        (Typeface::Ming, true),
+        (Typeface::Gothic, false),
+        (Typeface::Gothic, true),
+    ] {
+        let mut kage = Kage::new(typeface, use_curve);
+        let mut glyphs = Vec::new();
+        for line in data.lines() {
+            match line.split_once('\t') {
+                Some((name, data)) => {
+                    kage.components.push(name, data);
+                    glyphs.push(data);
+                }
+                None => glyphs.push(line),
+            }
+        }
+
+        let mut polygons = Polygons::new();
+        for glyph in glyphs {
+            polygons.clear();
+            kage.make_glyph_with_data(&mut polygons, glyph);
+            // `push` drops the polygons with a point that is not finite
+            assert_eq!(
+                polygons.non_finite_count(),
+                0,
+                "non-finite point in {glyph}"
+            );
+            polygons.generate_svg(use_curve);
+        }
+    }
+});
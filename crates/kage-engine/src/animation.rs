@@ -0,0 +1,346 @@
+use core::ops::Range;
+
+use crate::{
+    kage::Kage,
+    polygons::Polygons,
+    postscript::number,
+    svg::{escape, push_path_data},
+    utils::Vector,
+};
+
+/// Options of the stroke-order animation.
+///
+/// By default each stroke is revealed along its direction in 0.6 seconds,
+/// with a 0.2 second pause between strokes, and the animation plays once.
+#[derive(Debug, Clone)]
+pub struct AnimationOptions {
+    size: f64,
+    stroke_duration: f64,
+    speed: Option<f64>,
+    stroke_gap: f64,
+    initial_delay: f64,
+    loop_pause: Option<f64>,
+    brush_width: f64,
+    fill: String,
+    background: Option<String>,
+    id_prefix: String,
+}
+
+impl Default for AnimationOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AnimationOptions {
+    pub fn new() -> Self {
+        Self {
+            size: 200.0,
+            stroke_duration: 0.6,
+            speed: None,
+            stroke_gap: 0.2,
+            initial_delay: 0.0,
+            loop_pause: None,
+            brush_width: 30.0,
+            fill: "black".to_string(),
+            background: None,
+            id_prefix: "kage".to_string(),
+        }
+    }
+
+    /// Sets the `width` and `height` of the document in pixels.
+    pub fn size(mut self, size: f64) -> Self {
+        self.size = size;
+        self
+    }
+
+    /// Sets how long each stroke takes to draw, in seconds.
+    pub fn stroke_duration(mut self, seconds: f64) -> Self {
+        self.stroke_duration = seconds;
+        self
+    }
+
+    /// Draws every stroke at the same speed, in glyph units per second,
+    /// instead of giving each stroke the same duration.
+    pub fn speed(mut self, units_per_second: Option<f64>) -> Self {
+        self.speed = units_per_second;
+        self
+    }
+
+    /// Sets the pause between two strokes, in seconds.
+    pub fn stroke_gap(mut self, seconds: f64) -> Self {
+        self.stroke_gap = seconds;
+        self
+    }
+
+    /// Sets the time before the first stroke starts, in seconds.
+    pub fn initial_delay(mut self, seconds: f64) -> Self {
+        self.initial_delay = seconds;
+        self
+    }
+
+    /// Repeats the animation forever, showing the finished glyph for the
+    /// given number of seconds before starting over. `None` (the default)
+    /// plays it once.
+    pub fn loop_pause(mut self, seconds: Option<f64>) -> Self {
+        self.loop_pause = seconds;
+        self
+    }
+
+    /// Sets the width, in glyph units, of the brush that reveals a stroke.
+    /// It should cover the outline of the stroke around its centre line.
+    pub fn brush_width(mut self, brush_width: f64) -> Self {
+        self.brush_width = brush_width;
+        self
+    }
+
+    pub fn fill<S: Into<String>>(mut self, fill: S) -> Self {
+        self.fill = fill.into();
+        self
+    }
+
+    /// Shows the whole glyph in the given colour behind the animation, as
+    /// a model to trace.
+    pub fn background<S: Into<String>>(mut self, background: Option<S>) -> Self {
+        self.background = background.map(Into::into);
+        self
+    }
+
+    /// Sets the prefix of the ids in the document, to keep them unique
+    /// when several animations are embedded in the same page.
+    pub fn id_prefix<S: Into<String>>(mut self, id_prefix: S) -> Self {
+        self.id_prefix = id_prefix.into();
+        self
+    }
+}
+
+/// Returns the `values`, `keyTimes` and timing attributes of an animation
+/// switching from `from` to `to` between `start` and `end` seconds.
+fn timeline(start: f64, end: f64, total: f64, repeat: bool, from: &str, to: &str) -> String {
+    let key_time = |time: f64| number((time / total).clamp(0.0, 1.0));
+    let repeat = if repeat {
+        r#"repeatCount="indefinite""#
+    } else {
+        r#"fill="freeze""#
+    };
+
+    format!(
+        r#"values="{from};{from};{to};{to}" keyTimes="0;{};{};1" dur="{}s" {repeat}"#,
+        key_time(start),
+        key_time(end),
+        number(total)
+    )
+}
+
+impl Kage {
+    /// Generates an SVG document revealing the strokes of the glyph one by
+    /// one, in the order of the expanded glyph data, each along its
+    /// direction.
+    ///
+    /// Every stroke is masked by its centre line, rounded at bends like the
+    /// typeface of the engine and drawn with an animated dash offset, and
+    /// then shown unmasked once it is complete, so parts of the outline
+    /// beyond the brush are not lost.
+    pub fn generate_stroke_animation(&self, data: &str, options: &AnimationOptions) -> String {
+        let mut polygons = Polygons::new();
+        let ranges = self.make_strokes_with_data(&mut polygons, data);
+        let skeletons = self.make_skeleton_with_data(data, &self.font.skeleton_options());
+        let prefix = escape(&options.id_prefix);
+
+        // (start, end) of each stroke, in seconds
+        let mut schedule = Vec::with_capacity(ranges.len());
+        let mut time = options.initial_delay;
+        for skeleton in &skeletons {
+            let polyline = skeleton.to_polyline(0.5);
+            let length: f64 = polyline
+                .windows(2)
+                .map(|pair| Vector::from(pair[1] - pair[0]).hypot())
+                .sum();
+            let duration = match options.speed {
+                Some(speed) if speed > 0.0 => length / speed,
+                _ => options.stroke_duration,
+            };
+            schedule.push((time, time + duration));
+            time += duration + options.stroke_gap;
+        }
+        let finished = (time - options.stroke_gap).max(options.initial_delay);
+        let repeat = options.loop_pause.is_some();
+        let total = (finished + options.loop_pause.unwrap_or(0.0)).max(f64::EPSILON);
+
+        let mut buffer = format!(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink" version="1.1" viewBox="0 0 200 200" width="{0}" height="{0}">"#,
+            number(options.size)
+        );
+        buffer.push('\n');
+
+        // Polygons are kept apart: merged into one path, overlapping
+        // contours wound in opposite directions would cancel out.
+        let push_paths = |buffer: &mut String, range: Range<usize>| {
+            for polygon in polygons.array().skip(range.start).take(range.len()) {
+                let mut data = String::new();
+                push_path_data(&mut data, &polygon.segments(), number);
+                buffer.push_str(&format!(r#"<path d="{data}" />"#));
+                buffer.push('\n');
+            }
+        };
+
+        buffer.push_str("<defs>\n");
+        for (index, range) in ranges.iter().enumerate() {
+            buffer.push_str(&format!(r#"<g id="{prefix}-stroke-{index}">"#));
+            buffer.push('\n');
+            push_paths(&mut buffer, range.clone());
+            buffer.push_str("</g>\n");
+        }
+        for (index, (skeleton, &(start, end))) in skeletons.iter().zip(&schedule).enumerate() {
+            buffer.push_str(&format!(
+                r#"<mask id="{prefix}-mask-{index}" maskUnits="userSpaceOnUse" x="-50" y="-50" width="300" height="300">"#
+            ));
+            buffer.push('\n');
+            buffer.push_str(&format!(
+                r#"<path d="{}" fill="none" stroke="white" stroke-width="{}" stroke-linecap="round" stroke-linejoin="round" pathLength="1" stroke-dasharray="1 1" stroke-dashoffset="1">"#,
+                skeleton.path_data(),
+                number(options.brush_width)
+            ));
+            buffer.push('\n');
+            buffer.push_str(&format!(
+                r#"<animate attributeName="stroke-dashoffset" {} />"#,
+                timeline(start, end, total, repeat, "1", "0")
+            ));
+            buffer.push('\n');
+            buffer.push_str("</path>\n</mask>\n");
+        }
+        buffer.push_str("</defs>\n");
+
+        if let Some(background) = &options.background {
+            buffer.push_str(&format!(r#"<g fill="{}">"#, escape(background)));
+            buffer.push('\n');
+            push_paths(&mut buffer, 0..polygons.len());
+            buffer.push_str("</g>\n");
+        }
+
+        buffer.push_str(&format!(r#"<g fill="{}">"#, escape(&options.fill)));
+        buffer.push('\n');
+        for (index, &(_, end)) in schedule.iter().enumerate() {
+            buffer.push_str(&format!(
+                r##"<use xlink:href="#{prefix}-stroke-{index}" mask="url(#{prefix}-mask-{index})" />"##
+            ));
+            buffer.push('\n');
+            buffer.push_str(&format!(
+                r##"<use xlink:href="#{prefix}-stroke-{index}" opacity="0">"##
+            ));
+            buffer.push('\n');
+            buffer.push_str(&format!(
+                r#"<animate attributeName="opacity" calcMode="discrete" {} />"#,
+                timeline(end, end, total, repeat, "0", "1")
+            ));
+            buffer.push_str("\n</use>\n");
+        }
+        buffer.push_str("</g>\n");
+        buffer.push_str("</svg>\n");
+
+        buffer
+    }
+
+    pub fn generate_stroke_animation_with_component_name(
+        &self,
+        component_name: &str,
+        options: &AnimationOptions,
+    ) -> Option<String> {
+        self.components
+            .search(component_name)
+            .map(|data| self.generate_stroke_animation(data, options))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Typeface;
+
+    const DATA: &str = "1:0:2:32:31:176:31$2:22:7:176:31:170:43:156:63";
+
+    #[test]
+    fn test_strokes_and_masks() {
+        let kage = Kage::new(Typeface::Ming, false);
+        let svg = kage.generate_stroke_animation(DATA, &AnimationOptions::new());
+
+        assert!(svg.contains("<g id=\"kage-stroke-0\">\n<path d=\"M "));
+        assert!(svg.contains("<g id=\"kage-stroke-1\">\n<path d=\"M "));
+        assert!(svg.contains(r#"<mask id="kage-mask-1" "#));
+        assert!(svg.contains(r#"<path d="M 32,31 L 176,31" fill="none" stroke="white""#));
+        assert_eq!(svg.matches("<animate ").count(), 4);
+    }
+
+    #[test]
+    fn test_skeleton_of_typeface() {
+        use crate::{
+            font::{gothic::Gothic, parameters::Parameters},
+            skeleton::SkeletonOptions,
+        };
+
+        let data = "3:0:0:40:40:40:160:160:160";
+        let kage = Kage::from_parameters(Parameters::Gothic(Gothic {
+            curve_size: 30.0,
+            ..Gothic::default()
+        }))
+        .unwrap();
+        let svg = kage.generate_stroke_animation(data, &AnimationOptions::new());
+
+        let mask = |options: &SkeletonOptions| {
+            let skeleton = kage.make_skeleton_with_data(data, options).remove(0);
+            format!(r#"<path d="{}" fill="none""#, skeleton.path_data())
+        };
+        assert!(svg.contains(&mask(&SkeletonOptions::new().curve_size(30.0))));
+        assert!(!svg.contains(&mask(&SkeletonOptions::new())));
+    }
+
+    #[test]
+    fn test_timing() {
+        let kage = Kage::new(Typeface::Ming, false);
+
+        // the strokes are drawn in 0.6s each, with a 0.2s pause in between
+        let svg = kage.generate_stroke_animation(DATA, &AnimationOptions::new());
+        assert!(
+            svg.contains(r#"values="1;1;0;0" keyTimes="0;0;0.429;1" dur="1.4s" fill="freeze""#)
+        );
+        assert!(
+            svg.contains(r#"values="1;1;0;0" keyTimes="0;0.571;1;1" dur="1.4s" fill="freeze""#)
+        );
+
+        // strokes of 144 and 72 units, drawn from 1s to 2s and from 2s to
+        // 2.5s, then shown for 1.5s
+        let svg = kage.generate_stroke_animation(
+            "1:0:0:20:50:164:50$1:0:0:20:150:92:150",
+            &AnimationOptions::new()
+                .speed(Some(144.0))
+                .stroke_gap(0.0)
+                .initial_delay(1.0)
+                .loop_pause(Some(1.5)),
+        );
+        assert!(svg.contains(
+            r#"values="1;1;0;0" keyTimes="0;0.25;0.5;1" dur="4s" repeatCount="indefinite""#
+        ));
+        assert!(svg.contains(
+            r#"values="1;1;0;0" keyTimes="0;0.5;0.625;1" dur="4s" repeatCount="indefinite""#
+        ));
+        assert!(!svg.contains("freeze"));
+    }
+
+    #[test]
+    fn test_background_and_prefix() {
+        let kage = Kage::new(Typeface::Gothic, false);
+        let svg = kage.generate_stroke_animation(
+            DATA,
+            &AnimationOptions::new()
+                .background(Some("#ccc"))
+                .id_prefix("u4e00"),
+        );
+
+        assert!(svg.contains(r##"fill="#ccc""##));
+        assert!(svg.contains(r##"mask="url(#u4e00-mask-0)""##));
+        assert!(
+            kage.generate_stroke_animation_with_component_name("u4e00", &AnimationOptions::new())
+                .is_none()
+        );
+    }
+}
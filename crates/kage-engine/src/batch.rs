@@ -0,0 +1,301 @@
+use std::{
+    collections::BTreeMap,
+    fs, io,
+    path::PathBuf,
+    sync::{
+        Mutex,
+        atomic::{AtomicUsize, Ordering},
+    },
+    thread,
+};
+
+use crate::{kage::Kage, polygons::Polygons, svg::SvgOptions};
+
+/// Receives the glyphs rendered by [`Kage::render_batch`].
+///
+/// `write` is called from several threads at once, in no particular order;
+/// `index` is the position of the glyph in the batch. Any closure
+/// `Fn(usize, &str, &Polygons) -> Result<(), E>` is a sink.
+pub trait RenderSink: Sync {
+    type Error: Send;
+
+    fn write(&self, index: usize, name: &str, polygons: &Polygons) -> Result<(), Self::Error>;
+}
+
+impl<F, E> RenderSink for F
+where
+    F: Fn(usize, &str, &Polygons) -> Result<(), E> + Sync,
+    E: Send,
+{
+    type Error = E;
+
+    fn write(&self, index: usize, name: &str, polygons: &Polygons) -> Result<(), E> {
+        self(index, name, polygons)
+    }
+}
+
+/// Writes each glyph to an SVG file named after it in a directory.
+pub struct SvgDirectory {
+    path: PathBuf,
+    options: SvgOptions,
+}
+
+impl SvgDirectory {
+    /// Writes to `path`, which must exist, with the default SVG options.
+    pub fn new<P: Into<PathBuf>>(path: P) -> Self {
+        Self {
+            path: path.into(),
+            options: SvgOptions::new(),
+        }
+    }
+
+    pub fn options(mut self, options: SvgOptions) -> Self {
+        self.options = options;
+        self
+    }
+}
+
+impl RenderSink for SvgDirectory {
+    type Error = io::Error;
+
+    fn write(&self, _index: usize, name: &str, polygons: &Polygons) -> io::Result<()> {
+        let svg = polygons.generate_svg_with(&self.options);
+        fs::write(self.path.join(format!("{name}.svg")), svg)
+    }
+}
+
+/// Writes the SVG of each glyph to a writer, one after another in the
+/// order of the batch whatever the number of threads.
+pub struct SvgWriter<W> {
+    options: SvgOptions,
+    state: Mutex<WriterState<W>>,
+}
+
+struct WriterState<W> {
+    writer: W,
+    /// Index of the next glyph to write.
+    next: usize,
+    /// Glyphs rendered before the ones preceding them.
+    pending: BTreeMap<usize, String>,
+}
+
+impl<W: io::Write + Send> SvgWriter<W> {
+    pub fn new(writer: W, options: SvgOptions) -> Self {
+        Self {
+            options,
+            state: Mutex::new(WriterState {
+                writer,
+                next: 0,
+                pending: BTreeMap::new(),
+            }),
+        }
+    }
+
+    /// Returns the writer. Glyphs still waiting for a previous one, which
+    /// only happens after an error, are dropped.
+    pub fn into_inner(self) -> W {
+        let state = self
+            .state
+            .into_inner()
+            .unwrap_or_else(|error| error.into_inner());
+        state.writer
+    }
+}
+
+impl<W: io::Write + Send> RenderSink for SvgWriter<W> {
+    type Error = io::Error;
+
+    fn write(&self, index: usize, _name: &str, polygons: &Polygons) -> io::Result<()> {
+        let svg = polygons.generate_svg_with(&self.options);
+
+        let mut state = self
+            .state
+            .lock()
+            .map_err(|_| io::Error::other("a previous write panicked"))?;
+        state.pending.insert(index, svg);
+        loop {
+            let next = state.next;
+            let Some(svg) = state.pending.remove(&next) else {
+                break;
+            };
+            state.writer.write_all(svg.as_bytes())?;
+            state.next += 1;
+        }
+        Ok(())
+    }
+}
+
+impl Kage {
+    /// Renders the glyphs of the store with the given names on `threads`
+    /// threads, or on as many threads as the machine runs in parallel if
+    /// `threads` is 0, and hands each of them to `sink`. Names that are not
+    /// in the store are rendered as empty glyphs.
+    ///
+    /// Each glyph is drawn as [`Kage::make_glyph_with_component_name`]
+    /// would, so the output does not depend on the number of threads.
+    /// Rendering stops at the first error of the sink; the error returned
+    /// is the one of the first glyph that failed in the order of `names`.
+    /// Otherwise, returns the number of glyphs rendered.
+    pub fn render_batch<'a, I, S>(
+        &self,
+        names: I,
+        threads: usize,
+        sink: &S,
+    ) -> Result<usize, S::Error>
+    where
+        I: IntoIterator<Item = &'a str>,
+        S: RenderSink,
+    {
+        let names: Vec<&str> = names.into_iter().collect();
+        let threads = match threads {
+            0 => thread::available_parallelism().map_or(1, |threads| threads.get()),
+            threads => threads,
+        }
+        .min(names.len())
+        .max(1);
+
+        let next = AtomicUsize::new(0);
+        let first_error: Mutex<Option<(usize, S::Error)>> = Mutex::new(None);
+        // lowest index that failed, or `usize::MAX`
+        let first_failed = AtomicUsize::new(usize::MAX);
+
+        let render = || {
+            let mut polygons = Polygons::new();
+            loop {
+                // glyphs before a failed one are always handed to the sink,
+                // so that the error of the first failing glyph is returned
+                let index = next.fetch_add(1, Ordering::Relaxed);
+                if index >= names.len() || index > first_failed.load(Ordering::Relaxed) {
+                    break;
+                }
+
+                polygons.clear();
+                self.make_glyph_with_component_name(&mut polygons, names[index]);
+                if let Err(error) = sink.write(index, names[index], &polygons) {
+                    first_failed.fetch_min(index, Ordering::Relaxed);
+                    if let Ok(mut first_error) = first_error.lock()
+                        && first_error.as_ref().is_none_or(|(first, _)| index < *first)
+                    {
+                        *first_error = Some((index, error));
+                    }
+                }
+            }
+        };
+        thread::scope(|scope| {
+            for _ in 1..threads {
+                scope.spawn(render);
+            }
+            render();
+        });
+
+        match first_error.into_inner() {
+            Ok(Some((_, error))) => Err(error),
+            _ => Ok(names.len()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Typeface;
+
+    fn kage() -> Kage {
+        let mut kage = Kage::new(Typeface::Ming, false);
+        kage.components.push(
+            "u53e3",
+            "1:0:2:40:40:40:160$1:12:13:40:40:160:40:160:160$1:2:2:40:160:160:160",
+        );
+        kage.components
+            .push("u65e5", "1:0:2:40:20:40:180$1:12:13:40:20:160:20:160:180$1:2:2:40:100:160:100$1:2:2:40:180:160:180");
+        kage.components.push(
+            "u54c1",
+            "99:0:0:50:0:150:100:u53e3$99:0:0:0:100:100:200:u53e3$99:0:0:100:100:200:200:u53e3",
+        );
+        kage
+    }
+
+    const NAMES: [&str; 5] = ["u53e3", "u54c1", "missing", "u65e5", "u53e3"];
+
+    fn render(kage: &Kage, threads: usize) -> Vec<String> {
+        let results = Mutex::new(vec![String::new(); NAMES.len()]);
+        let count = kage
+            .render_batch(NAMES, threads, &|index, _: &str, polygons: &Polygons| {
+                results.lock().unwrap()[index] = polygons.generate_svg(false);
+                Ok::<(), ()>(())
+            })
+            .unwrap();
+        assert_eq!(count, NAMES.len());
+        results.into_inner().unwrap()
+    }
+
+    #[test]
+    fn test_render_batch() {
+        let kage = kage();
+
+        let results = render(&kage, 1);
+        let mut polygons = Polygons::new();
+        kage.make_glyph_with_component_name(&mut polygons, "u54c1");
+        assert_eq!(results[1], polygons.generate_svg(false));
+        assert_eq!(results[0], results[4]);
+        for threads in [0, 2, 8] {
+            assert_eq!(render(&kage, threads), results);
+        }
+
+        // in order, whatever the number of threads
+        for threads in [1, 3] {
+            let writer = SvgWriter::new(Vec::new(), SvgOptions::new());
+            kage.render_batch(NAMES, threads, &writer).unwrap();
+            let written = String::from_utf8(writer.into_inner()).unwrap();
+            let expected: String = NAMES
+                .iter()
+                .map(|name| {
+                    let mut polygons = Polygons::new();
+                    kage.make_glyph_with_component_name(&mut polygons, name);
+                    polygons.generate_svg_with(&SvgOptions::new())
+                })
+                .collect();
+            assert_eq!(written, expected);
+        }
+
+        assert_eq!(
+            kage.render_batch([], 4, &|_, _: &str, _: &Polygons| Err(())),
+            Ok(0)
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        let kage = kage();
+        let sink =
+            |index: usize, _: &str, _: &Polygons| if index >= 2 { Err(index) } else { Ok(()) };
+        for threads in [1, 2, 8] {
+            assert_eq!(kage.render_batch(NAMES, threads, &sink), Err(2));
+        }
+
+        // a later glyph failing first does not hide an earlier failure
+        let written = Mutex::new(Vec::new());
+        let sink = |index: usize, _: &str, _: &Polygons| {
+            if index == 1 {
+                thread::sleep(std::time::Duration::from_millis(50));
+                return Err(index);
+            }
+            written.lock().unwrap().push(index);
+            if index >= 3 { Err(index) } else { Ok(()) }
+        };
+        for threads in [2, 4, 8] {
+            written.lock().unwrap().clear();
+            assert_eq!(kage.render_batch(NAMES, threads, &sink), Err(1));
+            assert!(written.lock().unwrap().contains(&0));
+        }
+
+        let sink = SvgDirectory::new("/nonexistent/kage-batch");
+        assert!(kage.render_batch(NAMES, 2, &sink).is_err());
+    }
+
+    #[test]
+    fn test_sync() {
+        fn assert_sync<T: Sync + Send>() {}
+        assert_sync::<Kage>();
+    }
+}
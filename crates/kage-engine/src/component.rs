@@ -1,13 +1,25 @@
-use std::collections::HashMap;
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+};
+
+use crate::line::stroke_line::StrokeLineType;
+
+/// Expanded strokes of components in their own space, by component name.
+type StrokeCache = RwLock<HashMap<String, Arc<[StrokeLineType]>>>;
 
 pub struct Components {
     hash: HashMap<String, String>,
+    /// Present when the cache is enabled; cleared whenever a component
+    /// changes, since it may be referenced by any cached component.
+    cache: Option<StrokeCache>,
 }
 
 impl Components {
     pub(crate) fn new() -> Self {
         Components {
             hash: HashMap::new(),
+            cache: None,
         }
     }
 
@@ -17,6 +29,7 @@ impl Components {
         S1: Into<String>,
         S2: Into<String>,
     {
+        self.invalidate();
         self.hash.insert(name.into(), data.into()).is_some()
     }
 
@@ -27,6 +40,7 @@ impl Components {
         S1: Into<String>,
         S2: Into<String>,
     {
+        self.invalidate();
         self.hash.insert(name.into(), data.into()).is_some()
     }
 
@@ -39,4 +53,78 @@ impl Components {
     pub fn len(&self) -> usize {
         self.hash.len()
     }
+
+    pub(crate) fn set_cache_enabled(&mut self, enabled: bool) {
+        match (enabled, &self.cache) {
+            (true, None) => self.cache = Some(RwLock::default()),
+            (false, Some(_)) => self.cache = None,
+            _ => {}
+        }
+    }
+
+    pub(crate) fn is_cache_enabled(&self) -> bool {
+        self.cache.is_some()
+    }
+
+    /// Returns the cached strokes of a component, if any.
+    pub(crate) fn cached_strokes(&self, name: &str) -> Option<Arc<[StrokeLineType]>> {
+        let cache = self.cache.as_ref()?.read().ok()?;
+        cache.get(name).cloned()
+    }
+
+    /// Caches the strokes of a component, if the cache is enabled.
+    pub(crate) fn cache_strokes(&self, name: &str, strokes: &[StrokeLineType]) {
+        if let Some(Ok(mut cache)) = self.cache.as_ref().map(RwLock::write) {
+            cache.insert(name.to_string(), strokes.into());
+        }
+    }
+
+    pub(crate) fn invalidate(&mut self) {
+        if let Some(cache) = &mut self.cache {
+            *cache = RwLock::default();
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{Typeface, kage::Kage, polygons::Polygons};
+
+    fn draw(kage: &Kage, data: &str) -> String {
+        let mut polygons = Polygons::new();
+        kage.make_glyph_with_data(&mut polygons, data);
+        polygons.generate_svg(false)
+    }
+
+    #[test]
+    fn test_cache() {
+        let mut kage = Kage::new(Typeface::Ming, false);
+        kage.components.push(
+            "u53e3",
+            "1:0:2:40:40:40:160$1:0:2:40:40:160:40$1:0:2:160:40:160:160$1:0:2:40:160:160:160",
+        );
+        kage.components.push(
+            "u54c1-g",
+            "99:0:0:50:0:150:100:u53e3$99:0:0:0:100:100:200:u53e3$99:0:0:100:100:200:200:u53e3",
+        );
+        let data = "99:0:0:0:0:200:200:u54c1-g";
+        let uncached = draw(&kage, data);
+
+        kage.set_component_cache(true);
+        assert!(kage.is_component_cache_enabled());
+        assert_eq!(draw(&kage, data), uncached);
+        assert_eq!(kage.components.cached_strokes("u53e3").unwrap().len(), 4);
+        assert_eq!(kage.components.cached_strokes("u54c1-g").unwrap().len(), 12);
+        // drawn again from the cache
+        assert_eq!(draw(&kage, data), uncached);
+
+        // setting a component clears the cache
+        kage.components.set("u53e3", "1:0:0:20:100:180:100");
+        assert!(kage.components.cached_strokes("u54c1-g").is_none());
+        assert_ne!(draw(&kage, data), uncached);
+        assert_eq!(kage.components.cached_strokes("u54c1-g").unwrap().len(), 3);
+
+        kage.set_component_cache(false);
+        assert!(kage.components.cached_strokes("u54c1-g").is_none());
+    }
 }
@@ -1,12 +1,22 @@
 use crate::{
-    curve::{FattenResult, generate_fatten_curve},
+    curve::{
+        FattenResult, SplitResult, fit_quadratic_bezier, generate_fatten_curve,
+        split_quadratic_bezier_curve,
+    },
+    font::stroke_adjustment::AdjustedStroke,
     line::stroke_line::{EndKind, EndType, StrokeKind, StrokeLineType},
     pen::Pen,
     polygon::Polygon,
     polygons::Polygons,
-    utils::{Point, Rgb, Vector, normalize},
+    utils::{Point, Rgb, Vector, is_quadratic, normalize},
 };
 
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
 pub struct Gothic {
     /// must divide 1000
     pub sample_step: usize,
@@ -14,21 +24,62 @@ pub struct Gothic {
     /// Size of kakato in gothic.
     pub foot_size: f64,
     pub curve_size: f64,
+    /// Whether to use off-curve points to approximate curving strokes
+    /// with quadratic Bézier curves (experimental).
+    pub use_curve: bool,
+    /// Width of the collision box below カカト for shortening adjustment.
+    pub k_adjust_foot_range_x: f64,
+    /// Height of the collision box below カカト for each shortening adjustment level (0 to 3).
+    pub k_adjust_foot_range_y: Vec<f64>,
+    /// Number of カカト shortening levels. Must be set to 3.
+    pub k_adjust_foot_step: f64,
+}
+
+impl Default for Gothic {
+    fn default() -> Self {
+        Self::new(false)
+    }
 }
 
 impl Gothic {
-    pub fn new() -> Self {
+    pub fn new(use_curve: bool) -> Self {
         Self {
             sample_step: 100,
             width: 5.0,
             foot_size: 3.0,
             curve_size: 10.0,
+            use_curve,
+            k_adjust_foot_range_x: 20.0,
+            k_adjust_foot_range_y: vec![1.0, 19.0, 24.0, 30.0],
+            k_adjust_foot_step: 3.0,
         }
     }
+
+    /// Length of the kakato at the given shortening level, from
+    /// `width * foot_size` down to a quarter of it.
+    fn foot_length(&self, foot_adjustment: usize) -> f64 {
+        let level = (foot_adjustment as f64).min(self.k_adjust_foot_step);
+        self.width * self.foot_size * (1.0 - level / (self.k_adjust_foot_step + 1.0))
+    }
+
+    /// Size of the hane, shortened by the hane adjustment.
+    fn flick_size(&self, flick_adjustment: f64) -> f64 {
+        self.curve_size * (1.0 - flick_adjustment / 10.0).max(0.0)
+    }
+}
+
+/// How a segment of a stroke is drawn: its end shapes, the length of a
+/// kakato at its tail and its colour.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct SegmentShape {
+    pub(crate) head_shape: EndType,
+    pub(crate) tail_shape: EndType,
+    pub(crate) foot_length: f64,
+    pub(crate) color: Option<Rgb>,
 }
 
 impl Gothic {
-    fn draw_curve_body<P1, P2, P3, P4>(
+    pub(crate) fn draw_curve_body<P1, P2, P3, P4>(
         &self,
         polygons: &mut Polygons,
         start_point: P1,
@@ -47,6 +98,55 @@ impl Gothic {
         let control_point_2 = control_point_2.into();
         let end_point = end_point.into();
 
+        if self.use_curve && is_quadratic(control_point_1, control_point_2) {
+            let FattenResult {
+                left: left_sampled_points,
+                right: right_sampled_points,
+            } = generate_fatten_curve(
+                start_point,
+                control_point_1,
+                control_point_2,
+                end_point,
+                10,
+                |_| self.width,
+            );
+
+            // Both sides are offset curves of constant width, so each half
+            // of each side is fitted on its own.
+            let SplitResult { index, .. } = split_quadratic_bezier_curve(
+                start_point,
+                control_point_1,
+                end_point,
+                &left_sampled_points,
+            );
+            let fit_halves = |points: &[Point]| {
+                let first = fit_quadratic_bezier(&points[..index + 1])?;
+                let second = fit_quadratic_bezier(&points[index..])?;
+                Some(vec![
+                    first.start_point,
+                    first.control_point,
+                    first.end_point,
+                    second.control_point,
+                    second.end_point,
+                ])
+            };
+
+            let (Some(left_points), Some(right_points)) = (
+                fit_halves(&left_sampled_points),
+                fit_halves(&right_sampled_points),
+            ) else {
+                return;
+            };
+
+            let mut polygon_1 = Polygon::new(left_points, color);
+            let mut polygon_2 = Polygon::new(right_points, color);
+
+            polygon_2.reverse();
+            polygon_1.concat(polygon_2);
+            polygons.push(polygon_1);
+            return;
+        }
+
         let FattenResult {
             left: left_sampled_points,
             right: right_sampled_points,
@@ -74,15 +174,19 @@ impl Gothic {
         control_point_1: P2,
         control_point_2: P3,
         end_point: P4,
-        head_shape: EndType,
-        tail_shape: EndType,
-        color: Option<Rgb>,
+        shape: SegmentShape,
     ) where
         P1: Into<Point>,
         P2: Into<Point>,
         P3: Into<Point>,
         P4: Into<Point>,
     {
+        let SegmentShape {
+            head_shape,
+            tail_shape,
+            foot_length,
+            color,
+        } = shape;
         let mut start_point = start_point.into();
         let control_point_1 = control_point_1.into();
         let control_point_2 = control_point_2.into();
@@ -118,7 +222,7 @@ impl Gothic {
             &EndKind::BottomLeftCorner
             | &EndKind::BottomRightCorner
             | &EndKind::BottomLeftZhOld
-            | &EndKind::BottomLeftZhNew => self.width * self.foot_size,
+            | &EndKind::BottomLeftZhNew => foot_length,
             _ => 0.0,
         };
 
@@ -148,9 +252,7 @@ impl Gothic {
         start_point: P1,
         control_point: P2,
         end_point: P3,
-        head_shape: EndType,
-        tail_shape: EndType,
-        color: Option<Rgb>,
+        shape: SegmentShape,
     ) where
         P1: Into<Point>,
         P2: Into<Point>,
@@ -164,9 +266,7 @@ impl Gothic {
             control_point,
             control_point,
             end_point,
-            head_shape,
-            tail_shape,
-            color,
+            shape,
         )
     }
 
@@ -177,9 +277,7 @@ impl Gothic {
         control_point_1: P2,
         control_point_2: P3,
         end_point: P4,
-        head_shape: EndType,
-        tail_shape: EndType,
-        color: Option<Rgb>,
+        shape: SegmentShape,
     ) where
         P1: Into<Point>,
         P2: Into<Point>,
@@ -192,45 +290,52 @@ impl Gothic {
             control_point_1,
             control_point_2,
             end_point,
-            head_shape,
-            tail_shape,
-            color,
+            shape,
         )
     }
 
-    fn cd_draw_line<P1, P2>(
+    pub(crate) fn cd_draw_line<P1, P2>(
         &self,
         polygons: &mut Polygons,
         start_point: P1,
         end_point: P2,
-        head_shape: EndType,
-        tail_shape: EndType,
-        color: Option<Rgb>,
+        shape: SegmentShape,
     ) where
         P1: Into<Point>,
         P2: Into<Point>,
     {
+        let SegmentShape {
+            head_shape,
+            tail_shape,
+            foot_length,
+            color,
+        } = shape;
         let start_point = start_point.into();
         let end_point = end_point.into();
 
-        let (mut pen_1, mut pen_2, end_shape_1, end_shape_2) = if (start_point.x == end_point.x
-            && start_point.y > end_point.y)
-            || (start_point.x > end_point.x)
-        {
-            (
-                Pen::new(end_point.x, end_point.y),
-                Pen::new(start_point.x, start_point.y),
-                tail_shape,
-                head_shape,
-            )
-        } else {
-            (
-                Pen::new(start_point.x, start_point.y),
-                Pen::new(end_point.x, end_point.y),
-                head_shape,
-                tail_shape,
-            )
-        };
+        let head_foot_length = self.width * self.foot_size;
+        let (mut pen_1, mut pen_2, end_shape_1, end_shape_2, foot_length_1, foot_length_2) =
+            if (start_point.x == end_point.x && start_point.y > end_point.y)
+                || (start_point.x > end_point.x)
+            {
+                (
+                    Pen::new(end_point.x, end_point.y),
+                    Pen::new(start_point.x, start_point.y),
+                    tail_shape,
+                    head_shape,
+                    foot_length,
+                    head_foot_length,
+                )
+            } else {
+                (
+                    Pen::new(start_point.x, start_point.y),
+                    Pen::new(end_point.x, end_point.y),
+                    head_shape,
+                    tail_shape,
+                    head_foot_length,
+                    foot_length,
+                )
+            };
 
         // Avoid the degenerate case where the line collapses to a point,
         // since we can't compute a reliable normal vector then.
@@ -250,7 +355,7 @@ impl Gothic {
             | &EndKind::BottomRightCorner
             | &EndKind::BottomLeftZhOld
             | &EndKind::BottomLeftZhNew => {
-                pen_1.move_local(0.0, -self.width * self.foot_size);
+                pen_1.move_local(0.0, -foot_length_1);
             }
             _ => {}
         }
@@ -266,7 +371,7 @@ impl Gothic {
             | &EndKind::BottomRightCorner
             | &EndKind::BottomLeftZhOld
             | &EndKind::BottomLeftZhNew => {
-                pen_2.move_local(0.0, self.width * self.foot_size);
+                pen_2.move_local(0.0, foot_length_2);
             }
             _ => {}
         }
@@ -292,7 +397,21 @@ impl Gothic {
 }
 
 impl Gothic {
-    pub fn df_draw_font(&self, polygons: &mut Polygons, stroke_line: StrokeLineType) {
+    pub fn df_draw_font(
+        &self,
+        polygons: &mut Polygons,
+        stroke_line: StrokeLineType,
+        stroke_adjustment: AdjustedStroke,
+    ) {
+        let foot_length = self.foot_length(stroke_adjustment.foot_adjustment);
+        let shape = |head_shape, tail_shape| SegmentShape {
+            head_shape,
+            tail_shape,
+            foot_length,
+            color: stroke_line.color,
+        };
+        let flick_size = self.flick_size(stroke_adjustment.flick_adjustment);
+
         match stroke_line.stroke_type.kind {
             StrokeKind::StraightLine => {
                 if matches!(&stroke_line.tail_shape.kind, EndKind::LeftUpwardFlick) {
@@ -308,30 +427,24 @@ impl Gothic {
                         polygons,
                         stroke_line.point_1,
                         joint_point,
-                        stroke_line.head_shape,
-                        EndType::new(1.0),
-                        stroke_line.color,
+                        shape(stroke_line.head_shape, EndType::new(1.0)),
                     );
                     self.cd_draw_quadratic_bezier(
                         polygons,
                         joint_point,
                         stroke_line.point_2,
                         (
-                            stroke_line.point_2.x - self.curve_size * 2.0,
-                            stroke_line.point_2.y - self.curve_size * 0.5,
+                            stroke_line.point_2.x - flick_size * 2.0,
+                            stroke_line.point_2.y - flick_size * 0.5,
                         ),
-                        EndType::new(1.0),
-                        EndType::new(0.0),
-                        stroke_line.color,
+                        shape(EndType::new(1.0), EndType::new(0.0)),
                     );
                 } else {
                     self.cd_draw_line(
                         polygons,
                         stroke_line.point_1,
                         stroke_line.point_2,
-                        stroke_line.head_shape,
-                        stroke_line.tail_shape,
-                        stroke_line.color,
+                        shape(stroke_line.head_shape, stroke_line.tail_shape),
                     );
                 }
             }
@@ -353,22 +466,18 @@ impl Gothic {
                         stroke_line.point_1,
                         stroke_line.point_2,
                         joint_point,
-                        stroke_line.head_shape,
-                        EndType::new(1.0),
-                        stroke_line.color,
+                        shape(stroke_line.head_shape, EndType::new(1.0)),
                     );
                     self.cd_draw_quadratic_bezier(
                         polygons,
                         joint_point,
                         stroke_line.point_3,
                         (
-                            stroke_line.point_3.x - self.curve_size * 2.0,
-                            stroke_line.point_3.y - self.curve_size * 0.5,
+                            stroke_line.point_3.x - flick_size * 2.0,
+                            stroke_line.point_3.y - flick_size * 0.5,
                             false,
                         ),
-                        EndType::new(1.0),
-                        EndType::new(0.0),
-                        stroke_line.color,
+                        shape(EndType::new(1.0), EndType::new(0.0)),
                     );
                 } else if matches!(&stroke_line.tail_shape.kind, EndKind::RightUpwardFlick)
                     && stroke_line.tail_shape.opt == 0
@@ -388,18 +497,14 @@ impl Gothic {
                         stroke_line.point_1,
                         stroke_line.point_2,
                         stroke_line.point_3,
-                        stroke_line.head_shape,
-                        EndType::new(1.0),
-                        stroke_line.color,
+                        shape(stroke_line.head_shape, EndType::new(1.0)),
                     );
                     self.cd_draw_quadratic_bezier(
                         polygons,
                         stroke_line.point_3,
                         flick_control_point,
                         flick_end_point,
-                        EndType::new(1.0),
-                        EndType::new(0.0),
-                        stroke_line.color,
+                        shape(EndType::new(1.0), EndType::new(0.0)),
                     );
                 } else {
                     self.cd_draw_quadratic_bezier(
@@ -407,9 +512,7 @@ impl Gothic {
                         stroke_line.point_1,
                         stroke_line.point_2,
                         stroke_line.point_3,
-                        stroke_line.head_shape,
-                        stroke_line.tail_shape,
-                        stroke_line.color,
+                        shape(stroke_line.head_shape, stroke_line.tail_shape),
                     );
                 }
             }
@@ -436,18 +539,14 @@ impl Gothic {
                     polygons,
                     stroke_line.point_1,
                     joint_point_1,
-                    stroke_line.head_shape,
-                    EndType::new(1.0),
-                    stroke_line.color,
+                    shape(stroke_line.head_shape, EndType::new(1.0)),
                 );
                 self.cd_draw_quadratic_bezier(
                     polygons,
                     joint_point_1,
                     stroke_line.point_2,
                     joint_point_2,
-                    EndType::new(1.0),
-                    EndType::new(1.0),
-                    stroke_line.color,
+                    shape(EndType::new(1.0), EndType::new(1.0)),
                 );
 
                 if matches!(&stroke_line.tail_shape.kind, EndKind::RightUpwardFlick)
@@ -466,27 +565,21 @@ impl Gothic {
                         polygons,
                         joint_point_2,
                         joint_point_3,
-                        EndType::new(1.0),
-                        EndType::new(1.0),
-                        stroke_line.color,
+                        shape(EndType::new(1.0), EndType::new(1.0)),
                     );
                     self.cd_draw_quadratic_bezier(
                         polygons,
                         joint_point_3,
                         stroke_line.point_3,
                         flick_end_point,
-                        EndType::new(1.0),
-                        EndType::new(0.0),
-                        stroke_line.color,
+                        shape(EndType::new(1.0), EndType::new(0.0)),
                     );
                 } else {
                     self.cd_draw_line(
                         polygons,
                         joint_point_2,
                         stroke_line.point_3,
-                        EndType::new(1.0),
-                        stroke_line.tail_shape,
-                        stroke_line.color,
+                        shape(EndType::new(1.0), stroke_line.tail_shape),
                     );
                 }
             }
@@ -523,18 +616,14 @@ impl Gothic {
                     polygons,
                     stroke_line.point_1,
                     joint_point_1,
-                    stroke_line.head_shape,
-                    EndType::new(1.0),
-                    stroke_line.color,
+                    shape(stroke_line.head_shape, EndType::new(1.0)),
                 );
                 self.cd_draw_quadratic_bezier(
                     polygons,
                     joint_point_1,
                     stroke_line.point_2,
                     joint_point_2,
-                    EndType::new(1.0),
-                    EndType::new(1.0),
-                    stroke_line.color,
+                    shape(EndType::new(1.0), EndType::new(1.0)),
                 );
 
                 if matches!(&stroke_line.tail_shape.kind, EndKind::RightUpwardFlick)
@@ -553,27 +642,21 @@ impl Gothic {
                         polygons,
                         joint_point_2,
                         joint_point_3,
-                        EndType::new(1.0),
-                        EndType::new(1.0),
-                        stroke_line.color,
+                        shape(EndType::new(1.0), EndType::new(1.0)),
                     );
                     self.cd_draw_quadratic_bezier(
                         polygons,
                         joint_point_3,
                         stroke_line.point_3,
                         flick_end_point,
-                        EndType::new(1.0),
-                        EndType::new(0.0),
-                        stroke_line.color,
+                        shape(EndType::new(1.0), EndType::new(0.0)),
                     );
                 } else {
                     self.cd_draw_line(
                         polygons,
                         joint_point_2,
                         stroke_line.point_3,
-                        EndType::new(1.0),
-                        stroke_line.tail_shape,
-                        stroke_line.color,
+                        shape(EndType::new(1.0), stroke_line.tail_shape),
                     );
                 }
             }
@@ -594,22 +677,18 @@ impl Gothic {
                         stroke_line.point_2,
                         stroke_line.point_3,
                         joint_point,
-                        stroke_line.head_shape,
-                        EndType::new(1.0),
-                        stroke_line.color,
+                        shape(stroke_line.head_shape, EndType::new(1.0)),
                     );
                     self.cd_draw_quadratic_bezier(
                         polygons,
                         joint_point,
                         stroke_line.point_4,
                         (
-                            stroke_line.point_4.x - self.curve_size * 2.0,
-                            stroke_line.point_4.y - self.curve_size * 0.5,
+                            stroke_line.point_4.x - flick_size * 2.0,
+                            stroke_line.point_4.y - flick_size * 0.5,
                             false,
                         ),
-                        EndType::new(1.0),
-                        EndType::new(0.0),
-                        stroke_line.color,
+                        shape(EndType::new(1.0), EndType::new(0.0)),
                     );
                 } else if matches!(&stroke_line.tail_shape.kind, EndKind::RightUpwardFlick)
                     && stroke_line.tail_shape.opt == 0
@@ -629,18 +708,14 @@ impl Gothic {
                         stroke_line.point_2,
                         stroke_line.point_3,
                         joint_point,
-                        stroke_line.head_shape,
-                        EndType::new(1.0),
-                        stroke_line.color,
+                        shape(stroke_line.head_shape, EndType::new(1.0)),
                     );
                     self.cd_draw_quadratic_bezier(
                         polygons,
                         joint_point,
                         stroke_line.point_4,
                         flick_end_point,
-                        EndType::new(1.0),
-                        EndType::new(0.0),
-                        stroke_line.color,
+                        shape(EndType::new(1.0), EndType::new(0.0)),
                     );
                 } else {
                     self.cd_draw_cubic_bezier(
@@ -649,9 +724,7 @@ impl Gothic {
                         stroke_line.point_2,
                         stroke_line.point_3,
                         stroke_line.point_4,
-                        stroke_line.head_shape,
-                        stroke_line.tail_shape,
-                        stroke_line.color,
+                        shape(stroke_line.head_shape, stroke_line.tail_shape),
                     );
                 }
             }
@@ -660,18 +733,14 @@ impl Gothic {
                     polygons,
                     stroke_line.point_1,
                     stroke_line.point_2,
-                    stroke_line.head_shape,
-                    EndType::new(1.0),
-                    stroke_line.color,
+                    shape(stroke_line.head_shape, EndType::new(1.0)),
                 );
                 self.cd_draw_quadratic_bezier(
                     polygons,
                     stroke_line.point_2,
                     stroke_line.point_3,
                     stroke_line.point_4,
-                    EndType::new(1.0),
-                    stroke_line.tail_shape,
-                    stroke_line.color,
+                    shape(EndType::new(1.0), stroke_line.tail_shape),
                 );
             }
             // This arm should be reinterpretated as `Line::Unknown` in previous steps.
@@ -679,3 +748,48 @@ impl Gothic {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{Typeface, kage::Kage, polygons::Polygons};
+
+    fn draw(use_curve: bool, data: &str) -> Polygons {
+        let kage = Kage::new(Typeface::Gothic, use_curve);
+        let mut polygons = Polygons::new();
+        kage.make_glyph_with_data(&mut polygons, data);
+        polygons
+    }
+
+    #[test]
+    fn test_use_curve() {
+        let data = "2:0:7:100:30:60:100:20:170";
+
+        let sampled = draw(false, data);
+        assert_eq!(sampled.len(), 1);
+        assert_eq!(sampled.array().next().unwrap().len(), 22);
+
+        let fitted = draw(true, data);
+        assert_eq!(fitted.len(), 1);
+        let polygon = fitted.array().next().unwrap();
+        assert_eq!(polygon.len(), 10);
+        assert_eq!(
+            polygon
+                .points()
+                .iter()
+                .filter(|point| point.is_off_curve())
+                .count(),
+            4
+        );
+    }
+
+    #[test]
+    fn test_use_curve_straight_lines() {
+        // straight strokes are drawn the same either way
+        let data = "1:0:0:20:100:180:100$1:12:13:100:20:100:180";
+
+        assert_eq!(
+            draw(false, data).generate_svg(false),
+            draw(true, data).generate_svg(false)
+        );
+    }
+}
@@ -13,6 +13,12 @@ use crate::{
     utils::{Point, Rgb, Vector, is_quadratic, normalize},
 };
 
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
 pub struct Ming {
     /// must divide 1000
     pub sample_step: usize,
@@ -77,6 +83,12 @@ pub struct Ming {
     pub k_adjust_curve_step: f64,
 }
 
+impl Default for Ming {
+    fn default() -> Self {
+        Self::new(false)
+    }
+}
+
 impl Ming {
     pub fn new(use_curve: bool) -> Self {
         Self {
@@ -106,6 +118,12 @@ impl Ming {
     }
 }
 
+/// Returns the entry of an adjustment table at `level`, or its last entry
+/// for the levels written in the glyph data beyond the table.
+fn table_entry(table: &[f64], level: usize) -> f64 {
+    table.get(level).or(table.last()).copied().unwrap_or(0.0)
+}
+
 impl Ming {
     fn draw_curve_body(
         &self,
@@ -297,18 +315,26 @@ impl Ming {
                 polygon_1.floor();
                 polygon_2.floor();
 
+                let mut cut = false;
                 for index in 0..polygon_2.len() - 1 {
                     let point_1 = polygon_2.get(index).unwrap();
                     let point_2 = polygon_2.get(index + 1).unwrap();
 
                     if point_1.y <= start_point.y && start_point.y <= point_2.y {
-                        let new_x_1 = point_2.x
-                            + (point_1.x - point_2.x) * (start_point.y - point_2.y)
-                                / (point_1.y - point_2.y);
+                        // a horizontal edge is cut at its end
+                        let new_x_1 = if point_1.y == point_2.y {
+                            point_2.x
+                        } else {
+                            point_2.x
+                                + (point_1.x - point_2.x) * (start_point.y - point_2.y)
+                                    / (point_1.y - point_2.y)
+                        };
                         let new_y_1 = start_point.y;
                         let point_3 = polygon_1.get(0).unwrap();
                         let point_4 = polygon_1.get(1).unwrap();
-                        let new_x_2 = if (matches!(head_shape.kind, EndKind::VerticalConnection)
+                        let new_x_2 = if point_3.y == point_4.y {
+                            point_3.x
+                        } else if (matches!(head_shape.kind, EndKind::VerticalConnection)
                             && head_shape.opt_1 == 1)
                         {
                             point_3.x
@@ -327,15 +353,31 @@ impl Ming {
                             start_point.y + 1.0
                         };
 
-                        for _ in 0..index {
-                            polygon_2.shift();
+                        // where the edge is cut depends on the width, so
+                        // compatible outlines keep the cut points in place
+                        if polygons.is_compatible() {
+                            for cut_index in 0..index {
+                                polygon_2
+                                    .set(cut_index, new_x_1, new_y_1, Some(false))
+                                    .unwrap();
+                            }
+                        } else {
+                            for _ in 0..index {
+                                polygon_2.shift();
+                            }
                         }
 
                         polygon_2.set(0, new_x_1, new_y_1, Some(false)).unwrap();
                         polygon_1.unshift(new_x_2, new_y_2, Some(false));
+                        cut = true;
                         break;
                     }
                 }
+
+                if !cut && polygons.is_compatible() {
+                    let first = polygon_1.get(0).unwrap();
+                    polygon_1.unshift_point(first);
+                }
             }
 
             polygon_2.reverse();
@@ -1118,10 +1160,7 @@ impl Ming {
                             1,
                             pen_2.get_point(
                                 min_width_vertical,
-                                *self
-                                    .adjust_foot_left
-                                    .get(foot_adjustment)
-                                    .unwrap_or(&f64::NAN),
+                                table_entry(&self.adjust_foot_left, foot_adjustment),
                                 false,
                             ),
                         )
@@ -1131,9 +1170,7 @@ impl Ming {
                             2,
                             pen_2.get_point(
                                 -min_width_vertical,
-                                self.adjust_foot_left
-                                    .get(foot_adjustment)
-                                    .unwrap_or(&f64::NAN)
+                                table_entry(&self.adjust_foot_left, foot_adjustment)
                                     + min_width_vertical,
                                 false,
                             ),
@@ -1146,10 +1183,7 @@ impl Ming {
                             1,
                             pen_2.get_point(
                                 min_width_vertical,
-                                *self
-                                    .adjust_foot_right
-                                    .get(foot_adjustment)
-                                    .unwrap_or(&f64::NAN),
+                                table_entry(&self.adjust_foot_right, foot_adjustment),
                                 false,
                             ),
                         )
@@ -1159,9 +1193,7 @@ impl Ming {
                             2,
                             pen_2.get_point(
                                 -min_width_vertical,
-                                self.adjust_foot_right
-                                    .get(foot_adjustment)
-                                    .unwrap_or(&f64::NAN)
+                                table_entry(&self.adjust_foot_right, foot_adjustment)
                                     + min_width_vertical,
                                 false,
                             ),
@@ -1540,10 +1572,7 @@ impl Ming {
                         &[
                             (0.0, -self.min_width_horizontal, false),
                             (
-                                -self
-                                    .k_adjust_triangle_x
-                                    .get(triangle_adjustment)
-                                    .unwrap_or(&f64::NAN)
+                                -table_entry(&self.k_adjust_triangle_x, triangle_adjustment)
                                     * triangle_scale,
                                 0.0,
                                 false,
@@ -1554,18 +1583,12 @@ impl Ming {
                     polygon_2.push(
                         end_point.x
                             - (cos_radian - sin_radian)
-                                * self
-                                    .k_adjust_triangle_x
-                                    .get(triangle_adjustment)
-                                    .unwrap_or(&f64::NAN)
+                                * table_entry(&self.k_adjust_triangle_x, triangle_adjustment)
                                 * triangle_scale
                                 / 2.0,
                         end_point.y
                             - (sin_radian + cos_radian)
-                                * self
-                                    .k_adjust_triangle_y
-                                    .get(triangle_adjustment)
-                                    .unwrap_or(&f64::NAN)
+                                * table_entry(&self.k_adjust_triangle_y, triangle_adjustment)
                                 * triangle_scale,
                         Some(false),
                     );
@@ -1762,13 +1785,15 @@ impl Ming {
                     stroke_line.color,
                 );
 
-                let should_skip_tail =
-                    matches!(stroke_line.tail_shape.kind, EndKind::RightUpwardFlick)
-                        && stroke_line.tail_shape.opt_1 == 0
-                        && !((stroke_line.point_2.x < stroke_line.point_3.x
-                            && stroke_line.point_3.x - joint_point_2.x > 0.0)
-                            || (stroke_line.point_2.x > stroke_line.point_3.x
-                                && joint_point_2.x - stroke_line.point_3.x > 0.0));
+                // whether the tail fits depends on the curve size, so
+                // compatible outlines always draw it
+                let should_skip_tail = !polygons.is_compatible()
+                    && matches!(stroke_line.tail_shape.kind, EndKind::RightUpwardFlick)
+                    && stroke_line.tail_shape.opt_1 == 0
+                    && !((stroke_line.point_2.x < stroke_line.point_3.x
+                        && stroke_line.point_3.x - joint_point_2.x > 0.0)
+                        || (stroke_line.point_2.x > stroke_line.point_3.x
+                            && joint_point_2.x - stroke_line.point_3.x > 0.0));
 
                 if !should_skip_tail {
                     // TODO: opt_2 seems unnecessary?
@@ -1,45 +1,131 @@
+use core::{ops::Range, ptr};
+
 use crate::{
-    font::stroke_adjustment::StrokeAdjustmentTrait,
+    font::{
+        parameters::{ParameterError, Parameters},
+        renderer::StrokeRenderer,
+        stroke_adjustment::AdjustedStroke,
+    },
     line::{
         Line,
         special_line::{SpecialLineType, TransformType},
     },
     polygon::Polygon,
     polygons::Polygons,
+    skeleton::SkeletonOptions,
     utils::Point,
 };
 
 pub mod gothic;
 pub mod ming;
+pub mod parameters;
+pub mod renderer;
+pub mod rounded_gothic;
 pub mod stroke_adjustment;
+pub mod textbook;
+pub mod weight;
 
 pub enum Typeface {
     /// https://en.wikipedia.org/wiki/Ming_typefaces
     Ming,
     /// https://en.wikipedia.org/wiki/East_Asian_Gothic_typeface
     Gothic,
+    /// Gothic with rounded stroke ends and corners (丸ゴシック)
+    RoundedGothic,
+    /// Brush strokes of varying width, as in school textbooks (教科書体),
+    /// always drawn as polylines
+    Textbook,
 }
 
 pub enum FontWrapper {
     MingStyle(ming::Ming),
     GothicStyle(gothic::Gothic),
+    RoundedGothicStyle(rounded_gothic::RoundedGothic),
+    TextbookStyle(textbook::Textbook),
+    /// A style implemented outside of the crate.
+    Custom(Box<dyn StrokeRenderer>),
 }
 
 impl FontWrapper {
     pub(crate) fn new(typeface: Typeface, use_curve: bool) -> Self {
         match typeface {
             Typeface::Ming => Self::MingStyle(ming::Ming::new(use_curve)),
-            Typeface::Gothic => Self::GothicStyle(gothic::Gothic::new()),
+            Typeface::Gothic => Self::GothicStyle(gothic::Gothic::new(use_curve)),
+            Typeface::RoundedGothic => {
+                Self::RoundedGothicStyle(rounded_gothic::RoundedGothic::new(use_curve))
+            }
+            Typeface::Textbook => Self::TextbookStyle(textbook::Textbook::new()),
         }
     }
 
-    pub(crate) fn set_use_curve(&mut self, use_curve: bool) {
+    /// Builds the typeface described by the parameters, once validated.
+    pub(crate) fn from_parameters(parameters: Parameters) -> Result<Self, ParameterError> {
+        parameters.validate()?;
+
+        Ok(match parameters {
+            Parameters::Ming(ming) => Self::MingStyle(ming),
+            Parameters::Gothic(gothic) => Self::GothicStyle(gothic),
+            Parameters::RoundedGothic(gothic) => {
+                Self::RoundedGothicStyle(rounded_gothic::RoundedGothic { gothic })
+            }
+            Parameters::Textbook(textbook) => Self::TextbookStyle(textbook),
+        })
+    }
+
+    /// Returns the parameters of a built-in typeface.
+    pub fn parameters(&self) -> Option<Parameters> {
         match self {
-            FontWrapper::MingStyle(ming) => ming.use_curve = use_curve,
-            FontWrapper::GothicStyle(_) => {}
+            FontWrapper::MingStyle(ming) => Some(Parameters::Ming(ming.clone())),
+            FontWrapper::GothicStyle(gothic) => Some(Parameters::Gothic(gothic.clone())),
+            FontWrapper::RoundedGothicStyle(rounded_gothic) => {
+                Some(Parameters::RoundedGothic(rounded_gothic.gothic.clone()))
+            }
+            FontWrapper::TextbookStyle(textbook) => Some(Parameters::Textbook(textbook.clone())),
+            FontWrapper::Custom(_) => None,
         }
     }
 
+    pub fn renderer(&self) -> &dyn StrokeRenderer {
+        match self {
+            FontWrapper::MingStyle(ming) => ming,
+            FontWrapper::GothicStyle(gothic) => gothic,
+            FontWrapper::RoundedGothicStyle(rounded_gothic) => rounded_gothic,
+            FontWrapper::TextbookStyle(textbook) => textbook,
+            FontWrapper::Custom(renderer) => renderer.as_ref(),
+        }
+    }
+
+    pub fn renderer_mut(&mut self) -> &mut dyn StrokeRenderer {
+        match self {
+            FontWrapper::MingStyle(ming) => ming,
+            FontWrapper::GothicStyle(gothic) => gothic,
+            FontWrapper::RoundedGothicStyle(rounded_gothic) => rounded_gothic,
+            FontWrapper::TextbookStyle(textbook) => textbook,
+            FontWrapper::Custom(renderer) => renderer.as_mut(),
+        }
+    }
+
+    pub(crate) fn use_curve(&self) -> bool {
+        self.renderer().use_curve()
+    }
+
+    pub(crate) fn set_use_curve(&mut self, use_curve: bool) {
+        self.renderer_mut().set_use_curve(use_curve);
+    }
+
+    /// Returns the options to trace the centre lines of the strokes with,
+    /// rounding bends as the typeface does.
+    pub(crate) fn skeleton_options(&self) -> SkeletonOptions {
+        let curve_size = match self {
+            FontWrapper::MingStyle(ming) => ming.curve_size,
+            FontWrapper::GothicStyle(gothic) => gothic.curve_size,
+            FontWrapper::RoundedGothicStyle(rounded_gothic) => rounded_gothic.gothic.curve_size,
+            FontWrapper::TextbookStyle(textbook) => textbook.curve_size,
+            FontWrapper::Custom(_) => return SkeletonOptions::new(),
+        };
+        SkeletonOptions::new().curve_size(curve_size)
+    }
+
     fn select_polygons_rect<P1, P2>(
         polygons: &mut Polygons,
         box_diag_1: P1,
@@ -65,120 +151,121 @@ impl FontWrapper {
             .collect()
     }
 
-    pub(crate) fn df_transform(&self, polygons: &mut Polygons, line_type: SpecialLineType) {
+    pub(crate) fn df_transform(polygons: &mut Polygons, line_type: SpecialLineType) {
+        let floor_points = polygons.floors_points();
         let polygon_vec =
             Self::select_polygons_rect(polygons, line_type.box_diag_1, line_type.box_diag_2);
 
-        match line_type.transform_type {
+        let box_diag_1 = line_type.box_diag_1;
+        let box_diag_2 = line_type.box_diag_2;
+        let (transform, [dx, dy]): (fn(&mut Polygon) -> &mut Polygon, _) = match line_type
+            .transform_type
+        {
             TransformType::HorizontalFlip => {
-                let [dx, dy] = [line_type.box_diag_1.x + line_type.box_diag_2.x, 0.0];
-                for polygon in polygon_vec {
-                    polygon.reflect_x().translate(dx, dy).floor();
-                }
-            }
-            TransformType::VerticalFlip => {
-                let [dx, dy] = [0.0, line_type.box_diag_1.y + line_type.box_diag_2.y];
-                for polygon in polygon_vec {
-                    polygon.reflect_y().translate(dx, dy).floor();
-                }
-            }
-            TransformType::Rotate90 => {
-                let [dx, dy] = [
-                    line_type.box_diag_1.x + line_type.box_diag_2.y,
-                    line_type.box_diag_1.y - line_type.box_diag_1.x,
-                ];
-                for polygon in polygon_vec {
-                    polygon.rotate_90().translate(dx, dy).floor();
-                }
+                (Polygon::reflect_x, [box_diag_1.x + box_diag_2.x, 0.0])
             }
-            TransformType::Rotate180 => {
-                let [dx, dy] = [
-                    line_type.box_diag_1.x + line_type.box_diag_2.x,
-                    line_type.box_diag_1.y + line_type.box_diag_2.y,
-                ];
-                for polygon in polygon_vec {
-                    polygon.rotate_180().translate(dx, dy).floor();
-                }
+            TransformType::VerticalFlip => (Polygon::reflect_y, [0.0, box_diag_1.y + box_diag_2.y]),
+            TransformType::Rotate90 => (
+                Polygon::rotate_90,
+                [box_diag_1.x + box_diag_2.y, box_diag_1.y - box_diag_1.x],
+            ),
+            TransformType::Rotate180 => (
+                Polygon::rotate_180,
+                [box_diag_1.x + box_diag_2.x, box_diag_1.y + box_diag_2.y],
+            ),
+            TransformType::Rotate270 => (
+                Polygon::rotate_270,
+                [box_diag_1.x - box_diag_1.y, box_diag_2.y + box_diag_1.x],
+            ),
+        };
+
+        for polygon in polygon_vec {
+            let mut transformed = polygon.clone();
+            transform(&mut transformed).translate(dx, dy);
+            if floor_points {
+                transformed.floor();
             }
-            TransformType::Rotate270 => {
-                let [dx, dy] = [
-                    line_type.box_diag_1.x - line_type.box_diag_1.y,
-                    line_type.box_diag_2.y + line_type.box_diag_1.x,
-                ];
-                for polygon in polygon_vec {
-                    polygon.rotate_270().translate(dx, dy).floor();
-                }
+            // a box of huge or infinite coordinates moves the points to
+            // infinity, in which case the polygon is left as it was
+            if transformed
+                .points()
+                .iter()
+                .all(|point| point.x.is_finite() && point.y.is_finite())
+            {
+                *polygon = transformed;
             }
         }
     }
 
-    pub(crate) fn get_drawers<'a>(
-        &'a self,
-        lines: &'a Vec<Line<'a>>,
-    ) -> Vec<Box<dyn Fn(&mut Polygons) + 'a>> {
-        match self {
-            FontWrapper::MingStyle(ming) => {
-                let stroke_refs: Vec<_> = lines
-                    .iter()
-                    .filter_map(|each| match each {
-                        Line::StrokeLine(line_type) => Some(line_type),
-                        _ => None,
+    /// Draws the lines in order: the strokes, adjusted to each other, and
+    /// the transformations of the special lines.
+    pub(crate) fn draw_lines(&self, polygons: &mut Polygons, lines: &[Line]) {
+        let adjustments = self.adjust_strokes(lines);
+        self.draw_lines_with_adjustments(polygons, lines, &adjustments, |_, _| {});
+    }
+
+    /// Returns the adjustment of every stroke line, in order.
+    pub(crate) fn adjust_strokes(&self, lines: &[Line]) -> Vec<AdjustedStroke> {
+        let stroke_refs: Vec<_> = lines
+            .iter()
+            .filter_map(|each| match each {
+                Line::StrokeLine(line_type) => Some(line_type),
+                _ => None,
+            })
+            .collect();
+
+        // a renderer may leave strokes out or reorder them: each stroke gets
+        // its own adjustment, or the one written in the glyph data
+        let adjusted = self.renderer().adjust_strokes(&stroke_refs);
+        stroke_refs
+            .iter()
+            .enumerate()
+            .map(|(index, &stroke)| {
+                adjusted
+                    .get(index)
+                    .filter(|(adjusted_stroke, _)| ptr::eq(*adjusted_stroke, stroke))
+                    .or_else(|| {
+                        adjusted
+                            .iter()
+                            .find(|(adjusted_stroke, _)| ptr::eq(*adjusted_stroke, stroke))
                     })
-                    .collect();
-                let stroke_adjustment_vec = ming.adjust_strokes(&stroke_refs);
-                let mut stroke_adjustment_iter = stroke_adjustment_vec
-                    .iter()
-                    .map(|(_, adjusted_stroke)| *adjusted_stroke);
-
-                let mut drawers: Vec<Box<dyn Fn(&mut Polygons) + 'a>> =
-                    Vec::with_capacity(lines.len());
-
-                for line in lines {
-                    match line {
-                        Line::SpecialLine(special_line_type) => {
-                            let special_line_type = *special_line_type;
-                            drawers.push(Box::new(move |polygons: &mut Polygons| {
-                                self.df_transform(polygons, special_line_type)
-                            }));
-                        }
-                        Line::StrokeLine(stroke_line_type) => {
-                            let adjusted_stroke = stroke_adjustment_iter
-                                .next()
-                                .expect("stroke adjustment missing");
-                            let stroke_line_type = *stroke_line_type;
-                            drawers.push(Box::new(move |polygons: &mut Polygons| {
-                                ming.df_draw_font(polygons, stroke_line_type, adjusted_stroke)
-                            }));
-                        }
-                        Line::ComponentReferenceLine(_) | Line::Unknown => {}
-                    }
-                }
+                    .map_or_else(
+                        || AdjustedStroke::from_stroke(stroke),
+                        |(_, adjusted)| *adjusted,
+                    )
+            })
+            .collect()
+    }
 
-                drawers
-            }
-            FontWrapper::GothicStyle(gothic) => {
-                let mut drawers: Vec<Box<dyn Fn(&mut Polygons) + 'a>> =
-                    Vec::with_capacity(lines.len());
-
-                for line in lines {
-                    match line {
-                        Line::SpecialLine(special_line_type) => {
-                            let special_line_type = *special_line_type;
-                            drawers.push(Box::new(move |polygons: &mut Polygons| {
-                                self.df_transform(polygons, special_line_type)
-                            }));
-                        }
-                        Line::StrokeLine(stroke_line_type) => {
-                            drawers.push(Box::new(move |polygons: &mut Polygons| {
-                                gothic.df_draw_font(polygons, *stroke_line_type)
-                            }));
-                        }
-                        Line::ComponentReferenceLine(_) | Line::Unknown => {}
-                    }
-                }
+    /// Like [`FontWrapper::draw_lines`], with the adjustment of each stroke
+    /// line given, e.g. computed by another font. `drawn` is called after
+    /// each line with the range of `polygons` it added.
+    pub(crate) fn draw_lines_with_adjustments(
+        &self,
+        polygons: &mut Polygons,
+        lines: &[Line],
+        adjustments: &[AdjustedStroke],
+        mut drawn: impl FnMut(&Line, Range<usize>),
+    ) {
+        let renderer = self.renderer();
+        let mut stroke_adjustment_iter = adjustments.iter();
 
-                drawers
+        for line in lines {
+            let start = polygons.len();
+            match line {
+                Line::SpecialLine(special_line_type) => {
+                    renderer.transform(polygons, *special_line_type);
+                }
+                Line::StrokeLine(stroke_line_type) => {
+                    let adjusted_stroke = stroke_adjustment_iter
+                        .next()
+                        .copied()
+                        .unwrap_or_else(|| AdjustedStroke::from_stroke(stroke_line_type));
+                    renderer.draw_stroke(polygons, *stroke_line_type, adjusted_stroke);
+                }
+                Line::ComponentReferenceLine(_) | Line::Unknown => continue,
             }
+            drawn(line, start..polygons.len());
         }
     }
 }
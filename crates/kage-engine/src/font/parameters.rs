@@ -0,0 +1,362 @@
+use core::fmt;
+
+use crate::font::{gothic::Gothic, ming::Ming, rounded_gothic::RoundedGothic, textbook::Textbook};
+
+/// Tuning parameters of a built-in typeface, e.g. a house weight loaded
+/// from a configuration file.
+///
+/// With the `serde` feature the parameters can be serialized; the typeface
+/// is stored in a `typeface` field next to them, and missing parameters
+/// take their default value:
+///
+/// ```json
+/// { "typeface": "gothic", "width": 6.0, "foot_size": 2.5 }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(tag = "typeface", rename_all = "kebab-case")
+)]
+// Parameters are built once per typeface or master and moved around as
+// rarely as the `FontWrapper` they turn into, which holds a `Ming` unboxed
+// too; boxing it would only make them clumsier to build and match.
+#[allow(clippy::large_enum_variant)]
+pub enum Parameters {
+    Ming(Ming),
+    Gothic(Gothic),
+    /// Pen and curve parameters of [`RoundedGothic`].
+    RoundedGothic(Gothic),
+    Textbook(Textbook),
+}
+
+impl Parameters {
+    /// Checks that the parameters can be drawn with.
+    pub fn validate(&self) -> Result<(), ParameterError> {
+        match self {
+            Parameters::Ming(ming) => ming.validate(),
+            Parameters::Gothic(gothic) | Parameters::RoundedGothic(gothic) => gothic.validate(),
+            Parameters::Textbook(textbook) => textbook.validate(),
+        }
+    }
+
+    pub(crate) fn set_sample_step(&mut self, sample_step: usize) {
+        match self {
+            Parameters::Ming(ming) => ming.sample_step = sample_step,
+            Parameters::Gothic(gothic) | Parameters::RoundedGothic(gothic) => {
+                gothic.sample_step = sample_step
+            }
+            Parameters::Textbook(textbook) => textbook.sample_step = sample_step,
+        }
+    }
+
+    pub(crate) fn sample_step(&self) -> usize {
+        match self {
+            Parameters::Ming(ming) => ming.sample_step,
+            Parameters::Gothic(gothic) | Parameters::RoundedGothic(gothic) => gothic.sample_step,
+            Parameters::Textbook(textbook) => textbook.sample_step,
+        }
+    }
+}
+
+impl From<RoundedGothic> for Parameters {
+    fn from(rounded_gothic: RoundedGothic) -> Self {
+        Parameters::RoundedGothic(rounded_gothic.gothic)
+    }
+}
+
+/// Why a set of [`Parameters`] cannot be drawn with.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParameterError {
+    /// `sample_step` must be a positive divisor of 1000.
+    SampleStep(usize),
+    /// `k_adjust_foot_step` must be 3.
+    FootStep(f64),
+    /// The parameter must be a finite number in the given range.
+    OutOfRange {
+        name: &'static str,
+        value: f64,
+        min: f64,
+        max: f64,
+    },
+    /// The parameter is a number of adjustment levels and must be a whole
+    /// number.
+    NotAnInteger { name: &'static str, value: f64 },
+    /// The table has fewer entries than adjustment levels.
+    TooShort {
+        name: &'static str,
+        expected: usize,
+        found: usize,
+    },
+}
+
+impl fmt::Display for ParameterError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParameterError::SampleStep(value) => {
+                write!(f, "sample_step must divide 1000, got {value}")
+            }
+            ParameterError::FootStep(value) => {
+                write!(f, "k_adjust_foot_step must be 3, got {value}")
+            }
+            ParameterError::OutOfRange {
+                name,
+                value,
+                min,
+                max,
+            } => write!(f, "{name} must be between {min} and {max}, got {value}"),
+            ParameterError::NotAnInteger { name, value } => {
+                write!(f, "{name} must be a whole number, got {value}")
+            }
+            ParameterError::TooShort {
+                name,
+                expected,
+                found,
+            } => write!(f, "{name} needs {expected} entries, got {found}"),
+        }
+    }
+}
+
+impl core::error::Error for ParameterError {}
+
+fn check_sample_step(sample_step: usize) -> Result<(), ParameterError> {
+    if sample_step == 0 || 1000 % sample_step != 0 {
+        return Err(ParameterError::SampleStep(sample_step));
+    }
+    Ok(())
+}
+
+fn check_range(name: &'static str, value: f64, min: f64, max: f64) -> Result<(), ParameterError> {
+    if !(min..=max).contains(&value) {
+        return Err(ParameterError::OutOfRange {
+            name,
+            value,
+            min,
+            max,
+        });
+    }
+    Ok(())
+}
+
+/// Checks a width or a length.
+fn check_length(name: &'static str, value: f64) -> Result<(), ParameterError> {
+    check_range(name, value, 0.0, f64::MAX)
+}
+
+/// Checks a number of adjustment levels and returns it.
+fn check_levels(name: &'static str, value: f64) -> Result<usize, ParameterError> {
+    check_length(name, value)?;
+    if value.fract() != 0.0 {
+        return Err(ParameterError::NotAnInteger { name, value });
+    }
+    Ok(value as usize)
+}
+
+/// Checks a table with an entry for each adjustment level.
+fn check_table(name: &'static str, table: &[f64], expected: usize) -> Result<(), ParameterError> {
+    if table.len() < expected {
+        return Err(ParameterError::TooShort {
+            name,
+            expected,
+            found: table.len(),
+        });
+    }
+    for &value in table {
+        check_range(name, value, f64::MIN, f64::MAX)?;
+    }
+    Ok(())
+}
+
+fn check_foot(
+    k_adjust_foot_range_x: f64,
+    k_adjust_foot_range_y: &[f64],
+    k_adjust_foot_step: f64,
+) -> Result<(), ParameterError> {
+    if k_adjust_foot_step != 3.0 {
+        return Err(ParameterError::FootStep(k_adjust_foot_step));
+    }
+    check_length("k_adjust_foot_range_x", k_adjust_foot_range_x)?;
+    check_table("k_adjust_foot_range_y", k_adjust_foot_range_y, 4)
+}
+
+impl Ming {
+    pub fn validate(&self) -> Result<(), ParameterError> {
+        check_sample_step(self.sample_step)?;
+        check_length("min_width_horizontal", self.min_width_horizontal)?;
+        check_length("min_width_triangle", self.min_width_triangle)?;
+        check_range("min_width_vertical", self.min_width_vertical, 0.1, f64::MAX)?;
+        check_length("width", self.width)?;
+        check_length(
+            "right_sweep_end_scale_factor",
+            self.right_sweep_end_scale_factor,
+        )?;
+        check_length("curve_size", self.curve_size)?;
+
+        check_foot(
+            self.k_adjust_foot_range_x,
+            &self.k_adjust_foot_range_y,
+            self.k_adjust_foot_step,
+        )?;
+        // the last entry is for 左下zh用新
+        check_table("adjust_foot_left", &self.adjust_foot_left, 5)?;
+        check_table("adjust_foot_right", &self.adjust_foot_right, 4)?;
+
+        let length_steps = check_levels(
+            "k_adjust_triangle_length_step",
+            self.k_adjust_triangle_length_step,
+        )?;
+        let density_steps = check_levels("k_adjust_triangle2_step", self.k_adjust_triangle2_step)?;
+        let triangle_levels = length_steps.max(density_steps) + 1;
+        check_table(
+            "k_adjust_triangle_x",
+            &self.k_adjust_triangle_x,
+            triangle_levels,
+        )?;
+        check_table(
+            "k_adjust_triangle_y",
+            &self.k_adjust_triangle_y,
+            triangle_levels,
+        )?;
+        check_table(
+            "k_adjust_triangle_length",
+            &self.k_adjust_triangle_length,
+            length_steps,
+        )?;
+        check_table(
+            "k_adjust_triangle_line",
+            &self.k_adjust_triangle_line,
+            length_steps,
+        )?;
+        check_range(
+            "k_adjust_triangle2_length",
+            self.k_adjust_triangle2_length,
+            0.1,
+            f64::MAX,
+        )?;
+
+        check_levels("k_adjust_vertical_step", self.k_adjust_vertical_step)?;
+        if check_levels("k_adjust_curve_step", self.k_adjust_curve_step)? == 0 {
+            return Err(ParameterError::OutOfRange {
+                name: "k_adjust_curve_step",
+                value: self.k_adjust_curve_step,
+                min: 1.0,
+                max: f64::MAX,
+            });
+        }
+
+        Ok(())
+    }
+}
+
+impl Gothic {
+    pub fn validate(&self) -> Result<(), ParameterError> {
+        check_sample_step(self.sample_step)?;
+        check_length("width", self.width)?;
+        check_length("foot_size", self.foot_size)?;
+        check_length("curve_size", self.curve_size)?;
+        check_foot(
+            self.k_adjust_foot_range_x,
+            &self.k_adjust_foot_range_y,
+            self.k_adjust_foot_step,
+        )
+    }
+}
+
+impl Textbook {
+    pub fn validate(&self) -> Result<(), ParameterError> {
+        check_sample_step(self.sample_step)?;
+        check_length("width", self.width)?;
+        check_length("curve_size", self.curve_size)?;
+        check_range("taper_ratio", self.taper_ratio, 0.0, 1.0)?;
+        check_length("press", self.press)?;
+        check_range("entry_length", self.entry_length, 0.1, f64::MAX)?;
+        check_range("sweep_length", self.sweep_length, 0.1, f64::MAX)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::kage::Kage;
+
+    #[test]
+    fn test_validate() {
+        assert_eq!(Parameters::Ming(Ming::default()).validate(), Ok(()));
+        assert_eq!(Parameters::Gothic(Gothic::default()).validate(), Ok(()));
+        assert_eq!(Parameters::Textbook(Textbook::default()).validate(), Ok(()));
+
+        let ming = Ming {
+            sample_step: 30,
+            ..Ming::default()
+        };
+        assert_eq!(ming.validate(), Err(ParameterError::SampleStep(30)));
+
+        let ming = Ming {
+            k_adjust_foot_step: 4.0,
+            ..Ming::default()
+        };
+        assert_eq!(ming.validate(), Err(ParameterError::FootStep(4.0)));
+
+        let ming = Ming {
+            k_adjust_triangle_length_step: 4.0,
+            ..Ming::default()
+        };
+        assert_eq!(
+            ming.validate(),
+            Err(ParameterError::TooShort {
+                name: "k_adjust_triangle_x",
+                expected: 5,
+                found: 4
+            })
+        );
+
+        let gothic = Gothic {
+            width: f64::NAN,
+            ..Gothic::default()
+        };
+        assert!(matches!(
+            gothic.validate(),
+            Err(ParameterError::OutOfRange { name: "width", .. })
+        ));
+        assert_eq!(
+            ParameterError::SampleStep(30).to_string(),
+            "sample_step must divide 1000, got 30"
+        );
+    }
+
+    #[test]
+    fn test_from_parameters() {
+        let gothic = Gothic {
+            width: 8.0,
+            ..Gothic::default()
+        };
+        let kage = Kage::from_parameters(Parameters::Gothic(gothic.clone())).unwrap();
+        assert_eq!(kage.parameters(), Some(Parameters::Gothic(gothic)));
+
+        let mut ming = Ming::new(true);
+        ming.sample_step = 0;
+        assert!(Kage::from_parameters(Parameters::Ming(ming)).is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() {
+        let parameters: Parameters =
+            serde_json::from_str(r#"{ "typeface": "gothic", "width": 6.0 }"#).unwrap();
+        assert_eq!(
+            parameters,
+            Parameters::Gothic(Gothic {
+                width: 6.0,
+                ..Gothic::default()
+            })
+        );
+
+        let parameters = Parameters::Ming(Ming::default());
+        let json = serde_json::to_string(&parameters).unwrap();
+        assert!(json.starts_with(r#"{"typeface":"ming","sample_step":100,"#));
+        assert_eq!(
+            serde_json::from_str::<Parameters>(&json).unwrap(),
+            parameters
+        );
+    }
+}
@@ -0,0 +1,195 @@
+use crate::{
+    font::{
+        gothic::Gothic,
+        ming::Ming,
+        stroke_adjustment::{AdjustedStroke, StrokeAdjustmentTrait, initial_adjustments},
+    },
+    line::{special_line::SpecialLineType, stroke_line::StrokeLineType},
+    polygons::Polygons,
+};
+
+/// A typeface style: draws each stroke of a glyph into polygons.
+///
+/// Implement this trait to add a style outside of the crate and pass it
+/// to [`Kage::with_renderer`](crate::kage::Kage::with_renderer). Only
+/// [`StrokeRenderer::draw_stroke`] is required; the other methods default
+/// to the adjustments written in the glyph data ([`initial_adjustments`]),
+/// no curves and the standard special-line transforms
+/// ([`SpecialLineType::apply`]).
+///
+/// ```ignore
+/// struct Hairline;
+///
+/// impl StrokeRenderer for Hairline {
+///     fn draw_stroke(&self, polygons: &mut Polygons, stroke: StrokeLineType, _: AdjustedStroke) {
+///         let [start, end, ..] = stroke.points();
+///         polygons.push(Polygon::new(vec![start, end, end + 1.0, start + 1.0], stroke.color()));
+///     }
+/// }
+///
+/// let kage = Kage::with_renderer(Hairline);
+/// ```
+pub trait StrokeRenderer: Send + Sync {
+    /// Computes the adjustment of every stroke of a glyph, in order, e.g.
+    /// to shorten the ornaments that collide with other strokes.
+    ///
+    /// By default strokes only get the adjustments written in the glyph
+    /// data, as do the strokes left out of the result.
+    fn adjust_strokes<'a>(
+        &self,
+        strokes: &'a [&'a StrokeLineType],
+    ) -> Vec<(&'a StrokeLineType, AdjustedStroke)> {
+        initial_adjustments(strokes)
+    }
+
+    /// Draws one stroke with its adjustment.
+    fn draw_stroke(
+        &self,
+        polygons: &mut Polygons,
+        stroke: StrokeLineType,
+        adjustment: AdjustedStroke,
+    );
+
+    /// Applies a special line (flip or rotation of a box) to the polygons
+    /// drawn so far.
+    fn transform(&self, polygons: &mut Polygons, special_line: SpecialLineType) {
+        special_line.apply(polygons);
+    }
+
+    /// Whether curving strokes are drawn with quadratic Bézier curves.
+    fn use_curve(&self) -> bool {
+        false
+    }
+
+    fn set_use_curve(&mut self, _use_curve: bool) {}
+}
+
+impl StrokeRenderer for Ming {
+    fn adjust_strokes<'a>(
+        &self,
+        strokes: &'a [&'a StrokeLineType],
+    ) -> Vec<(&'a StrokeLineType, AdjustedStroke)> {
+        StrokeAdjustmentTrait::adjust_strokes(self, strokes)
+    }
+
+    fn draw_stroke(
+        &self,
+        polygons: &mut Polygons,
+        stroke: StrokeLineType,
+        adjustment: AdjustedStroke,
+    ) {
+        self.df_draw_font(polygons, stroke, adjustment);
+    }
+
+    fn use_curve(&self) -> bool {
+        self.use_curve
+    }
+
+    fn set_use_curve(&mut self, use_curve: bool) {
+        self.use_curve = use_curve;
+    }
+}
+
+impl StrokeRenderer for Gothic {
+    fn adjust_strokes<'a>(
+        &self,
+        strokes: &'a [&'a StrokeLineType],
+    ) -> Vec<(&'a StrokeLineType, AdjustedStroke)> {
+        StrokeAdjustmentTrait::adjust_strokes(self, strokes)
+    }
+
+    fn draw_stroke(
+        &self,
+        polygons: &mut Polygons,
+        stroke: StrokeLineType,
+        adjustment: AdjustedStroke,
+    ) {
+        self.df_draw_font(polygons, stroke, adjustment);
+    }
+
+    fn use_curve(&self) -> bool {
+        self.use_curve
+    }
+
+    fn set_use_curve(&mut self, use_curve: bool) {
+        self.use_curve = use_curve;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{Typeface, kage::Kage, polygon::Polygon};
+
+    /// Draws every stroke as a 2-unit wide bar between its first two points.
+    struct Bars;
+
+    impl StrokeRenderer for Bars {
+        fn draw_stroke(
+            &self,
+            polygons: &mut Polygons,
+            stroke: StrokeLineType,
+            _adjustment: AdjustedStroke,
+        ) {
+            let [start, end, ..] = stroke.points();
+            polygons.push(Polygon::new(
+                vec![
+                    (start.x(), start.y() - 1.0),
+                    (end.x(), end.y() - 1.0),
+                    (end.x(), end.y() + 1.0),
+                    (start.x(), start.y() + 1.0),
+                ],
+                stroke.color(),
+            ));
+        }
+    }
+
+    #[test]
+    fn test_custom_renderer() {
+        let mut kage = Kage::with_renderer(Bars);
+        kage.components.set("u4e00", "1:0:0:20:100:180:100");
+
+        let mut polygons = Polygons::new();
+        kage.make_glyph_with_component_name(&mut polygons, "u4e00");
+        assert_eq!(
+            polygons.array().next().unwrap().points(),
+            vec![
+                (20.0, 99.0).into(),
+                (180.0, 99.0).into(),
+                (180.0, 101.0).into(),
+                (20.0, 101.0).into()
+            ]
+        );
+
+        // special lines are applied by default
+        polygons.clear();
+        kage.make_glyph_with_data(&mut polygons, "1:0:0:20:100:180:100$0:98:0:0:0:200:200");
+        assert_eq!(
+            polygons.array().next().unwrap().points()[0],
+            (180.0, 99.0).into()
+        );
+
+        // curves are not supported
+        kage.set_use_curve(true);
+        assert!(!kage.renderer().use_curve());
+    }
+
+    #[test]
+    fn test_builtin_renderers() {
+        let mut kage = Kage::new(Typeface::Gothic, false);
+        kage.set_use_curve(true);
+        assert!(kage.renderer().use_curve());
+
+        let lines = kage.get_each_expanded_line("1:0:13:50:20:50:150$1:0:0:20:160:180:160");
+        let strokes: Vec<&StrokeLineType> = lines
+            .iter()
+            .filter_map(|line| match line {
+                crate::line::Line::StrokeLine(stroke) => Some(stroke),
+                _ => None,
+            })
+            .collect();
+        let adjusted = kage.renderer().adjust_strokes(&strokes);
+        assert_eq!(adjusted[0].1.foot_adjustment(), 3);
+        assert_eq!(Bars.adjust_strokes(&strokes)[0].1.foot_adjustment(), 0);
+    }
+}
@@ -0,0 +1,247 @@
+use core::f64::consts::PI;
+
+use crate::{
+    font::{
+        gothic::{Gothic, SegmentShape},
+        renderer::StrokeRenderer,
+        stroke_adjustment::AdjustedStroke,
+    },
+    line::stroke_line::{EndType, StrokeLineType},
+    polygon::Polygon,
+    polygons::Polygons,
+    skeleton::{SkeletonOptions, SkeletonSegment, stroke_skeleton},
+    utils::{Point, Rgb, Vector},
+};
+
+/// Number of arcs a cap is made of when curves are used.
+const CAP_ARCS: usize = 8;
+/// Number of sides of a cap when curves are not used.
+const CAP_SIDES: usize = 24;
+
+/// Maru gothic (丸ゴシック): uniform-width strokes like [`Gothic`], with
+/// round caps, round joins and round corners.
+///
+/// Every stroke is split into its centre line segments the way the Gothic
+/// font draws it, each segment is drawn with flat ends, and a disc the
+/// width of the stroke is put on both ends of the stroke and on every
+/// sharp joint. Corners and kakato are therefore rounded instead of
+/// squared, and free ends stick out by half the width of the stroke.
+pub struct RoundedGothic {
+    /// Pen and curve parameters. The kakato and adjustment parameters are
+    /// not used.
+    pub gothic: Gothic,
+}
+
+impl RoundedGothic {
+    pub fn new(use_curve: bool) -> Self {
+        Self {
+            gothic: Gothic::new(use_curve),
+        }
+    }
+
+    fn draw_segment(&self, polygons: &mut Polygons, segment: SkeletonSegment, color: Option<Rgb>) {
+        let free = EndType::new(0.0);
+        match segment {
+            SkeletonSegment::Line(start, end) => {
+                let shape = SegmentShape {
+                    head_shape: free,
+                    tail_shape: free,
+                    foot_length: 0.0,
+                    color,
+                };
+                self.gothic.cd_draw_line(polygons, start, end, shape);
+            }
+            SkeletonSegment::Quadratic(start, control, end) => {
+                self.gothic
+                    .draw_curve_body(polygons, start, control, control, end, color);
+            }
+            SkeletonSegment::Cubic(start, control_1, control_2, end) => {
+                self.gothic
+                    .draw_curve_body(polygons, start, control_1, control_2, end, color);
+            }
+        }
+    }
+
+    /// Draws a disc the width of the stroke centred on `center`.
+    fn draw_cap(&self, polygons: &mut Polygons, center: Point, color: Option<Rgb>) {
+        push_disc(
+            polygons,
+            center,
+            self.gothic.width,
+            self.gothic.use_curve,
+            color,
+        );
+    }
+}
+
+/// Pushes a disc, made of quadratic arcs when `use_curve` is set and of a
+/// regular polygon otherwise.
+pub(crate) fn push_disc(
+    polygons: &mut Polygons,
+    center: Point,
+    radius: f64,
+    use_curve: bool,
+    color: Option<Rgb>,
+) {
+    let at = |angle: f64, radius: f64, off_curve: bool| {
+        Point::new(
+            center.x + radius * angle.cos(),
+            center.y + radius * angle.sin(),
+            Some(off_curve),
+        )
+    };
+
+    let mut points = Vec::new();
+    if use_curve {
+        let step = 2.0 * PI / CAP_ARCS as f64;
+        let control_radius = radius / (step / 2.0).cos();
+        for index in 0..CAP_ARCS {
+            let angle = step * index as f64;
+            points.push(at(angle, radius, false));
+            points.push(at(angle + step / 2.0, control_radius, true));
+        }
+        // close on an on-curve point, for writers that cannot end a
+        // contour with a control point
+        points.push(at(0.0, radius, false));
+    } else {
+        let step = 2.0 * PI / CAP_SIDES as f64;
+        for index in 0..CAP_SIDES {
+            points.push(at(step * index as f64, radius, false));
+        }
+    }
+
+    polygons.push(Polygon::new(points, color));
+}
+
+/// Whether the centre line turns at the joint between two consecutive
+/// segments, leaving a notch between their flat ends.
+pub(crate) fn is_sharp_joint(incoming: &SkeletonSegment, outgoing: &SkeletonSegment) -> bool {
+    match (direction(incoming, true), direction(outgoing, false)) {
+        (Some(incoming), Some(outgoing)) => {
+            incoming.x * outgoing.x + incoming.y * outgoing.y <= 0.9998
+        }
+        _ => false,
+    }
+}
+
+/// Returns the unit direction in which `segment` leaves its start point or
+/// enters its end point.
+fn direction(segment: &SkeletonSegment, at_end: bool) -> Option<Vector> {
+    let points = match *segment {
+        SkeletonSegment::Line(p1, p2) => vec![p1, p2],
+        SkeletonSegment::Quadratic(p1, p2, p3) => vec![p1, p2, p3],
+        SkeletonSegment::Cubic(p1, p2, p3, p4) => vec![p1, p2, p3, p4],
+    };
+    let (from, to) = if at_end {
+        let end = points[points.len() - 1];
+        let from = points.iter().rev().find(|point| **point != end)?;
+        (*from, end)
+    } else {
+        let start = points[0];
+        let to = points.iter().find(|point| **point != start)?;
+        (start, *to)
+    };
+    let vector = Vector::from(to - from);
+    let length = vector.hypot();
+
+    Some(Vector::new(vector.x / length, vector.y / length))
+}
+
+impl StrokeRenderer for RoundedGothic {
+    fn draw_stroke(
+        &self,
+        polygons: &mut Polygons,
+        stroke: StrokeLineType,
+        _adjustment: AdjustedStroke,
+    ) {
+        let skeleton = stroke_skeleton(
+            &stroke,
+            &SkeletonOptions::new().curve_size(self.gothic.curve_size),
+        );
+        let segments = skeleton.segments();
+        let (Some(first), Some(last)) = (segments.first(), segments.last()) else {
+            return;
+        };
+
+        for segment in segments {
+            self.draw_segment(polygons, *segment, stroke.color);
+        }
+
+        self.draw_cap(polygons, first.start(), stroke.color);
+        for pair in segments.windows(2) {
+            if is_sharp_joint(&pair[0], &pair[1]) {
+                self.draw_cap(polygons, pair[0].end(), stroke.color);
+            }
+        }
+        self.draw_cap(polygons, last.end(), stroke.color);
+    }
+
+    fn use_curve(&self) -> bool {
+        self.gothic.use_curve
+    }
+
+    fn set_use_curve(&mut self, use_curve: bool) {
+        self.gothic.use_curve = use_curve;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{Typeface, kage::Kage, polygons::Polygons};
+
+    fn draw(use_curve: bool, data: &str) -> Polygons {
+        let kage = Kage::new(Typeface::RoundedGothic, use_curve);
+        let mut polygons = Polygons::new();
+        kage.make_glyph_with_data(&mut polygons, data);
+        polygons
+    }
+
+    fn bounds(polygons: &Polygons) -> (f64, f64, f64, f64) {
+        polygons.array().flat_map(|polygon| polygon.points()).fold(
+            (f64::MAX, f64::MAX, f64::MIN, f64::MIN),
+            |(min_x, min_y, max_x, max_y), point| {
+                (
+                    min_x.min(point.x),
+                    min_y.min(point.y),
+                    max_x.max(point.x),
+                    max_y.max(point.y),
+                )
+            },
+        )
+    }
+
+    #[test]
+    fn test_round_caps() {
+        // a bar with a cap on each end
+        let polygons = draw(false, "1:0:0:20:100:180:100");
+        assert_eq!(polygons.len(), 3);
+        assert_eq!(bounds(&polygons), (15.0, 95.0, 185.0, 105.0));
+
+        let polygons = draw(true, "1:0:0:20:100:180:100");
+        assert_eq!(polygons.len(), 3);
+        let cap = polygons.array().nth(1).unwrap();
+        assert_eq!(cap.len(), 17);
+        assert_eq!(
+            cap.points()
+                .iter()
+                .filter(|point| point.is_off_curve())
+                .count(),
+            8
+        );
+    }
+
+    #[test]
+    fn test_round_joins() {
+        // the sharp joint of a vertical slash gets a cap, the rounded bend
+        // of a bend line does not
+        assert_eq!(draw(false, "7:0:7:100:20:100:100:80:160:40:180").len(), 5);
+        assert_eq!(draw(false, "3:0:0:40:40:40:160:160:160").len(), 5);
+    }
+
+    #[test]
+    fn test_round_corners() {
+        // corners are not squared off but rounded to the same extent
+        let polygons = draw(false, "1:12:13:40:40:40:160");
+        assert_eq!(bounds(&polygons), (35.0, 35.0, 45.0, 165.0));
+    }
+}
@@ -1,6 +1,14 @@
-use crate::{font::ming::Ming, line::stroke_line::StrokeLineType, two_d, utils::Point};
-
-#[derive(Debug, Clone, Copy)]
+use crate::{
+    font::{gothic::Gothic, ming::Ming},
+    line::stroke_line::{StrokeKind, StrokeLineType},
+    two_d,
+    utils::Point,
+};
+
+/// How a stroke is drawn differently from its plain shape: the sizes of
+/// its ornaments and ends, read from the glyph data and changed by the
+/// collisions with other strokes.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
 pub struct AdjustedStroke {
     /// origin name: kirikuchiAdjustment
     pub(crate) slash_adjustment: f64,
@@ -16,6 +24,75 @@ pub struct AdjustedStroke {
     pub(crate) curve_adjustment: f64,
 }
 
+impl AdjustedStroke {
+    /// No adjustment at all.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The adjustments written in the optional parts of the glyph data of
+    /// the stroke.
+    pub fn from_stroke(stroke: &StrokeLineType) -> Self {
+        Self {
+            slash_adjustment: stroke.head_shape.opt_1 as f64,
+            vertical_adjustment: stroke.head_shape.opt_2 as f64
+                + stroke.head_shape.opt_3 as f64 * 10.0,
+            flick_adjustment: stroke.tail_shape.opt_1 as f64,
+            triangle_adjustment: stroke.tail_shape.opt as usize,
+            foot_adjustment: stroke.tail_shape.opt as usize,
+            curve_adjustment: stroke.tail_shape.opt_2 as f64,
+        }
+    }
+
+    pub fn set_slash_adjustment(&mut self, slash_adjustment: f64) {
+        self.slash_adjustment = slash_adjustment;
+    }
+
+    pub fn set_vertical_adjustment(&mut self, vertical_adjustment: f64) {
+        self.vertical_adjustment = vertical_adjustment;
+    }
+
+    pub fn set_flick_adjustment(&mut self, flick_adjustment: f64) {
+        self.flick_adjustment = flick_adjustment;
+    }
+
+    pub fn set_triangle_adjustment(&mut self, triangle_adjustment: usize) {
+        self.triangle_adjustment = triangle_adjustment;
+    }
+
+    pub fn set_foot_adjustment(&mut self, foot_adjustment: usize) {
+        self.foot_adjustment = foot_adjustment;
+    }
+
+    pub fn set_curve_adjustment(&mut self, curve_adjustment: f64) {
+        self.curve_adjustment = curve_adjustment;
+    }
+
+    pub fn slash_adjustment(&self) -> f64 {
+        self.slash_adjustment
+    }
+
+    pub fn vertical_adjustment(&self) -> f64 {
+        self.vertical_adjustment
+    }
+
+    pub fn flick_adjustment(&self) -> f64 {
+        self.flick_adjustment
+    }
+
+    pub fn triangle_adjustment(&self) -> usize {
+        self.triangle_adjustment
+    }
+
+    pub fn foot_adjustment(&self) -> usize {
+        self.foot_adjustment
+    }
+
+    pub fn curve_adjustment(&self) -> f64 {
+        self.curve_adjustment
+    }
+}
+
 pub trait StrokeAdjustmentTrait {
     fn adjust_strokes<'a>(
         &self,
@@ -23,54 +100,171 @@ pub trait StrokeAdjustmentTrait {
     ) -> Vec<(&'a StrokeLineType, AdjustedStroke)>;
 }
 
-impl StrokeAdjustmentTrait for Ming {
-    fn adjust_strokes<'a>(
-        &self,
-        line_type: &'a [&'a StrokeLineType],
-    ) -> Vec<(&'a StrokeLineType, AdjustedStroke)> {
-        #[inline]
-        fn for_each_segment<F: FnMut(Point, Point)>(stroke: &StrokeLineType, mut f: F) {
-            if stroke.stroke_type.opt != 0 {
-                f(stroke.point_1, stroke.point_2);
-                return;
+fn for_each_segment<F: FnMut(Point, Point)>(stroke: &StrokeLineType, mut f: F) {
+    if stroke.stroke_type.opt != 0 {
+        f(stroke.point_1, stroke.point_2);
+        return;
+    }
+
+    match stroke.stroke_type.kind {
+        StrokeKind::StraightLine => {
+            f(stroke.point_1, stroke.point_2);
+        }
+        StrokeKind::Curve | StrokeKind::BendLine | StrokeKind::OtsuCurve => {
+            f(stroke.point_1, stroke.point_2);
+            f(stroke.point_2, stroke.point_3);
+        }
+        StrokeKind::ComplexCurve | StrokeKind::VerticalSlash => {
+            f(stroke.point_1, stroke.point_2);
+            f(stroke.point_2, stroke.point_3);
+            f(stroke.point_3, stroke.point_4);
+        }
+        StrokeKind::Unknown => {}
+    }
+}
+
+fn crosses_box(stroke: &StrokeLineType, diag_1: Point, diag_2: Point) -> bool {
+    let mut hit = false;
+    for_each_segment(stroke, |s1, s2| {
+        if !hit && two_d::is_cross_box(s1, s2, diag_1, diag_2) {
+            hit = true;
+        }
+    });
+    hit
+}
+
+/// Returns the adjustments given by the optional parts of the glyph data,
+/// before any collision is checked: [`AdjustedStroke::from_stroke`] of
+/// every stroke.
+pub fn initial_adjustments<'a>(
+    line_type: &'a [&'a StrokeLineType],
+) -> Vec<(&'a StrokeLineType, AdjustedStroke)> {
+    line_type
+        .iter()
+        .map(|&stroke| (stroke, AdjustedStroke::from_stroke(stroke)))
+        .collect()
+}
+
+/// Shortens the hane (left upward flick) of strokes ending close to a
+/// vertical stroke on their left.
+/// origin name: adjustHane
+fn adjust_hane(adjusted: &mut [(&StrokeLineType, AdjustedStroke)]) {
+    let mut vert_segments = Vec::new();
+    for (idx, (stroke, _)) in adjusted.iter().enumerate() {
+        if stroke.stroke_type.base == 1
+            && stroke.stroke_type.opt == 0
+            && stroke.point_1.x == stroke.point_2.x
+        {
+            vert_segments.push((idx, stroke.point_1.x, stroke.point_1.y, stroke.point_2.y));
+        }
+    }
+
+    for (idx, (stroke, adj)) in adjusted.iter_mut().enumerate() {
+        let base = stroke.stroke_type.base;
+        if (base == 1 || base == 2 || base == 6)
+            && stroke.stroke_type.opt == 0
+            && stroke.tail_shape.base == 4
+            && stroke.tail_shape.opt == 0
+        {
+            let (lpx, lpy) = match base {
+                1 => (stroke.point_2.x, stroke.point_2.y),
+                2 => (stroke.point_3.x, stroke.point_3.y),
+                _ => (stroke.point_4.x, stroke.point_4.y),
+            };
+
+            let mut nearest = f64::INFINITY;
+            if lpx + 18.0 < 100.0 {
+                nearest = lpx + 18.0;
             }
 
-            match stroke.stroke_type.kind {
-                crate::line::stroke_line::StrokeKind::StraightLine => {
-                    f(stroke.point_1, stroke.point_2);
-                }
-                crate::line::stroke_line::StrokeKind::Curve
-                | crate::line::stroke_line::StrokeKind::BendLine
-                | crate::line::stroke_line::StrokeKind::OtsuCurve => {
-                    f(stroke.point_1, stroke.point_2);
-                    f(stroke.point_2, stroke.point_3);
-                }
-                crate::line::stroke_line::StrokeKind::ComplexCurve
-                | crate::line::stroke_line::StrokeKind::VerticalSlash => {
-                    f(stroke.point_1, stroke.point_2);
-                    f(stroke.point_2, stroke.point_3);
-                    f(stroke.point_3, stroke.point_4);
+            for &(other_idx, x, y1, y2) in &vert_segments {
+                if idx != other_idx && lpx - x < 100.0 && x < lpx && y1 <= lpy && y2 >= lpy {
+                    let diff = lpx - x;
+                    if diff < nearest {
+                        nearest = diff;
+                    }
                 }
-                crate::line::stroke_line::StrokeKind::Unknown => {}
+            }
+
+            if nearest.is_finite() {
+                adj.flick_adjustment += 7.0 - (nearest / 15.0).floor();
             }
         }
+    }
+}
 
-        #[inline]
-        fn crosses(stroke: &StrokeLineType, start: Point, end: Point) -> bool {
-            let mut hit = false;
-            for_each_segment(stroke, |s1, s2| {
-                if !hit && two_d::is_cross(s1, s2, start, end) {
-                    hit = true;
+/// Shortens the kakato (foot) of vertical strokes when another stroke
+/// lies in the box below their end, checked in `step` growing boxes of
+/// width `range_x` and heights taken from `range_y`.
+/// origin name: adjustKakato
+fn adjust_kakato(
+    adjusted: &mut [(&StrokeLineType, AdjustedStroke)],
+    range_x: f64,
+    range_y: &[f64],
+    step: f64,
+) {
+    let step = step as usize;
+    for idx in 0..adjusted.len() {
+        let stroke = adjusted[idx].0;
+        if stroke.stroke_type.base == 1
+            && stroke.stroke_type.opt == 0
+            && (stroke.tail_shape.base == 13 || stroke.tail_shape.base == 23)
+            && stroke.tail_shape.opt == 0
+        {
+            let mut foot = None;
+
+            for k in 0..step {
+                let y_range_next = range_y[k + 1];
+                let collide = (0..adjusted.len()).any(|other_idx| {
+                    if idx == other_idx {
+                        return false;
+                    }
+                    let stroke2 = adjusted[other_idx].0;
+                    crosses_box(
+                        stroke2,
+                        (
+                            stroke.point_2.x - range_x / 2.0,
+                            stroke.point_2.y + range_y[k],
+                            None,
+                        )
+                            .into(),
+                        (
+                            stroke.point_2.x + range_x / 2.0,
+                            stroke.point_2.y + y_range_next,
+                            None,
+                        )
+                            .into(),
+                    )
+                });
+
+                if collide
+                    || (stroke.point_2.y + y_range_next).round() > 200.0
+                    || (stroke.point_2.y - stroke.point_1.y).round() < y_range_next
+                {
+                    if 3 >= k {
+                        foot = Some(3 - k);
+                    }
+                    break;
                 }
-            });
-            hit
+            }
+
+            if let Some(value) = foot {
+                adjusted[idx].1.foot_adjustment = value;
+            }
         }
+    }
+}
 
+impl StrokeAdjustmentTrait for Ming {
+    fn adjust_strokes<'a>(
+        &self,
+        line_type: &'a [&'a StrokeLineType],
+    ) -> Vec<(&'a StrokeLineType, AdjustedStroke)> {
         #[inline]
-        fn crosses_box(stroke: &StrokeLineType, diag_1: Point, diag_2: Point) -> bool {
+        fn crosses(stroke: &StrokeLineType, start: Point, end: Point) -> bool {
             let mut hit = false;
             for_each_segment(stroke, |s1, s2| {
-                if !hit && two_d::is_cross_box(s1, s2, diag_1, diag_2) {
+                if !hit && two_d::is_cross(s1, s2, start, end) {
                     hit = true;
                 }
             });
@@ -87,70 +281,9 @@ impl StrokeAdjustmentTrait for Ming {
             (dx / len, dy / len)
         }
 
-        let mut adjusted: Vec<(&StrokeLineType, AdjustedStroke)> =
-            Vec::with_capacity(line_type.len());
-
-        for &stroke in line_type {
-            adjusted.push((
-                stroke,
-                AdjustedStroke {
-                    slash_adjustment: stroke.head_shape.opt_1 as f64,
-                    vertical_adjustment: stroke.head_shape.opt_2 as f64
-                        + stroke.head_shape.opt_3 as f64 * 10.0,
-                    flick_adjustment: stroke.tail_shape.opt_1 as f64,
-                    triangle_adjustment: stroke.tail_shape.opt as usize,
-                    foot_adjustment: stroke.tail_shape.opt as usize,
-                    curve_adjustment: stroke.tail_shape.opt_2 as f64,
-                },
-            ));
-        }
+        let mut adjusted = initial_adjustments(line_type);
 
-        // adjust hane
-        {
-            let mut vert_segments = Vec::new();
-            for (idx, (stroke, _)) in adjusted.iter().enumerate() {
-                if stroke.stroke_type.base == 1
-                    && stroke.stroke_type.opt == 0
-                    && stroke.point_1.x == stroke.point_2.x
-                {
-                    vert_segments.push((idx, stroke.point_1.x, stroke.point_1.y, stroke.point_2.y));
-                }
-            }
-
-            for (idx, (stroke, adj)) in adjusted.iter_mut().enumerate() {
-                let base = stroke.stroke_type.base;
-                if (base == 1 || base == 2 || base == 6)
-                    && stroke.stroke_type.opt == 0
-                    && stroke.tail_shape.base == 4
-                    && stroke.tail_shape.opt == 0
-                {
-                    let (lpx, lpy) = match base {
-                        1 => (stroke.point_2.x, stroke.point_2.y),
-                        2 => (stroke.point_3.x, stroke.point_3.y),
-                        _ => (stroke.point_4.x, stroke.point_4.y),
-                    };
-
-                    let mut nearest = f64::INFINITY;
-                    if lpx + 18.0 < 100.0 {
-                        nearest = lpx + 18.0;
-                    }
-
-                    for &(other_idx, x, y1, y2) in &vert_segments {
-                        if idx != other_idx && lpx - x < 100.0 && x < lpx && y1 <= lpy && y2 >= lpy
-                        {
-                            let diff = lpx - x;
-                            if diff < nearest {
-                                nearest = diff;
-                            }
-                        }
-                    }
-
-                    if nearest.is_finite() {
-                        adj.flick_adjustment += 7.0 - (nearest / 15.0).floor();
-                    }
-                }
-            }
-        }
+        adjust_hane(&mut adjusted);
 
         // adjust mage
         {
@@ -222,8 +355,8 @@ impl StrokeAdjustmentTrait for Ming {
                         let dx = (x - other_x).abs();
                         if dx.round() < self.min_width_vertical * self.k_adjust_vertical_step {
                             let adj = &mut adjusted[idx].1;
-                            adj.vertical_adjustment +=
-                                self.k_adjust_vertical_step - (dx / self.min_width_vertical).floor();
+                            adj.vertical_adjustment += self.k_adjust_vertical_step
+                                - (dx / self.min_width_vertical).floor();
                             if adj.vertical_adjustment > self.k_adjust_vertical_step
                                 || (adj.vertical_adjustment == self.k_adjust_vertical_step
                                     && (head_shape.opt_1 != 0 || head_shape.base != 0))
@@ -236,59 +369,12 @@ impl StrokeAdjustmentTrait for Ming {
             }
         }
 
-        // adjust kakato
-        {
-            let step = self.k_adjust_foot_step as usize;
-            for idx in 0..adjusted.len() {
-                let stroke = adjusted[idx].0;
-                if stroke.stroke_type.base == 1
-                    && stroke.stroke_type.opt == 0
-                    && (stroke.tail_shape.base == 13 || stroke.tail_shape.base == 23)
-                    && stroke.tail_shape.opt == 0
-                {
-                    let mut foot = None;
-
-                    for k in 0..step {
-                        let y_range_next = self.k_adjust_foot_range_y[k + 1];
-                        let collide = (0..adjusted.len()).any(|other_idx| {
-                            if idx == other_idx {
-                                return false;
-                            }
-                            let stroke2 = adjusted[other_idx].0;
-                            crosses_box(
-                                stroke2,
-                                (
-                                    stroke.point_2.x - self.k_adjust_foot_range_x / 2.0,
-                                    stroke.point_2.y + self.k_adjust_foot_range_y[k],
-                                    None,
-                                )
-                                    .into(),
-                                (
-                                    stroke.point_2.x + self.k_adjust_foot_range_x / 2.0,
-                                    stroke.point_2.y + y_range_next,
-                                    None,
-                                )
-                                    .into(),
-                            )
-                        });
-
-                        if collide
-                            || (stroke.point_2.y + y_range_next).round() > 200.0
-                            || (stroke.point_2.y - stroke.point_1.y).round() < y_range_next
-                        {
-                            if 3 >= k {
-                                foot = Some(3 - k);
-                            }
-                            break;
-                        }
-                    }
-
-                    if let Some(value) = foot {
-                        adjusted[idx].1.foot_adjustment = value;
-                    }
-                }
-            }
-        }
+        adjust_kakato(
+            &mut adjusted,
+            self.k_adjust_foot_range_x,
+            &self.k_adjust_foot_range_y,
+            self.k_adjust_foot_step,
+        );
 
         // adjust uroko
         {
@@ -316,10 +402,12 @@ impl StrokeAdjustmentTrait for Ming {
                             )
                         };
 
-                        let tx =
-                            stroke.point_2.x - self.k_adjust_triangle_line[k] * cosrad - 0.5 * sinrad;
-                        let ty =
-                            stroke.point_2.y - self.k_adjust_triangle_line[k] * sinrad - 0.5 * cosrad;
+                        let tx = stroke.point_2.x
+                            - self.k_adjust_triangle_line[k] * cosrad
+                            - 0.5 * sinrad;
+                        let ty = stroke.point_2.y
+                            - self.k_adjust_triangle_line[k] * sinrad
+                            - 0.5 * cosrad;
 
                         let tlen = if stroke.point_1.y == stroke.point_2.y {
                             stroke.point_2.x - stroke.point_1.x
@@ -433,5 +521,70 @@ impl StrokeAdjustmentTrait for Ming {
     }
 }
 
+/// Gothic strokes have a constant width and no ornaments, so only the
+/// passes that keep hane and kakato clear of other strokes apply.
+impl StrokeAdjustmentTrait for Gothic {
+    fn adjust_strokes<'a>(
+        &self,
+        line_type: &'a [&'a StrokeLineType],
+    ) -> Vec<(&'a StrokeLineType, AdjustedStroke)> {
+        let mut adjusted = initial_adjustments(line_type);
+
+        adjust_hane(&mut adjusted);
+        adjust_kakato(
+            &mut adjusted,
+            self.k_adjust_foot_range_x,
+            &self.k_adjust_foot_range_y,
+            self.k_adjust_foot_step,
+        );
+
+        adjusted
+    }
+}
+
 #[cfg(test)]
-mod test {}
+mod test {
+    use super::*;
+    use crate::line::Line;
+
+    fn adjust_gothic(data: &[&'static str]) -> Vec<AdjustedStroke> {
+        let lines: Vec<Line> = data.iter().map(|line| Line::new(line)).collect();
+        let strokes: Vec<&StrokeLineType> = lines
+            .iter()
+            .filter_map(|line| match line {
+                Line::StrokeLine(stroke) => Some(stroke),
+                _ => None,
+            })
+            .collect();
+        Gothic::new(false)
+            .adjust_strokes(&strokes)
+            .into_iter()
+            .map(|(_, adjusted)| adjusted)
+            .collect()
+    }
+
+    #[test]
+    fn test_gothic_hane() {
+        let alone = adjust_gothic(&["1:0:4:100:20:100:150"]);
+        assert_eq!(alone[0].flick_adjustment, 0.0);
+
+        // a vertical stroke 40 units to the left of the hane
+        let adjusted = adjust_gothic(&["1:0:4:100:20:100:150", "1:0:0:60:20:60:180"]);
+        assert_eq!(adjusted[0].flick_adjustment, 5.0);
+        assert_eq!(adjusted[1].flick_adjustment, 0.0);
+    }
+
+    #[test]
+    fn test_gothic_kakato() {
+        let alone = adjust_gothic(&["1:0:13:50:20:50:150"]);
+        assert_eq!(alone[0].foot_adjustment, 0);
+
+        // a horizontal stroke right below the foot
+        let adjusted = adjust_gothic(&["1:0:13:50:20:50:150", "1:0:0:20:160:180:160"]);
+        assert_eq!(adjusted[0].foot_adjustment, 3);
+
+        // the foot would stick out of the glyph box
+        let adjusted = adjust_gothic(&["1:0:13:50:20:50:185"]);
+        assert_eq!(adjusted[0].foot_adjustment, 3);
+    }
+}
@@ -0,0 +1,324 @@
+use crate::{
+    curve::{FattenResult, generate_fatten_curve},
+    font::{
+        renderer::StrokeRenderer,
+        rounded_gothic::{is_sharp_joint, push_disc},
+        stroke_adjustment::AdjustedStroke,
+    },
+    line::stroke_line::{EndKind, StrokeKind, StrokeLineType},
+    polygon::Polygon,
+    polygons::Polygons,
+    skeleton::{SkeletonOptions, SkeletonSegment, StrokeSkeleton, stroke_skeleton},
+    utils::{Point, Rgb, Vector},
+};
+
+/// Textbook style (教科書体): strokes drawn as with a brush, whose width
+/// follows the pressure along the stroke.
+///
+/// The brush is pressed at free ends and stops, narrow entries thicken
+/// gradually, left sweeps (左払い) and flicks (ハネ) thin out to a point,
+/// and right sweeps (右払い) widen towards their end.
+///
+/// Strokes are always drawn as polylines: [`StrokeRenderer::use_curve`]
+/// reports `false` and setting it has no effect. The adjustments written in
+/// the glyph data, which shorten the ornaments of the other typefaces, are
+/// ignored too, as a brush stroke has no ornament to shorten.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct Textbook {
+    /// must divide 1000
+    pub sample_step: usize,
+    /// Half of the width of a stroke under normal pressure.
+    pub width: f64,
+    /// Size of the curve at the end of flicks and at the middle of 折れ
+    /// strokes.
+    pub curve_size: f64,
+    /// Width of a tapered end relative to `width`.
+    pub taper_ratio: f64,
+    /// Extra width, relative to `width`, where the brush is pressed.
+    pub press: f64,
+    /// Length over which the pressure of an entry or a stop fades.
+    pub entry_length: f64,
+    /// Length over which a left sweep thins out.
+    pub sweep_length: f64,
+}
+
+impl Default for Textbook {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Textbook {
+    pub fn new() -> Self {
+        Self {
+            sample_step: 50,
+            width: 5.5,
+            curve_size: 10.0,
+            taper_ratio: 0.15,
+            press: 0.2,
+            entry_length: 16.0,
+            sweep_length: 60.0,
+        }
+    }
+
+    /// Returns the pressure, relative to `width`, at `distance` along a
+    /// stroke of the given `length`. Flicks thin out from `hook_start`.
+    fn pressure(
+        &self,
+        skeleton: &StrokeSkeleton,
+        distance: f64,
+        length: f64,
+        hook_start: f64,
+    ) -> f64 {
+        let taper = self.taper_ratio;
+        let remaining = (length - distance).max(0.0);
+        // 1 at the end, fading to 0 over `entry_length`
+        let fade = |distance: f64| (1.0 - distance / self.entry_length).max(0.0).powi(2);
+        let smooth = |x: f64| {
+            let x = x.clamp(0.0, 1.0);
+            x * x * (3.0 - 2.0 * x)
+        };
+
+        let sweeping = matches!(
+            skeleton.kind(),
+            StrokeKind::Curve | StrokeKind::ComplexCurve
+        );
+        let entry = match skeleton.head() {
+            EndKind::Narrow | EndKind::RoofedNarrowEntry
+                if sweeping && skeleton.tail() == EndKind::Free =>
+            {
+                // right sweep: widens all the way to its end
+                let progress = (distance / length.max(f64::EPSILON)).clamp(0.0, 1.0);
+                taper + (1.0 + self.press - taper) * progress.powf(0.8)
+            }
+            EndKind::Narrow | EndKind::RoofedNarrowEntry => {
+                taper + (1.0 - taper) * smooth(distance / self.entry_length)
+            }
+            EndKind::Free => 1.0 + self.press * fade(distance),
+            _ => 1.0,
+        };
+
+        let exit = match skeleton.tail() {
+            EndKind::Narrow => taper + (1.0 - taper) * smooth(remaining / self.sweep_length),
+            EndKind::LeftUpwardFlick | EndKind::RightUpwardFlick if hook_start < length => {
+                // pressed before the hook, then flicked out
+                let pressed = 1.0 + self.press * 0.5;
+                if distance < hook_start {
+                    1.0 + self.press * 0.5 * fade(hook_start - distance)
+                } else {
+                    let progress = ((distance - hook_start) / (length - hook_start)).min(1.0);
+                    taper + (pressed - taper) * (1.0 - progress)
+                }
+            }
+            EndKind::Free
+                if matches!(
+                    skeleton.head(),
+                    EndKind::Narrow | EndKind::RoofedNarrowEntry
+                ) && sweeping =>
+            {
+                1.0
+            }
+            EndKind::Free | EndKind::Stop => 1.0 + self.press * fade(remaining),
+            _ => 1.0,
+        };
+
+        (entry * exit).max(taper)
+    }
+
+    fn draw_segment(
+        &self,
+        polygons: &mut Polygons,
+        segment: SkeletonSegment,
+        width_func: impl Fn(f64) -> f64,
+        color: Option<Rgb>,
+    ) {
+        let (start, control_1, control_2, end) = match segment {
+            SkeletonSegment::Line(start, end) => {
+                let middle = (start + end) * 0.5;
+                (start, middle, middle, end)
+            }
+            SkeletonSegment::Quadratic(start, control, end) => (start, control, control, end),
+            SkeletonSegment::Cubic(start, control_1, control_2, end) => {
+                (start, control_1, control_2, end)
+            }
+        };
+
+        let FattenResult { left, right } = generate_fatten_curve(
+            start,
+            control_1,
+            control_2,
+            end,
+            self.sample_step,
+            width_func,
+        );
+
+        let mut polygon_1 = Polygon::new(left, color);
+        let mut polygon_2 = Polygon::new(right, color);
+        polygon_2.reverse();
+        polygon_1.concat(polygon_2);
+        polygons.push(polygon_1);
+    }
+}
+
+/// Returns the approximate arc length of a segment.
+fn segment_length(segment: &SkeletonSegment) -> f64 {
+    let mut polyline = vec![segment.start()];
+    segment.flatten(0.1, &mut polyline);
+    polyline
+        .windows(2)
+        .map(|pair| Vector::from(pair[1] - pair[0]).hypot())
+        .sum()
+}
+
+impl StrokeRenderer for Textbook {
+    fn draw_stroke(
+        &self,
+        polygons: &mut Polygons,
+        stroke: StrokeLineType,
+        // there is no ornament to adjust
+        _adjustment: AdjustedStroke,
+    ) {
+        let skeleton =
+            stroke_skeleton(&stroke, &SkeletonOptions::new().curve_size(self.curve_size));
+        let segments = skeleton.segments();
+        if segments.is_empty() {
+            return;
+        }
+
+        let lengths: Vec<f64> = segments.iter().map(segment_length).collect();
+        let length: f64 = lengths.iter().sum();
+        let hook_start = match skeleton.tail() {
+            EndKind::LeftUpwardFlick | EndKind::RightUpwardFlick if segments.len() > 1 => {
+                length - lengths[lengths.len() - 1]
+            }
+            _ => length,
+        };
+        let width_at =
+            |distance: f64| self.width * self.pressure(&skeleton, distance, length, hook_start);
+
+        let mut offset = 0.0;
+        for (segment, segment_length) in segments.iter().zip(&lengths) {
+            self.draw_segment(
+                polygons,
+                *segment,
+                |progress| width_at(offset + progress * segment_length),
+                stroke.color,
+            );
+            offset += segment_length;
+        }
+
+        // the brush leaves round marks where it rests
+        let mut push_mark = |center: Point, distance: f64| {
+            push_disc(polygons, center, width_at(distance), false, stroke.color);
+        };
+        if matches!(skeleton.head(), EndKind::Free) {
+            push_mark(segments[0].start(), 0.0);
+        }
+        let mut offset = 0.0;
+        for (pair, segment_length) in segments.windows(2).zip(&lengths) {
+            offset += segment_length;
+            if is_sharp_joint(&pair[0], &pair[1]) {
+                push_mark(pair[0].end(), offset);
+            }
+        }
+        if matches!(skeleton.tail(), EndKind::Free | EndKind::Stop) {
+            push_mark(segments[segments.len() - 1].end(), length);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{Typeface, kage::Kage, line::Line};
+
+    fn skeleton(data: &str) -> StrokeSkeleton {
+        let Line::StrokeLine(stroke) = Line::new(data) else {
+            panic!("not a stroke: {data}");
+        };
+        stroke_skeleton(&stroke, &SkeletonOptions::new())
+    }
+
+    #[test]
+    fn test_pressure() {
+        let textbook = Textbook::new();
+
+        // pressed at both ends of a horizontal stroke
+        let horizontal = skeleton("1:0:0:20:100:180:100");
+        assert_eq!(textbook.pressure(&horizontal, 0.0, 160.0, 160.0), 1.2);
+        assert_eq!(textbook.pressure(&horizontal, 80.0, 160.0, 160.0), 1.0);
+        assert_eq!(textbook.pressure(&horizontal, 160.0, 160.0, 160.0), 1.2);
+
+        // a left sweep thins out to the taper ratio
+        let sweep = skeleton("2:0:7:100:40:80:120:30:170");
+        assert_eq!(textbook.pressure(&sweep, 150.0, 150.0, 150.0), 0.15);
+        assert!(textbook.pressure(&sweep, 100.0, 150.0, 150.0) < 1.0);
+
+        // a right sweep widens towards its end
+        let sweep = skeleton("2:7:0:60:40:80:120:170:170");
+        let widths: Vec<f64> = [0.0, 50.0, 100.0, 150.0]
+            .iter()
+            .map(|&distance| textbook.pressure(&sweep, distance, 150.0, 150.0))
+            .collect();
+        assert_eq!(widths[0], 0.15);
+        assert!(widths.windows(2).all(|pair| pair[0] < pair[1]));
+        assert_eq!(widths[3], 1.2);
+
+        // a flick thins out along its hook only
+        let flick = skeleton("1:0:4:100:20:100:150");
+        assert_eq!(textbook.pressure(&flick, 100.0, 150.0, 130.0), 1.0);
+        assert_eq!(textbook.pressure(&flick, 150.0, 150.0, 130.0), 0.15);
+    }
+
+    #[test]
+    fn test_draw() {
+        let kage = Kage::new(Typeface::Textbook, false);
+        let mut polygons = Polygons::new();
+
+        // body and a mark at each end
+        kage.make_glyph_with_data(&mut polygons, "1:0:0:20:100:180:100");
+        assert_eq!(polygons.len(), 3);
+        // 21 samples on each side
+        assert_eq!(polygons.array().next().unwrap().len(), 42);
+
+        // a left sweep only has a mark at its head
+        polygons.clear();
+        kage.make_glyph_with_data(&mut polygons, "2:0:7:100:40:80:120:30:170");
+        assert_eq!(polygons.len(), 2);
+    }
+
+    #[test]
+    fn test_curves_and_adjustments_ignored() {
+        let draw = |kage: &Kage, data: &str| {
+            let mut polygons = Polygons::new();
+            kage.make_glyph_with_data(&mut polygons, data);
+            polygons
+        };
+        let data = "1:0:0:20:40:180:40$1:0:4:100:20:100:180$2:32:7:100:100:70:150:20:170";
+        let straight = Kage::new(Typeface::Textbook, false);
+
+        let mut kage = Kage::new(Typeface::Textbook, true);
+        assert!(!kage.renderer().use_curve());
+        let svg = |polygons: Polygons| polygons.generate_svg(true);
+        assert_eq!(svg(draw(&kage, data)), svg(draw(&straight, data)));
+        kage.set_use_curve(true);
+        assert!(!kage.renderer().use_curve());
+        assert!(
+            draw(&kage, data)
+                .array()
+                .flat_map(|polygon| polygon.points())
+                .all(|point| !point.is_off_curve())
+        );
+
+        // the flick adjusted by the glyph data is drawn as is
+        assert_eq!(
+            svg(draw(&straight, "1:0:104:100:20:100:180")),
+            svg(draw(&straight, "1:0:4:100:20:100:180"))
+        );
+    }
+}
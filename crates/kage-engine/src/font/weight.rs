@@ -0,0 +1,294 @@
+use crate::font::{gothic::Gothic, ming::Ming, parameters::Parameters, textbook::Textbook};
+
+/// Weight of the default parameters of every typeface, on the OpenType
+/// `wght` scale where 300 is Light and 700 is Bold.
+pub const REGULAR_WEIGHT: f64 = 400.0;
+
+/// Style parameters that can be blended to derive weights of a family from
+/// the same glyph data.
+///
+/// Lengths and widths are interpolated linearly, per level for the
+/// adjustment tables. Numbers of adjustment levels, `sample_step` and
+/// `use_curve` are not interpolated but taken from the nearest master.
+pub trait Interpolate: Sized {
+    /// Returns the parameters `t` of the way from `self` to `other`. `t`
+    /// outside of `0.0..=1.0` extrapolates.
+    fn interpolate(&self, other: &Self, t: f64) -> Self;
+
+    /// Returns the parameters thickened (`scale > 1`) or thinned
+    /// (`scale < 1`) by `scale`, with the ornaments and the collision
+    /// boxes of the adjustments scaled along.
+    fn scale_weight(&self, scale: f64) -> Self;
+
+    /// Returns the parameters at `weight`, taking `self` as the regular
+    /// weight ([`REGULAR_WEIGHT`]): stems are as thick as the weight.
+    fn at_weight(&self, weight: f64) -> Self {
+        self.scale_weight(weight / REGULAR_WEIGHT)
+    }
+}
+
+fn lerp(from: f64, to: f64, t: f64) -> f64 {
+    from + (to - from) * t
+}
+
+/// Interpolates two adjustment tables level by level. Tables of different
+/// lengths are not blended but taken from the nearest master.
+fn lerp_table(from: &[f64], to: &[f64], t: f64) -> Vec<f64> {
+    if from.len() != to.len() {
+        return nearest(from, to, t).to_vec();
+    }
+    from.iter()
+        .zip(to)
+        .map(|(&from, &to)| lerp(from, to, t))
+        .collect()
+}
+
+fn nearest<'a, T: ?Sized>(from: &'a T, to: &'a T, t: f64) -> &'a T {
+    if t < 0.5 { from } else { to }
+}
+
+fn scale_table(table: &[f64], scale: f64) -> Vec<f64> {
+    table.iter().map(|value| value * scale).collect()
+}
+
+impl Interpolate for Ming {
+    fn interpolate(&self, other: &Self, t: f64) -> Self {
+        let steps = nearest(self, other, t);
+
+        Self {
+            sample_step: steps.sample_step,
+            min_width_horizontal: lerp(self.min_width_horizontal, other.min_width_horizontal, t),
+            min_width_triangle: lerp(self.min_width_triangle, other.min_width_triangle, t),
+            min_width_vertical: lerp(self.min_width_vertical, other.min_width_vertical, t),
+            width: lerp(self.width, other.width, t),
+            right_sweep_end_scale_factor: lerp(
+                self.right_sweep_end_scale_factor,
+                other.right_sweep_end_scale_factor,
+                t,
+            ),
+            curve_size: lerp(self.curve_size, other.curve_size, t),
+            use_curve: steps.use_curve,
+            adjust_foot_left: lerp_table(&self.adjust_foot_left, &other.adjust_foot_left, t),
+            adjust_foot_right: lerp_table(&self.adjust_foot_right, &other.adjust_foot_right, t),
+            k_adjust_foot_range_x: lerp(self.k_adjust_foot_range_x, other.k_adjust_foot_range_x, t),
+            k_adjust_foot_range_y: lerp_table(
+                &self.k_adjust_foot_range_y,
+                &other.k_adjust_foot_range_y,
+                t,
+            ),
+            k_adjust_foot_step: steps.k_adjust_foot_step,
+            k_adjust_triangle_x: lerp_table(
+                &self.k_adjust_triangle_x,
+                &other.k_adjust_triangle_x,
+                t,
+            ),
+            k_adjust_triangle_y: lerp_table(
+                &self.k_adjust_triangle_y,
+                &other.k_adjust_triangle_y,
+                t,
+            ),
+            k_adjust_triangle_length: lerp_table(
+                &self.k_adjust_triangle_length,
+                &other.k_adjust_triangle_length,
+                t,
+            ),
+            k_adjust_triangle_length_step: steps.k_adjust_triangle_length_step,
+            k_adjust_triangle_line: lerp_table(
+                &self.k_adjust_triangle_line,
+                &other.k_adjust_triangle_line,
+                t,
+            ),
+            k_adjust_triangle2_step: steps.k_adjust_triangle2_step,
+            k_adjust_triangle2_length: lerp(
+                self.k_adjust_triangle2_length,
+                other.k_adjust_triangle2_length,
+                t,
+            ),
+            k_adjust_vertical_step: steps.k_adjust_vertical_step,
+            k_adjust_curve_step: steps.k_adjust_curve_step,
+        }
+    }
+
+    /// Vertical strokes and the kakato collision boxes are scaled by
+    /// `scale`. Horizontal strokes, ウロコ and カカト, as well as the
+    /// collision boxes sized after them, grow more slowly (by the square
+    /// root of `scale`) to keep the contrast of the style.
+    fn scale_weight(&self, scale: f64) -> Self {
+        let ornament_scale = scale.sqrt();
+
+        Self {
+            min_width_horizontal: self.min_width_horizontal * ornament_scale,
+            min_width_triangle: self.min_width_triangle * ornament_scale,
+            min_width_vertical: self.min_width_vertical * scale,
+            width: self.width * ornament_scale,
+            curve_size: self.curve_size * ornament_scale,
+            adjust_foot_left: scale_table(&self.adjust_foot_left, ornament_scale),
+            adjust_foot_right: scale_table(&self.adjust_foot_right, ornament_scale),
+            k_adjust_foot_range_x: self.k_adjust_foot_range_x * scale,
+            k_adjust_foot_range_y: scale_table(&self.k_adjust_foot_range_y, scale),
+            k_adjust_triangle_x: scale_table(&self.k_adjust_triangle_x, ornament_scale),
+            k_adjust_triangle_y: scale_table(&self.k_adjust_triangle_y, ornament_scale),
+            k_adjust_triangle_length: scale_table(&self.k_adjust_triangle_length, ornament_scale),
+            k_adjust_triangle_line: scale_table(&self.k_adjust_triangle_line, ornament_scale),
+            k_adjust_triangle2_length: self.k_adjust_triangle2_length * ornament_scale,
+            ..self.clone()
+        }
+    }
+}
+
+impl Interpolate for Gothic {
+    fn interpolate(&self, other: &Self, t: f64) -> Self {
+        let steps = nearest(self, other, t);
+
+        Self {
+            sample_step: steps.sample_step,
+            width: lerp(self.width, other.width, t),
+            foot_size: lerp(self.foot_size, other.foot_size, t),
+            curve_size: lerp(self.curve_size, other.curve_size, t),
+            use_curve: steps.use_curve,
+            k_adjust_foot_range_x: lerp(self.k_adjust_foot_range_x, other.k_adjust_foot_range_x, t),
+            k_adjust_foot_range_y: lerp_table(
+                &self.k_adjust_foot_range_y,
+                &other.k_adjust_foot_range_y,
+                t,
+            ),
+            k_adjust_foot_step: steps.k_adjust_foot_step,
+        }
+    }
+
+    /// The width and the kakato collision boxes are scaled by `scale`; the
+    /// kakato is relative to the width already. Corners and hane grow more
+    /// slowly, by the square root of `scale`.
+    fn scale_weight(&self, scale: f64) -> Self {
+        Self {
+            width: self.width * scale,
+            curve_size: self.curve_size * scale.sqrt(),
+            k_adjust_foot_range_x: self.k_adjust_foot_range_x * scale,
+            k_adjust_foot_range_y: scale_table(&self.k_adjust_foot_range_y, scale),
+            ..self.clone()
+        }
+    }
+}
+
+impl Interpolate for Textbook {
+    fn interpolate(&self, other: &Self, t: f64) -> Self {
+        Self {
+            sample_step: nearest(self, other, t).sample_step,
+            width: lerp(self.width, other.width, t),
+            curve_size: lerp(self.curve_size, other.curve_size, t),
+            taper_ratio: lerp(self.taper_ratio, other.taper_ratio, t),
+            press: lerp(self.press, other.press, t),
+            entry_length: lerp(self.entry_length, other.entry_length, t),
+            sweep_length: lerp(self.sweep_length, other.sweep_length, t),
+        }
+    }
+
+    /// The width is scaled by `scale`, and corners and hooks by its square
+    /// root. The pressure profile is kept.
+    fn scale_weight(&self, scale: f64) -> Self {
+        Self {
+            width: self.width * scale,
+            curve_size: self.curve_size * scale.sqrt(),
+            ..self.clone()
+        }
+    }
+}
+
+impl Parameters {
+    /// Returns the parameters `t` of the way from `self` to `other`, or
+    /// `None` if they are not of the same typeface.
+    pub fn interpolate(&self, other: &Self, t: f64) -> Option<Self> {
+        Some(match (self, other) {
+            (Parameters::Ming(from), Parameters::Ming(to)) => {
+                Parameters::Ming(from.interpolate(to, t))
+            }
+            (Parameters::Gothic(from), Parameters::Gothic(to)) => {
+                Parameters::Gothic(from.interpolate(to, t))
+            }
+            (Parameters::RoundedGothic(from), Parameters::RoundedGothic(to)) => {
+                Parameters::RoundedGothic(from.interpolate(to, t))
+            }
+            (Parameters::Textbook(from), Parameters::Textbook(to)) => {
+                Parameters::Textbook(from.interpolate(to, t))
+            }
+            _ => return None,
+        })
+    }
+
+    /// Returns the parameters at `weight`, taking `self` as the regular
+    /// weight. See [`Interpolate::at_weight`].
+    pub fn at_weight(&self, weight: f64) -> Self {
+        match self {
+            Parameters::Ming(ming) => Parameters::Ming(ming.at_weight(weight)),
+            Parameters::Gothic(gothic) => Parameters::Gothic(gothic.at_weight(weight)),
+            Parameters::RoundedGothic(gothic) => {
+                Parameters::RoundedGothic(gothic.at_weight(weight))
+            }
+            Parameters::Textbook(textbook) => Parameters::Textbook(textbook.at_weight(weight)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{kage::Kage, polygons::Polygons};
+
+    #[test]
+    fn test_interpolate() {
+        let light = Ming::default().at_weight(300.0);
+        let bold = Ming::default().at_weight(700.0);
+
+        let regular = light.interpolate(&bold, 0.25);
+        assert_eq!(regular.min_width_vertical, 6.0);
+        assert_eq!(regular.k_adjust_foot_range_y, vec![1.0, 19.0, 24.0, 30.0]);
+        assert_eq!(regular.k_adjust_foot_step, 3.0);
+        assert_eq!(light.interpolate(&bold, 0.0), light);
+        assert_eq!(light.interpolate(&bold, 1.0), bold);
+
+        // tables of different lengths are not blended
+        let mut other = bold.clone();
+        other.k_adjust_triangle_x.push(8.0);
+        assert_eq!(light.interpolate(&other, 0.4).k_adjust_triangle_x.len(), 4);
+        assert_eq!(light.interpolate(&other, 0.6).k_adjust_triangle_x.len(), 5);
+
+        assert!(
+            Parameters::Ming(light)
+                .interpolate(&Parameters::Gothic(Gothic::default()), 0.5)
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_weights() {
+        let weights = [200.0, 300.0, 400.0, 500.0, 700.0, 900.0];
+
+        let gothic = Gothic::default();
+        assert_eq!(gothic.at_weight(REGULAR_WEIGHT), gothic);
+        let widths: Vec<f64> = weights
+            .iter()
+            .map(|&weight| gothic.at_weight(weight).width)
+            .collect();
+        assert_eq!(widths, vec![2.5, 3.75, 5.0, 6.25, 8.75, 11.25]);
+
+        // every weight of the family can be drawn, thicker and thicker
+        let mut stems = Vec::new();
+        for weight in weights {
+            let parameters = Parameters::Ming(Ming::default()).at_weight(weight);
+            let kage = Kage::from_parameters(parameters).unwrap();
+            let mut polygons = Polygons::new();
+            kage.make_glyph_with_data(&mut polygons, "1:0:0:20:50:180:50$1:0:0:100:20:100:180");
+            // width of the vertical stroke, height of the horizontal one
+            let points: Vec<_> = polygons
+                .array()
+                .flat_map(|polygon| polygon.points())
+                .collect();
+            let stem = points.iter().filter(|point| point.y > 100.0);
+            let (left, right) = stem.fold((f64::MAX, f64::MIN), |(min, max), point| {
+                (min.min(point.x), max.max(point.x))
+            });
+            stems.push(right - left);
+        }
+        assert!(stems.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+}
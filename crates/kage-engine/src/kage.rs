@@ -1,32 +1,130 @@
 use crate::{
     component::Components,
-    font::{FontWrapper, Typeface},
+    font::{
+        FontWrapper, Typeface,
+        parameters::{ParameterError, Parameters},
+        renderer::StrokeRenderer,
+    },
     line::{
-        Line,
+        Line, LineError, LineOptions,
+        component_reference_line::ComponentReferenceLine,
         stroke_line::{self, StrokeLineType},
     },
     polygons::Polygons,
     utils::{Point, Vector},
 };
-use core::f64;
+use core::{f64, mem, ops::Range};
+
+/// How many components deep references are expanded.
+pub const MAX_COMPONENT_DEPTH: usize = 16;
+/// How many lines of glyph and component data are read at most to expand
+/// a glyph, so that components referencing each other many times cannot
+/// blow up.
+pub const MAX_EXPANDED_LINES: usize = 10_000;
+
+/// What is left of the limits of an expansion.
+struct ExpansionBudget {
+    remaining: usize,
+    /// Whether a reference or a line was dropped because of a limit.
+    truncated: bool,
+}
 
 pub struct Kage {
     pub components: Components,
     pub font: FontWrapper,
+    line_options: LineOptions,
 }
 
 impl Kage {
+    /// Creates an engine drawing glyphs with a built-in typeface, with
+    /// quadratic Bézier curves if `use_curve` is set and the typeface draws
+    /// them ([`Typeface::Textbook`] does not).
     pub fn new(typeface: Typeface, use_curve: bool) -> Self {
         Self {
             components: Components::new(),
             font: FontWrapper::new(typeface, use_curve),
+            line_options: LineOptions::new(),
+        }
+    }
+
+    /// Creates an engine drawing glyphs with a custom typeface style.
+    pub fn with_renderer<R: StrokeRenderer + 'static>(renderer: R) -> Self {
+        Self {
+            components: Components::new(),
+            font: FontWrapper::Custom(Box::new(renderer)),
+            line_options: LineOptions::new(),
         }
     }
 
+    /// Creates an engine drawing glyphs with a built-in typeface tuned by
+    /// the given parameters, after checking them.
+    pub fn from_parameters(parameters: Parameters) -> Result<Self, ParameterError> {
+        Ok(Self {
+            components: Components::new(),
+            font: FontWrapper::from_parameters(parameters)?,
+            line_options: LineOptions::new(),
+        })
+    }
+
+    /// Returns the parameters of the typeface, to save them, or `None` for
+    /// a custom renderer.
+    pub fn parameters(&self) -> Option<Parameters> {
+        self.font.parameters()
+    }
+
+    pub fn renderer(&self) -> &dyn StrokeRenderer {
+        self.font.renderer()
+    }
+
     pub fn set_use_curve(&mut self, use_curve: bool) {
         self.font.set_use_curve(use_curve);
     }
 
+    /// Enables or disables the cache of expanded components, off by
+    /// default.
+    ///
+    /// With the cache, each component referenced by the glyphs drawn is
+    /// parsed and expanded once, which speeds up drawing many glyphs that
+    /// share components. The cache is cleared whenever a component is set.
+    pub fn set_component_cache(&mut self, enabled: bool) {
+        self.components.set_cache_enabled(enabled);
+    }
+
+    pub fn is_component_cache_enabled(&self) -> bool {
+        self.components.is_cache_enabled()
+    }
+
+    /// Sets how the lines of glyph and component data are read, by default
+    /// as [`Line::new`] does.
+    ///
+    /// With precise options, coordinates keep their fractional part through
+    /// the expansion of components, including the stretching by control
+    /// points, so that scaled components do not drift by flooring; turn off
+    /// [`Polygons::set_floor_points`] as well to keep it in the output. With
+    /// strict options, the lines that [`Line::parse`] rejects are not drawn;
+    /// [`Kage::check_glyph_data`] tells which they are.
+    pub fn set_line_options(&mut self, options: LineOptions) {
+        self.line_options = options;
+        self.components.invalidate();
+    }
+
+    pub fn line_options(&self) -> LineOptions {
+        self.line_options
+    }
+
+    /// Reads every line of the glyph data strictly, with the other line
+    /// options of this engine, and returns the index of the first line
+    /// rejected with its error. The components it references are not read;
+    /// check their own data for them.
+    pub fn check_glyph_data(&self, data: &str) -> Result<(), (usize, LineError)> {
+        let options = self.line_options.strict(true);
+        for (index, line_data) in data.split('$').enumerate() {
+            Line::parse(line_data, &options).map_err(|error| (index, error))?;
+        }
+
+        Ok(())
+    }
+
     pub fn make_glyph_with_component_name(&self, polygons: &mut Polygons, component_name: &str) {
         let glyph_data = match self.components.search(component_name) {
             Some(content) => content,
@@ -37,37 +135,128 @@ impl Kage {
     }
 
     pub fn make_glyph_with_data(&self, polygons: &mut Polygons, data: &str) {
+        self.make_glyph_with_font(&self.font, polygons, data);
+    }
+
+    /// Draws the glyph like [`Kage::make_glyph_with_data`] and returns, for
+    /// each stroke of the expanded glyph data in order, the range of
+    /// `polygons` it drew.
+    pub fn make_strokes_with_data(&self, polygons: &mut Polygons, data: &str) -> Vec<Range<usize>> {
+        if data.is_empty() {
+            return Vec::new();
+        }
+
+        let lines = self.get_each_expanded_line(data);
+        let adjustments = self.font.adjust_strokes(&lines);
+
+        let mut ranges = Vec::new();
+        self.font
+            .draw_lines_with_adjustments(polygons, &lines, &adjustments, |line, range| {
+                if matches!(line, Line::StrokeLine(_)) {
+                    ranges.push(range);
+                }
+            });
+
+        ranges
+    }
+
+    /// Draws the glyph with the components of this engine but another font,
+    /// e.g. to compare typefaces.
+    pub(crate) fn make_glyph_with_font(
+        &self,
+        font: &FontWrapper,
+        polygons: &mut Polygons,
+        data: &str,
+    ) {
         if data.is_empty() {
             return;
         }
 
         let lines = self.get_each_expanded_line(data);
+        font.draw_lines(polygons, &lines);
+    }
+
+    /// Returns the names of the components referenced by the glyph data,
+    /// directly or through other components, that are not in the store.
+    /// Each name is reported once, in the order it is first referenced.
+    pub fn missing_components<'a>(&'a self, glyph_data: &'a str) -> Vec<&'a str> {
+        let mut visited = Vec::new();
+        let mut missing = Vec::new();
+        self.collect_missing_components(glyph_data, &mut visited, &mut missing);
+
+        missing
+    }
+
+    fn collect_missing_components<'a>(
+        &'a self,
+        glyph_data: &'a str,
+        visited: &mut Vec<&'a str>,
+        missing: &mut Vec<&'a str>,
+    ) {
+        for line_data in glyph_data.split('$') {
+            let Line::ComponentReferenceLine(reference) = Line::new(line_data) else {
+                continue;
+            };
+            let name = reference.component_name;
+            if visited.contains(&name) {
+                continue;
+            }
+            visited.push(name);
 
-        for drawer in self.font.get_drawers(&lines) {
-            drawer(polygons);
+            match self.components.search(name) {
+                Some(component_data) => {
+                    self.collect_missing_components(component_data, visited, missing)
+                }
+                None => missing.push(name),
+            }
         }
     }
 
+    /// Returns the lines of the glyph data with the component references
+    /// replaced by the strokes of the components.
+    ///
+    /// References nested more than [`MAX_COMPONENT_DEPTH`] components deep,
+    /// which only cyclic references reach in practice, are dropped, and the
+    /// expansion stops after reading [`MAX_EXPANDED_LINES`] lines.
     pub fn get_each_expanded_line<'a>(&'a self, glyph_data: &'a str) -> Vec<Line<'a>> {
+        let mut budget = ExpansionBudget {
+            remaining: MAX_EXPANDED_LINES,
+            truncated: false,
+        };
+        self.expand_lines(glyph_data, 0, &mut budget)
+    }
+
+    fn expand_lines<'a>(
+        &'a self,
+        glyph_data: &'a str,
+        depth: usize,
+        budget: &mut ExpansionBudget,
+    ) -> Vec<Line<'a>> {
         let mut lines = Vec::new();
 
         for line_data in glyph_data.split('$') {
-            match Line::new(line_data) {
+            if budget.remaining == 0 {
+                budget.truncated = true;
+                break;
+            }
+            budget.remaining -= 1;
+
+            match Line::parse(line_data, &self.line_options).unwrap_or(Line::Unknown) {
                 Line::StrokeLine(stroke_line) => lines.push(Line::StrokeLine(stroke_line)),
                 Line::SpecialLine(special_line) => lines.push(Line::SpecialLine(special_line)),
+                Line::ComponentReferenceLine(_) if depth == MAX_COMPONENT_DEPTH => {
+                    budget.truncated = true;
+                }
                 Line::ComponentReferenceLine(component_reference_line) => {
                     if let Some(component_data) = self
                         .components
                         .search(component_reference_line.component_name)
                     {
                         let mut expanded = self.expand_component_strokes(
+                            &component_reference_line,
                             component_data,
-                            component_reference_line.box_diag_1,
-                            component_reference_line.box_diag_2,
-                            component_reference_line.primary_control_point.x,
-                            component_reference_line.primary_control_point.y,
-                            component_reference_line.secondary_control_point.x,
-                            component_reference_line.secondary_control_point.y,
+                            depth + 1,
+                            budget,
                         );
                         lines.append(&mut expanded);
                     }
@@ -79,24 +268,63 @@ impl Kage {
         lines
     }
 
-    fn expand_component_strokes<'a>(
-        &'a self,
-        component_data: &'a str,
-        box_diag_1: Point,
-        box_diag_2: Point,
-        mut sx: f64,
-        sy: f64,
-        mut sx2: f64,
-        mut sy2: f64,
-    ) -> Vec<Line<'a>> {
-        let mut stroke_lines: Vec<StrokeLineType> = self
-            .get_each_expanded_line(component_data)
+    /// Returns the strokes of a component in its own space, with its own
+    /// references expanded, from the cache when it is enabled. Strokes cut
+    /// short by the limits of the expansion are not cached.
+    fn component_strokes(
+        &self,
+        component_name: &str,
+        component_data: &str,
+        depth: usize,
+        budget: &mut ExpansionBudget,
+    ) -> Vec<StrokeLineType> {
+        if let Some(strokes) = self.components.cached_strokes(component_name) {
+            if strokes.len() > budget.remaining {
+                let strokes = strokes[..budget.remaining].to_vec();
+                budget.remaining = 0;
+                budget.truncated = true;
+                return strokes;
+            }
+            budget.remaining -= strokes.len();
+            return strokes.to_vec();
+        }
+
+        let truncated = mem::replace(&mut budget.truncated, false);
+        let strokes: Vec<StrokeLineType> = self
+            .expand_lines(component_data, depth, budget)
             .into_iter()
             .filter_map(|line| match line {
                 Line::StrokeLine(stroke_line) => Some(stroke_line),
                 _ => None,
             })
             .collect();
+        if !budget.truncated {
+            self.components.cache_strokes(component_name, &strokes);
+        }
+        budget.truncated |= truncated;
+        strokes
+    }
+
+    fn expand_component_strokes<'a>(
+        &'a self,
+        reference: &ComponentReferenceLine,
+        component_data: &'a str,
+        depth: usize,
+        budget: &mut ExpansionBudget,
+    ) -> Vec<Line<'a>> {
+        let mut stroke_lines =
+            self.component_strokes(reference.component_name, component_data, depth, budget);
+
+        let box_diag_1 = reference.box_diag_1;
+        let box_diag_2 = reference.box_diag_2;
+        let Point {
+            x: mut sx, y: sy, ..
+        } = reference.primary_control_point;
+        let Point {
+            x: mut sx2,
+            y: mut sy2,
+            ..
+        } = reference.secondary_control_point;
 
         if sx != 0.0 || sy != 0.0 {
             if sx > 100.0 {
@@ -117,7 +345,13 @@ impl Kage {
             let src_pivot = Point::new(sx2, sy2, None);
 
             for stroke in &mut stroke_lines {
-                stroke.stretch(dest_pivot, src_pivot, min_point, max_point);
+                stroke.stretch(
+                    dest_pivot,
+                    src_pivot,
+                    min_point,
+                    max_point,
+                    !self.line_options.is_precise(),
+                );
             }
         }
 
@@ -129,6 +363,14 @@ impl Kage {
             stroke.point_3 = box_diag_1 + stroke.point_3 * scale_vector;
             stroke.point_4 = box_diag_1 + stroke.point_4 * scale_vector;
         }
+        // nested boxes can scale strokes beyond the range of `f64`, where
+        // they cannot be drawn
+        stroke_lines.retain(|stroke| {
+            stroke
+                .points()
+                .iter()
+                .all(|point| point.x.is_finite() && point.y.is_finite())
+        });
 
         stroke_lines.into_iter().map(Line::StrokeLine).collect()
     }
@@ -156,3 +398,145 @@ impl Kage {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_cyclic_components() {
+        let mut kage = Kage::new(Typeface::Ming, false);
+        kage.components
+            .push("a", "1:0:0:20:100:180:100$99:0:0:0:0:200:200:b:0:0:0");
+        kage.components
+            .push("b", "99:0:0:0:0:200:200:a:0:0:0$99:0:0:0:0:200:200:a:0:0:0");
+        kage.components.push("c", "99:0:0:0:0:200:200:c:0:0:0");
+
+        for cache in [false, true] {
+            kage.set_component_cache(cache);
+            // a nests two copies of itself through b
+            let lines = kage.get_each_expanded_line("99:0:0:0:0:200:200:a:0:0:0");
+            assert!(!lines.is_empty() && lines.len() <= MAX_EXPANDED_LINES);
+            assert!(lines.iter().all(|line| matches!(line, Line::StrokeLine(_))));
+            assert!(
+                kage.get_each_expanded_line("99:0:0:0:0:200:200:c:0:0:0")
+                    .is_empty()
+            );
+        }
+
+        // cached strokes count against the limit too
+        let big = vec!["1:0:0:20:100:180:100"; MAX_EXPANDED_LINES / 2 + 1].join("$");
+        kage.components.push("e", &big);
+        let twice = "99:0:0:0:0:200:200:e:0:0:0$99:0:0:0:0:200:200:e:0:0:0";
+        let uncached = kage.get_each_expanded_line(twice).len();
+        assert!(kage.components.cached_strokes("e").is_some());
+        assert_eq!(kage.get_each_expanded_line(twice).len(), uncached);
+        assert!(uncached <= MAX_EXPANDED_LINES);
+
+        // components that expand completely are still cached
+        kage.components.push("d", "1:0:0:20:100:180:100");
+        kage.get_each_expanded_line("99:0:0:0:0:200:200:d:0:0:0");
+        assert!(kage.components.cached_strokes("d").is_some());
+        assert!(kage.components.cached_strokes("c").is_none());
+    }
+
+    #[test]
+    fn test_infinite_transform() {
+        let kage = Kage::new(Typeface::Gothic, false);
+        let mut polygons = Polygons::new();
+        kage.make_glyph_with_data(
+            &mut polygons,
+            "1:0:0:20:100:180:100$0:99:2:-1e308:0:200:200",
+        );
+        assert!(!polygons.is_empty());
+        assert_eq!(polygons.bounds().unwrap()[0], 20.0);
+    }
+
+    #[test]
+    fn test_finite_points() {
+        let glyphs = [
+            // a component stretched into a single column
+            "99:2:0:-1:0:201:160:a",
+            // a slash cut at a horizontal edge
+            "2:132:70:0:0:0:0:0:0",
+            // a kakato adjusted beyond the table
+            "1:22:1723:175:31:175:66",
+            // an infinite coordinate
+            "2:0:7:169:25:131e578:140:20:150",
+            // strokes scaled beyond the range of f64
+            "99:0:0:0:0:1e200:200:b",
+        ];
+        for (typeface, use_curve) in [
+            (Typeface::Ming, false),
+            (Typeface::Ming, true),
+            (Typeface::Gothic, false),
+            (Typeface::Gothic, true),
+        ] {
+            let mut kage = Kage::new(typeface, use_curve);
+            kage.components.push("a", "1:0:32:100:15:100:64");
+            kage.components
+                .push("b", "99:0:0:0:0:1e200:200:c$1:0:0:20:100:180:100");
+            kage.components.push("c", "2:7:8:66:12:58:82:20:138");
+            let mut polygons = Polygons::new();
+            for glyph in glyphs {
+                polygons.clear();
+                kage.make_glyph_with_data(&mut polygons, glyph);
+                assert_eq!(polygons.non_finite_count(), 0, "{glyph}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_precise_components() {
+        let mut kage = Kage::new(Typeface::Gothic, false);
+        kage.components.push(
+            "a",
+            "1:0:0:20:20:20:180$1:0:0:70:20:70:180$1:0:0:20:100:180:100",
+        );
+        // stretched by a control point, in a third of the box
+        let data = "99:50:0:0:0:67:200:a";
+
+        let draw = |kage: &Kage, floor_points: bool| {
+            let mut polygons = Polygons::new();
+            polygons.set_floor_points(floor_points);
+            kage.make_glyph_with_data(&mut polygons, data);
+            polygons
+                .array()
+                .flat_map(|polygon| polygon.points().to_vec())
+                .map(|point| [point.x(), point.y()])
+                .collect::<Vec<_>>()
+        };
+        let floored = draw(&kage, true);
+        assert!(!floored.is_empty());
+
+        kage.set_line_options(LineOptions::new().precise(true));
+        let precise = draw(&kage, false);
+        assert_ne!(precise, floored);
+        assert!(precise.iter().flatten().any(|value| value.fract() != 0.0));
+        assert_eq!(
+            draw(&kage, true),
+            precise
+                .iter()
+                .map(|point| point.map(|value| (value * 10.0).floor() / 10.0))
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_check_glyph_data() {
+        let mut kage = Kage::new(Typeface::Ming, false);
+        assert_eq!(
+            kage.check_glyph_data("1:0:0:20:100:180:100$99:0:0:0:0:200:200:a:0:0:0$"),
+            Ok(())
+        );
+        assert_eq!(
+            kage.check_glyph_data("1:0:0:20:100:180:100$1:0:0:20:x:180:100"),
+            Err((1, LineError::NotANumber { field: 5 }))
+        );
+
+        // the precision of the engine is kept
+        kage.set_line_options(LineOptions::new().precise(true));
+        assert_eq!(kage.check_glyph_data("1:0:0:20.5:100:180:100"), Ok(()));
+        assert!(!kage.line_options().is_strict());
+    }
+}
@@ -1,12 +1,35 @@
+pub mod animation;
+pub mod batch;
 pub(crate) mod component;
 pub(crate) mod curve;
 pub mod font;
 pub mod kage;
 pub mod line;
+pub mod masters;
+pub mod metrics;
+pub mod outline;
+pub mod pdf;
 pub(crate) mod pen;
+pub(crate) mod png;
 pub(crate) mod polygon;
 pub mod polygons;
+pub mod postscript;
+pub mod raster;
+pub mod sheet;
+pub mod skeleton;
+pub mod svg;
+pub mod truetype;
 pub(crate) mod two_d;
 pub(crate) mod utils;
+pub mod vertical;
 
-pub use font::Typeface;
+pub use font::{
+    Typeface,
+    parameters::{ParameterError, Parameters},
+    renderer::StrokeRenderer,
+    stroke_adjustment::AdjustedStroke,
+    weight::{Interpolate, REGULAR_WEIGHT},
+};
+pub use line::stroke_line::StrokeLineType;
+pub use polygon::Polygon;
+pub use utils::{Point, Rgb};
@@ -1,6 +1,8 @@
 pub(crate) mod component_reference_line;
-pub(crate) mod special_line;
-pub(crate) mod stroke_line;
+pub mod special_line;
+pub mod stroke_line;
+
+use core::fmt;
 
 use crate::{
     line::{
@@ -22,14 +24,94 @@ pub enum Line<'a> {
     Unknown,
 }
 
+/// How [`Line::parse`] reads the numbers of a line.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct LineOptions {
+    precise: bool,
+    strict: bool,
+}
+
+impl LineOptions {
+    /// Returns the options of [`Line::new`], which reads lines as the
+    /// reference implementation does.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Keeps the fractional part of coordinates instead of flooring them.
+    pub fn precise(mut self, precise: bool) -> Self {
+        self.precise = precise;
+        self
+    }
+
+    /// Rejects the lines that [`Line::new`] reads loosely: fields that are
+    /// not numbers, infinite or NaN coordinates, which would otherwise make
+    /// the line unknown, and type codes that are negative or too large,
+    /// which would otherwise be clamped.
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    pub fn is_precise(&self) -> bool {
+        self.precise
+    }
+
+    pub fn is_strict(&self) -> bool {
+        self.strict
+    }
+}
+
+/// Why [`Line::parse`] rejected a line in strict mode. Fields are numbered
+/// from 1.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LineError {
+    /// The field must be a number.
+    NotANumber { field: usize },
+    /// The coordinate must be finite.
+    NotFinite { field: usize, value: f64 },
+    /// The type code must be a whole number from 0 to `u32::MAX`.
+    TypeCode { field: usize, value: f64 },
+}
+
+impl fmt::Display for LineError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LineError::NotANumber { field } => write!(f, "field {field} is not a number"),
+            LineError::NotFinite { field, value } => {
+                write!(f, "field {field} must be finite, got {value}")
+            }
+            LineError::TypeCode { field, value } => {
+                write!(f, "field {field} is not a valid type code, got {value}")
+            }
+        }
+    }
+}
+
+impl core::error::Error for LineError {}
+
 impl<'a> Line<'a> {
+    /// Reads a line of glyph data as the reference implementation does:
+    /// numbers are floored, type codes out of range are clamped, and lines
+    /// that cannot be read, or have an infinite or NaN number, are
+    /// [`Line::Unknown`].
     pub fn new(line_data: &'a str) -> Self {
+        Self::parse(line_data, &LineOptions::new()).unwrap_or(Line::Unknown)
+    }
+
+    /// Reads a line of glyph data with the given options. Only strict
+    /// options return errors; an empty line is [`Line::Unknown`].
+    pub fn parse(line_data: &'a str, options: &LineOptions) -> Result<Self, LineError> {
         #[derive(Clone, Copy)]
         enum FieldType<'a> {
             Num(f64),
             Str(&'a str),
         }
 
+        if options.strict && line_data.trim().is_empty() {
+            return Ok(Line::Unknown);
+        }
+
         let mut tmp = if line_data.contains("::") {
             line_data.split("::")
         } else {
@@ -41,60 +123,69 @@ impl<'a> Line<'a> {
         };
         let extra_line_data = tmp.next();
 
-        let mut fields = main_line_data
-            // I think this implementation is very naïve, but this is
-            // what the initial JavaScript version does.
-            .split(':')
-            .map(|each| {
-                if let Ok(parsed) = each.trim().parse::<f64>() {
-                    FieldType::Num(parsed)
-                } else {
-                    FieldType::Str(each)
-                }
-            })
-            .chain(core::iter::repeat(FieldType::Num(0.0)))
-            .take(11);
-
-        macro_rules! next_num_field {
-            ($fields:expr) => {{
-                match $fields.next() {
-                    Some(FieldType::Num(field)) => field.floor(),
-                    _ => return Line::Unknown,
-                }
-            }};
+        let mut fields = [FieldType::Num(0.0); 11];
+        // I think this implementation is very naïve, but this is
+        // what the initial JavaScript version does.
+        for (field, each) in fields.iter_mut().zip(main_line_data.split(':')) {
+            *field = if let Ok(parsed) = each.trim().parse::<f64>() {
+                FieldType::Num(parsed)
+            } else {
+                FieldType::Str(each)
+            };
         }
 
-        macro_rules! get_field_num {
-            ($field:expr) => {{
-                match $field {
-                    FieldType::Num(field) => field.floor(),
-                    _ => return Line::Unknown,
+        // a line with a field that is not a number is unknown, unless strict
+        let not_a_number = |field: usize| {
+            if options.strict {
+                Err(LineError::NotANumber { field })
+            } else {
+                Ok(Line::Unknown)
+            }
+        };
+        let mut numbers = [0.0; 11];
+        for (index, field) in fields.iter().enumerate() {
+            match *field {
+                FieldType::Num(value) if !value.is_finite() => {
+                    if options.strict {
+                        return Err(LineError::NotFinite {
+                            field: index + 1,
+                            value,
+                        });
+                    }
+                    return Ok(Line::Unknown);
                 }
-            }};
+                FieldType::Num(value) if options.precise => numbers[index] = value,
+                FieldType::Num(value) => numbers[index] = value.floor(),
+                // field 8 is the name of a component
+                FieldType::Str(_) if index == 7 => {}
+                FieldType::Str(_) => return not_a_number(index + 1),
+            }
         }
+        let [
+            field_1,
+            field_2,
+            field_3,
+            field_4,
+            field_5,
+            field_6,
+            field_7,
+            field_8,
+            field_9,
+            field_10,
+            field_11,
+        ] = numbers;
 
-        macro_rules! get_field_str {
-            ($field:expr) => {{
-                match $field {
-                    FieldType::Str(field) => field,
-                    _ => return Line::Unknown,
-                }
-            }};
+        // fields 2 and 3 are coordinates in a component reference
+        let code_count = if field_1.floor() == 99.0 { 1 } else { 3 };
+        for (index, value) in [field_1, field_2, field_3][..code_count].iter().enumerate() {
+            if options.strict && !(0.0..=u32::MAX as f64).contains(&value.floor()) {
+                return Err(LineError::TypeCode {
+                    field: index + 1,
+                    value: *value,
+                });
+            }
         }
 
-        let field_1 = next_num_field!(fields);
-        let field_2 = next_num_field!(fields);
-        let field_3 = next_num_field!(fields);
-        let field_4 = next_num_field!(fields);
-        let field_5 = next_num_field!(fields);
-        let field_6 = next_num_field!(fields);
-        let field_7 = next_num_field!(fields);
-        let field_8 = fields
-            .next()
-            .expect("The length of `fields` is equal to 11."); // field 7 is special
-        let field_9 = next_num_field!(fields);
-        let field_10 = next_num_field!(fields);
-        let field_11 = next_num_field!(fields);
         let color = match extra_line_data {
             Some(content) => {
                 let mut tmp = content.split(":");
@@ -131,15 +222,24 @@ impl<'a> Line<'a> {
             None => None,
         };
 
-        match (field_1 as u32, field_2 as u32, field_3 as u32) {
-            (99, _, _) => Line::ComponentReferenceLine(ComponentReferenceLine {
-                component_name: get_field_str!(field_8),
-                box_diag_1: (field_4, field_5, None).into(),
-                box_diag_2: (field_6, field_7, None).into(),
-                primary_control_point: (field_2, field_3, None).into(), // None or Some?
-                secondary_control_point: (field_10, field_11, None).into(), // None or Some?
-                color,
-            }),
+        let line = match (
+            field_1.floor() as u32,
+            field_2.floor() as u32,
+            field_3.floor() as u32,
+        ) {
+            (99, _, _) => match fields[7] {
+                FieldType::Str(component_name) => {
+                    Line::ComponentReferenceLine(ComponentReferenceLine {
+                        component_name,
+                        box_diag_1: (field_4, field_5, None).into(),
+                        box_diag_2: (field_6, field_7, None).into(),
+                        primary_control_point: (field_2, field_3, None).into(), // None or Some?
+                        secondary_control_point: (field_10, field_11, None).into(), // None or Some?
+                        color,
+                    })
+                }
+                FieldType::Num(_) => Line::Unknown,
+            },
             // special line
             (0, 99, 1) => Line::SpecialLine(SpecialLineType {
                 transform_type: TransformType::Rotate90,
@@ -167,15 +267,18 @@ impl<'a> Line<'a> {
                 box_diag_2: (field_6, field_7, None).into(),
             }),
             _ => {
+                if let FieldType::Str(_) = fields[7] {
+                    return not_a_number(8);
+                }
                 let stroke_result = StrokeLineType::new(
-                    field_1,
-                    field_2,
-                    field_3,
+                    field_1.floor(),
+                    field_2.floor(),
+                    field_3.floor(),
                     field_4,
                     field_5,
                     field_6,
                     field_7,
-                    get_field_num!(field_8),
+                    field_8,
                     field_9,
                     field_10,
                     field_11,
@@ -187,7 +290,9 @@ impl<'a> Line<'a> {
                     _ => Line::StrokeLine(stroke_result),
                 }
             }
-        }
+        };
+
+        Ok(line)
     }
 }
 
@@ -236,7 +341,8 @@ impl<'a> LineVecTrait for Vec<Line<'a>> {
                         component_reference_line.box_diag_2.y,
                     ));
                     result.push_str(&format!(
-                        "0:{}:{}",
+                        "{}:0:{}:{}",
+                        component_reference_line.component_name,
                         component_reference_line.secondary_control_point.x,
                         component_reference_line.secondary_control_point.y,
                     ));
@@ -246,8 +352,8 @@ impl<'a> LineVecTrait for Vec<Line<'a>> {
                     result.push_str(&format!(
                         "{}:{}:{}:",
                         stroke_line.stroke_type.base,
-                        stroke_line.head_shape.base + stroke_line.head_shape.opt * 100,
-                        stroke_line.tail_shape.base + stroke_line.tail_shape.opt * 100,
+                        stroke_line.head_shape.number(),
+                        stroke_line.tail_shape.number(),
                     ));
                     result.push_str(&format!(
                         "{}:{}:{}:{}",
@@ -286,7 +392,7 @@ impl<'a> LineVecTrait for Vec<Line<'a>> {
 
 #[cfg(test)]
 mod test {
-    use crate::line::Line;
+    use crate::line::{Line, LineError, LineOptions, LineVecTrait};
 
     #[test]
     fn test_line_init() {
@@ -306,4 +412,73 @@ mod test {
         println!("{:#?}", line_stroke_2);
         println!("{:#?}", line_stroke_3);
     }
+
+    #[test]
+    fn test_generate_kage() {
+        let data = "99:0:0:41:0:172:200:u4e3f-07:0:0:0$1:12:413:26:42:87:42$";
+        let lines: Vec<Line> = data.split_terminator('$').map(Line::new).collect();
+        assert_eq!(lines.generate_kage(), data);
+
+        // a type code out of range has no representation to write back
+        assert_eq!(
+            Line::parse("1:1e20:0:26:42:87:42", &LineOptions::new().strict(true)),
+            Err(LineError::TypeCode {
+                field: 2,
+                value: 1e20
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse() {
+        let precise = LineOptions::new().precise(true);
+        let Ok(Line::StrokeLine(stroke)) = Line::parse("1:0:0:20.5:30.25:180:30.25", &precise)
+        else {
+            panic!("not a stroke");
+        };
+        assert_eq!(stroke.points()[0].x(), 20.5);
+        assert_eq!(stroke.points()[0].y(), 30.25);
+        let Line::StrokeLine(stroke) = Line::new("1:0:0:20.5:30.25:180:30.25") else {
+            panic!("not a stroke");
+        };
+        assert_eq!(stroke.points()[0].x(), 20.0);
+
+        // legacy reading clamps what strict reading rejects
+        let strict = LineOptions::new().strict(true);
+        assert_eq!(
+            Line::parse("1:-2:0:20:30:180:30", &strict),
+            Err(LineError::TypeCode {
+                field: 2,
+                value: -2.0
+            })
+        );
+        assert_eq!(
+            Line::parse("1:0:0:20:inf:180:30", &strict),
+            Err(LineError::NotFinite {
+                field: 5,
+                value: f64::INFINITY
+            })
+        );
+        assert_eq!(
+            Line::parse("1:0:0:20:30:x:30", &strict),
+            Err(LineError::NotANumber { field: 6 })
+        );
+        assert!(matches!(
+            Line::new("1:-2:0:20:30:180:30"),
+            Line::StrokeLine(_)
+        ));
+        assert_eq!(Line::new("1:0:0:20:inf:180:30"), Line::Unknown);
+        assert_eq!(Line::new("2:0:7:169:25:131e578:140:20:150"), Line::Unknown);
+
+        // fields 2 and 3 of a component reference are coordinates
+        assert!(matches!(
+            Line::parse("99:-20:30:0:0:200:200:u53e3", &strict),
+            Ok(Line::ComponentReferenceLine(_))
+        ));
+        assert_eq!(Line::parse("", &strict), Ok(Line::Unknown));
+        assert_eq!(
+            Line::parse("1:0:0:x", &LineOptions::new()),
+            Ok(Line::Unknown)
+        );
+    }
 }
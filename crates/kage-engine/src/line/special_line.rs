@@ -1,7 +1,8 @@
-use crate::utils::Point;
+use crate::{font::FontWrapper, polygons::Polygons, utils::Point};
 
+/// The transformation a special line applies to its box.
 #[derive(Debug, PartialEq, Clone, Copy)]
-pub(crate) enum TransformType {
+pub enum TransformType {
     Rotate90,
     Rotate180,
     Rotate270,
@@ -15,3 +16,43 @@ pub struct SpecialLineType {
     pub(crate) box_diag_1: Point,
     pub(crate) box_diag_2: Point,
 }
+
+impl SpecialLineType {
+    pub fn transform_type(&self) -> TransformType {
+        self.transform_type
+    }
+
+    /// The two corners of the box, as written in the glyph data.
+    pub fn box_diag(&self) -> [Point; 2] {
+        [self.box_diag_1, self.box_diag_2]
+    }
+
+    /// Applies the transformation to the polygons lying entirely in the
+    /// box, as the built-in typefaces do.
+    pub fn apply(&self, polygons: &mut Polygons) {
+        FontWrapper::df_transform(polygons, *self);
+    }
+
+    /// Whether the point lies in the box the transformation applies to.
+    pub fn contains(&self, point: Point) -> bool {
+        self.box_diag_1.x <= point.x
+            && point.x <= self.box_diag_2.x
+            && self.box_diag_1.y <= point.y
+            && point.y <= self.box_diag_2.y
+    }
+
+    /// Maps a point the way `df_transform` maps the polygons in the box,
+    /// without flooring.
+    pub fn transform_point(&self, point: Point) -> Point {
+        let (b1, b2) = (self.box_diag_1, self.box_diag_2);
+        let (x, y) = match self.transform_type {
+            TransformType::HorizontalFlip => (b1.x + b2.x - point.x, point.y),
+            TransformType::VerticalFlip => (point.x, b1.y + b2.y - point.y),
+            TransformType::Rotate90 => (b1.x + b2.y - point.y, b1.y - b1.x + point.x),
+            TransformType::Rotate180 => (b1.x + b2.x - point.x, b1.y + b2.y - point.y),
+            TransformType::Rotate270 => (b1.x - b1.y + point.y, b2.y + b1.x - point.x),
+        };
+
+        Point::new(x, y, point.off_curve)
+    }
+}
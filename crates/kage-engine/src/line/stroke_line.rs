@@ -6,6 +6,7 @@ pub(crate) fn stretch_numeric(
     origin_point: f64,
     min: f64,
     max: f64,
+    floor: bool,
 ) -> f64 {
     let (p1, p2, p3, p4) = if origin_point < src_pivot + 100.0 {
         (min, src_pivot + 100.0, min, dest_pivot + 100.0)
@@ -13,7 +14,13 @@ pub(crate) fn stretch_numeric(
         (src_pivot + 100.0, max, dest_pivot + 100.0, max)
     };
 
-    ((origin_point - p1) / (p2 - p1) * (p4 - p3) + p3).floor()
+    // a range of one point, where the point can only be its start
+    let value = if p2 == p1 {
+        p3
+    } else {
+        (origin_point - p1) / (p2 - p1) * (p4 - p3) + p3
+    };
+    if floor { value.floor() } else { value }
 }
 
 pub(crate) fn stretch<P1, P2, P3, P4, P5>(
@@ -22,6 +29,7 @@ pub(crate) fn stretch<P1, P2, P3, P4, P5>(
     origin_point: P3,
     min_point: P4,
     max_point: P5,
+    floor: bool,
 ) -> Point
 where
     P1: Into<Point>,
@@ -36,14 +44,28 @@ where
     let min: Point = min_point.into();
     let max: Point = max_point.into();
 
-    let x = stretch_numeric(dest_pivot.x, src_pivot.x, origin_point.x, min.x, max.x);
-    let y = stretch_numeric(dest_pivot.y, src_pivot.y, origin_point.y, min.y, max.y);
+    let x = stretch_numeric(
+        dest_pivot.x,
+        src_pivot.x,
+        origin_point.x,
+        min.x,
+        max.x,
+        floor,
+    );
+    let y = stretch_numeric(
+        dest_pivot.y,
+        src_pivot.y,
+        origin_point.y,
+        min.y,
+        max.y,
+        floor,
+    );
 
     Point::new(x, y, origin_point.off_curve)
 }
 
 #[derive(Debug, PartialEq, Clone, Copy)]
-pub(crate) enum StrokeKind {
+pub enum StrokeKind {
     // 1 ~ 6: Stroke Lines
     /// 直線
     StraightLine = 1, // 2 control point
@@ -100,7 +122,7 @@ impl StrokeType {
 }
 
 #[derive(Debug, PartialEq, Clone, Copy)]
-pub(crate) enum EndKind {
+pub enum EndKind {
     /// 開放
     Free = 0,
     /// 連接（橫向）
@@ -203,6 +225,12 @@ impl EndType {
             kind,
         }
     }
+
+    /// Returns the number of the end type, options included, which does not
+    /// always fit in a `u32` when it was parsed from a huge number.
+    pub(crate) fn number(&self) -> u64 {
+        u64::from(self.base) + u64::from(self.opt) * 100
+    }
 }
 
 #[derive(Debug, PartialEq)]
@@ -225,6 +253,28 @@ pub struct StrokeLineType {
 }
 
 impl StrokeLineType {
+    pub fn kind(&self) -> StrokeKind {
+        self.stroke_type.kind
+    }
+
+    pub fn head(&self) -> EndKind {
+        self.head_shape.kind
+    }
+
+    pub fn tail(&self) -> EndKind {
+        self.tail_shape.kind
+    }
+
+    /// Returns the four control points of the stroke. Strokes with fewer
+    /// points leave the last ones at the origin.
+    pub fn points(&self) -> [Point; 4] {
+        [self.point_1, self.point_2, self.point_3, self.point_4]
+    }
+
+    pub fn color(&self) -> Option<Rgb> {
+        self.color
+    }
+
     pub(crate) fn new(
         field_1: f64,
         field_2: f64,
@@ -302,23 +352,29 @@ impl StrokeLineType {
     //         .any(|(p1, p2)| two_d::is_cross_box(p1, p2, box_diag_1, box_diag_2))
     // }
 
+    /// Stretches the stroke as a component reference with control points
+    /// does, flooring the coordinates unless `floor` is false.
     pub(crate) fn stretch<P1, P2, P3, P4>(
         &mut self,
         dest_pivot: P1,
         src_pivot: P2,
         min_point: P3,
         max_point: P4,
+        floor: bool,
     ) where
         P1: Into<Point> + Copy,
         P2: Into<Point> + Copy,
         P3: Into<Point> + Copy,
         P4: Into<Point> + Copy,
     {
-        self.point_1 = stretch(dest_pivot, src_pivot, self.point_1, min_point, max_point);
-        self.point_2 = stretch(dest_pivot, src_pivot, self.point_2, min_point, max_point);
-        // if !(this.a1_100 === 99 && this.a1_opt === 0) {  }
-        self.point_3 = stretch(dest_pivot, src_pivot, self.point_3, min_point, max_point);
-        self.point_4 = stretch(dest_pivot, src_pivot, self.point_4, min_point, max_point);
+        for point in [
+            &mut self.point_1,
+            &mut self.point_2,
+            &mut self.point_3,
+            &mut self.point_4,
+        ] {
+            *point = stretch(dest_pivot, src_pivot, *point, min_point, max_point, floor);
+        }
     }
 
     pub(crate) fn get_box(&self) -> Bounds {
@@ -391,6 +447,7 @@ mod test {
             (0.0, 150.0),
             (0.0, 0.0),
             (1000.0, 1000.0),
+            true,
         );
         assert_eq!(result1, (0.0, 250.0).into());
 
@@ -400,15 +457,27 @@ mod test {
             (299.0, 300.0),
             (0.0, 0.0),
             (1000.0, 1000.0),
+            true,
         );
         assert_eq!(result2, (498.0, 500.0).into());
 
+        let unfloored = stretch(
+            (400.0, 400.0),
+            (200.0, 200.0),
+            (299.0, 300.0),
+            (0.0, 0.0),
+            (1000.0, 1000.0),
+            false,
+        );
+        assert!((unfloored.x - 498.333).abs() < 0.001 && unfloored.y == 500.0);
+
         let result3 = stretch(
             (400.0, 400.0),
             (200.0, 200.0),
             (650.0, 1000.0),
             (0.0, 0.0),
             (1000.0, 1000.0),
+            true,
         );
         assert_eq!(result3, (750.0, 1000.0).into());
 
@@ -420,6 +489,7 @@ mod test {
             (123.0, 400.0),
             (0.0, 0.0),
             (1000.0, 1000.0),
+            true,
         );
         assert_eq!(result4, (123.0, 400.0).into());
 
@@ -429,6 +499,7 @@ mod test {
             (999.0, 1000.0),
             (0.0, 0.0),
             (1000.0, 1000.0),
+            true,
         );
         assert_eq!(result5, (999.0, 1000.0).into());
 
@@ -440,6 +511,7 @@ mod test {
             (-200.0, -50.0),
             (-200.0, -200.0),
             (800.0, 800.0),
+            true,
         );
         assert_eq!(result6, (-200.0, 250.0).into());
 
@@ -449,6 +521,7 @@ mod test {
             (0.0, 800.0),
             (-200.0, -200.0),
             (800.0, 800.0),
+            true,
         );
         assert_eq!(result7, (400.0, 800.0).into());
 
@@ -459,8 +532,10 @@ mod test {
             (1000.0, 999.0),
             (0.0, 0.0),
             (1000.0, 1000.0),
+            true,
         );
-        assert!(result8.x.is_nan() && result8.y == 499.0);
+        // the range from the pivot to the maximum is a single point
+        assert_eq!(result8, (500.0, 499.0).into());
 
         // === sp = 500, dp = 100
         // origin point: 300.0, 600.0, 900.0
@@ -470,6 +545,7 @@ mod test {
             (300.0, 600.0),
             (0.0, 0.0),
             (1000.0, 1000.0),
+            true,
         );
         assert_eq!(result9, (100.0, 200.0).into());
 
@@ -479,6 +555,7 @@ mod test {
             (900.0, 900.0),
             (0.0, 0.0),
             (1000.0, 1000.0),
+            true,
         );
         assert_eq!(result10, (800.0, 800.0).into());
     }
@@ -695,7 +772,7 @@ mod test {
             }
         );
 
-        stroke6.stretch((0.0, 0.0), (0.0, 0.0), (0.0, 0.0), (200.0, 200.0));
+        stroke6.stretch((0.0, 0.0), (0.0, 0.0), (0.0, 0.0), (200.0, 200.0), true);
 
         assert_eq!(
             stroke6.get_box(),
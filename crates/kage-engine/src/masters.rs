@@ -0,0 +1,268 @@
+use core::{fmt, mem};
+
+use crate::{
+    font::{
+        FontWrapper,
+        parameters::{ParameterError, Parameters},
+    },
+    kage::Kage,
+    line::Line,
+    polygons::Polygons,
+};
+
+/// Masters of a family along the weight axis, drawn compatibly: every
+/// master draws a glyph with the same contours, the same number of points
+/// per contour and the same on- and off-curve points, so that the outlines
+/// can be interpolated, e.g. in a variable font.
+///
+/// The first master is the default one. To keep the point counts equal,
+/// every master samples curves with the `sample_step` of the default
+/// master, and strokes are adjusted as in the default master.
+pub struct Masters {
+    masters: Vec<(f64, FontWrapper)>,
+}
+
+impl Masters {
+    /// Creates the masters from their weight (on the OpenType `wght`
+    /// scale) and their parameters, which must be of the same typeface.
+    pub fn new(masters: Vec<(f64, Parameters)>) -> Result<Self, MasterError> {
+        let Some((_, default)) = masters.first() else {
+            return Err(MasterError::Empty);
+        };
+        let sample_step = default.sample_step();
+        let typeface = mem::discriminant(default);
+
+        let mut fonts = Vec::with_capacity(masters.len());
+        for (weight, mut parameters) in masters {
+            if mem::discriminant(&parameters) != typeface {
+                return Err(MasterError::MixedTypefaces);
+            }
+            if !weight.is_finite() || fonts.iter().any(|(other, _)| *other == weight) {
+                return Err(MasterError::Weight(weight));
+            }
+            parameters.set_sample_step(sample_step);
+            fonts.push((weight, FontWrapper::from_parameters(parameters)?));
+        }
+
+        Ok(Self { masters: fonts })
+    }
+
+    pub fn len(&self) -> usize {
+        self.masters.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.masters.is_empty()
+    }
+
+    /// Returns the weight of each master, the default one first.
+    pub fn weights(&self) -> impl Iterator<Item = f64> + '_ {
+        self.masters.iter().map(|(weight, _)| *weight)
+    }
+}
+
+/// Why masters cannot be drawn compatibly.
+#[derive(Debug, Clone, PartialEq)]
+pub enum MasterError {
+    /// No master was given.
+    Empty,
+    Parameters(ParameterError),
+    /// The masters are not all of the same typeface.
+    MixedTypefaces,
+    /// The weight is not finite or is the weight of another master.
+    Weight(f64),
+    /// The glyph drawn with the master does not have the same structure as
+    /// in the default master: `contour` is the first contour that differs,
+    /// or `None` if the number of contours differs.
+    Incompatible {
+        master: usize,
+        contour: Option<usize>,
+    },
+}
+
+impl From<ParameterError> for MasterError {
+    fn from(error: ParameterError) -> Self {
+        MasterError::Parameters(error)
+    }
+}
+
+impl fmt::Display for MasterError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MasterError::Empty => write!(f, "no master"),
+            MasterError::Parameters(error) => error.fmt(f),
+            MasterError::MixedTypefaces => write!(f, "masters of different typefaces"),
+            MasterError::Weight(weight) => write!(f, "invalid or duplicate weight {weight}"),
+            MasterError::Incompatible {
+                master,
+                contour: Some(contour),
+            } => write!(f, "contour {contour} of master {master} is incompatible"),
+            MasterError::Incompatible {
+                master,
+                contour: None,
+            } => write!(f, "master {master} has a different number of contours"),
+        }
+    }
+}
+
+impl core::error::Error for MasterError {}
+
+impl Kage {
+    /// Draws the glyph with every master, in the order of the masters.
+    ///
+    /// Returns an error if a master still draws the glyph with another
+    /// structure than the default master, e.g. because a shape of the
+    /// typeface depends on a width.
+    pub fn make_compatible_glyphs(
+        &self,
+        masters: &Masters,
+        data: &str,
+    ) -> Result<Vec<Polygons>, MasterError> {
+        let lines: Vec<Line> = if data.is_empty() {
+            Vec::new()
+        } else {
+            self.get_each_expanded_line(data)
+        };
+        let Some((_, default)) = masters.masters.first() else {
+            return Err(MasterError::Empty);
+        };
+        let adjustments = default.adjust_strokes(&lines);
+
+        let mut glyphs: Vec<Polygons> = Vec::with_capacity(masters.len());
+        for (master, (_, font)) in masters.masters.iter().enumerate() {
+            let mut polygons = Polygons::new_compatible();
+            font.draw_lines_with_adjustments(&mut polygons, &lines, &adjustments, |_, _| {});
+
+            if let Some(reference) = glyphs.first() {
+                check_compatible(reference, &polygons)
+                    .map_err(|contour| MasterError::Incompatible { master, contour })?;
+            }
+            glyphs.push(polygons);
+        }
+
+        Ok(glyphs)
+    }
+}
+
+/// Returns the first contour of `polygons` whose points do not match
+/// `reference`, or `Err(None)` if the number of contours differs.
+fn check_compatible(reference: &Polygons, polygons: &Polygons) -> Result<(), Option<usize>> {
+    if reference.len() != polygons.len() {
+        return Err(None);
+    }
+    for (index, (expected, polygon)) in reference.array().zip(polygons.array()).enumerate() {
+        let expected = expected.points();
+        let points = polygon.points();
+        if expected.len() != points.len()
+            || expected
+                .iter()
+                .zip(points)
+                .any(|(expected, point)| expected.is_off_curve() != point.is_off_curve())
+        {
+            return Err(Some(index));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::font::{gothic::Gothic, ming::Ming, weight::Interpolate};
+
+    const DATA: &str = "1:0:2:32:31:176:31$2:22:7:176:31:170:43:156:63$1:0:0:20:100:180:100$1:0:13:40:60:40:180$1:0:4:160:60:160:180";
+
+    fn ming_masters(use_curve: bool) -> Masters {
+        let regular = Ming::new(use_curve);
+        Masters::new(vec![
+            (400.0, Parameters::Ming(regular.clone())),
+            (200.0, Parameters::Ming(regular.at_weight(200.0))),
+            (900.0, Parameters::Ming(regular.at_weight(900.0))),
+        ])
+        .unwrap()
+    }
+
+    #[test]
+    fn test_compatible_glyphs() {
+        let kage = Kage::new(crate::Typeface::Ming, false);
+
+        for use_curve in [false, true] {
+            let glyphs = kage
+                .make_compatible_glyphs(&ming_masters(use_curve), DATA)
+                .unwrap();
+            assert_eq!(glyphs.len(), 3);
+            assert!(!glyphs[0].is_empty());
+            for glyph in &glyphs[1..] {
+                assert_eq!(check_compatible(&glyphs[0], glyph), Ok(()));
+            }
+        }
+
+        let glyphs = kage
+            .make_compatible_glyphs(&ming_masters(false), "")
+            .unwrap();
+        assert!(glyphs.iter().all(Polygons::is_empty));
+    }
+
+    #[test]
+    fn test_width_dependent_shapes() {
+        let mut kage = Kage::new(crate::Typeface::Ming, false);
+        kage.components.push(
+            "u6728",
+            "1:0:0:20:60:180:60$1:0:4:100:20:100:180$2:32:7:100:60:70:120:20:160$2:7:0:100:60:130:120:180:160",
+        );
+        let regular = Ming::new(false);
+
+        for weight in [800.0, 900.0] {
+            let masters = Masters::new(vec![
+                (400.0, Parameters::Ming(regular.clone())),
+                (weight, Parameters::Ming(regular.at_weight(weight))),
+            ])
+            .unwrap();
+            // the edge of the curve cut at the vertical connection
+            let forest =
+                "99:0:0:40:0:160:100:u6728$99:0:0:0:90:110:200:u6728$99:0:0:90:90:200:200:u6728";
+            assert!(kage.make_compatible_glyphs(&masters, forest).is_ok());
+            // the tail of a bend too short for the curve size of heavy masters
+            assert!(
+                kage.make_compatible_glyphs(&masters, "3:32:5:65:31:65:117:79:117")
+                    .is_ok()
+            );
+        }
+    }
+
+    #[test]
+    fn test_masters() {
+        assert_eq!(Masters::new(Vec::new()).err(), Some(MasterError::Empty));
+        assert_eq!(
+            Masters::new(vec![
+                (400.0, Parameters::Ming(Ming::default())),
+                (700.0, Parameters::Gothic(Gothic::default())),
+            ])
+            .err(),
+            Some(MasterError::MixedTypefaces)
+        );
+        assert_eq!(
+            Masters::new(vec![
+                (400.0, Parameters::Gothic(Gothic::default())),
+                (400.0, Parameters::Gothic(Gothic::default())),
+            ])
+            .err(),
+            Some(MasterError::Weight(400.0))
+        );
+
+        // the sample step of the default master is used throughout
+        let mut bold = Gothic::default().at_weight(700.0);
+        bold.sample_step = 50;
+        let masters = Masters::new(vec![
+            (400.0, Parameters::Gothic(Gothic::default())),
+            (700.0, Parameters::Gothic(bold)),
+        ])
+        .unwrap();
+        assert_eq!(masters.weights().collect::<Vec<_>>(), vec![400.0, 700.0]);
+        let kage = Kage::new(crate::Typeface::Gothic, false);
+        assert!(
+            kage.make_compatible_glyphs(&masters, "2:7:8:30:40:80:120:170:170")
+                .is_ok()
+        );
+    }
+}
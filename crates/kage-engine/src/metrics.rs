@@ -0,0 +1,143 @@
+use crate::polygons::Polygons;
+
+/// Size of the glyph box, which is one em.
+pub const EM: f64 = 200.0;
+
+/// How the advance width of a glyph is chosen.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum MetricsPolicy {
+    /// The whole glyph box, as for ideographs.
+    #[default]
+    FullWidth,
+    /// The left half of the glyph box, as for half-width kana, punctuation
+    /// and Latin letters drawn in it.
+    HalfWidth,
+    /// The ink of the glyph with `side_bearing` on each side. The glyph is
+    /// moved horizontally so that its left side bearing is `side_bearing`;
+    /// an empty glyph is half-width.
+    Proportional { side_bearing: f64 },
+}
+
+impl MetricsPolicy {
+    /// Returns the usual policy of a character in a CJK font: half-width
+    /// for ASCII and the half-width forms, full-width otherwise.
+    pub fn for_char(c: char) -> Self {
+        match c {
+            '\u{20}'..='\u{7e}' | '\u{ff61}'..='\u{ffdc}' | '\u{ffe8}'..='\u{ffee}' => {
+                MetricsPolicy::HalfWidth
+            }
+            _ => MetricsPolicy::FullWidth,
+        }
+    }
+}
+
+/// Horizontal and vertical metrics of a glyph, in glyph units (200 per em,
+/// y pointing down).
+///
+/// In horizontal layout the origin is on the left edge of the glyph box,
+/// moved by `x_offset`. In vertical layout the origin is at the middle of
+/// the top edge of the glyph box (`vertical_origin_y`, which a CFF font
+/// writes in its `VORG` table) and the advance is always one em.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GlyphMetrics {
+    /// The bounds of the ink as `[min_x, min_y, max_x, max_y]`, after
+    /// moving the glyph by `x_offset`, or `None` for an empty glyph.
+    pub bounds: Option<[f64; 4]>,
+    /// How far the glyph must be moved to the right to fit its advance.
+    pub x_offset: f64,
+    pub advance_width: f64,
+    pub left_side_bearing: f64,
+    pub advance_height: f64,
+    pub top_side_bearing: f64,
+    pub vertical_origin_y: f64,
+}
+
+impl Polygons {
+    /// Returns the bounds of the points as `[min_x, min_y, max_x, max_y]`,
+    /// or `None` if there is no polygon. Off-curve points are included, so
+    /// the box may be slightly larger than the ink.
+    pub fn bounds(&self) -> Option<[f64; 4]> {
+        self.array()
+            .flat_map(|polygon| polygon.points())
+            .fold(None, |bounds, point| {
+                let [min_x, min_y, max_x, max_y] =
+                    bounds.unwrap_or([point.x, point.y, point.x, point.y]);
+                Some([
+                    min_x.min(point.x),
+                    min_y.min(point.y),
+                    max_x.max(point.x),
+                    max_y.max(point.y),
+                ])
+            })
+    }
+
+    /// Computes the metrics of the glyph with the given policy.
+    pub fn metrics(&self, policy: MetricsPolicy) -> GlyphMetrics {
+        let bounds = self.bounds();
+
+        let (x_offset, advance_width) = match (policy, bounds) {
+            (MetricsPolicy::FullWidth, _) => (0.0, EM),
+            (MetricsPolicy::HalfWidth, _) | (MetricsPolicy::Proportional { .. }, None) => {
+                (0.0, EM / 2.0)
+            }
+            (MetricsPolicy::Proportional { side_bearing }, Some([min_x, _, max_x, _])) => {
+                (side_bearing - min_x, max_x - min_x + 2.0 * side_bearing)
+            }
+        };
+        let bounds = bounds
+            .map(|[min_x, min_y, max_x, max_y]| [min_x + x_offset, min_y, max_x + x_offset, max_y]);
+
+        GlyphMetrics {
+            bounds,
+            x_offset,
+            advance_width,
+            left_side_bearing: bounds.map_or(0.0, |bounds| bounds[0]),
+            advance_height: EM,
+            top_side_bearing: bounds.map_or(0.0, |bounds| bounds[1]),
+            vertical_origin_y: 0.0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{Typeface, kage::Kage};
+
+    #[test]
+    fn test_metrics() {
+        let kage = Kage::new(Typeface::Gothic, false);
+        let mut polygons = Polygons::new();
+        kage.make_glyph_with_data(&mut polygons, "1:0:0:40:100:120:100");
+        let [min_x, min_y, max_x, _] = polygons.bounds().unwrap();
+
+        let full = polygons.metrics(MetricsPolicy::FullWidth);
+        assert_eq!(full.advance_width, 200.0);
+        assert_eq!(full.advance_height, 200.0);
+        assert_eq!(full.left_side_bearing, min_x);
+        assert_eq!(full.top_side_bearing, min_y);
+        assert_eq!(full.x_offset, 0.0);
+
+        assert_eq!(
+            polygons.metrics(MetricsPolicy::HalfWidth).advance_width,
+            100.0
+        );
+
+        let proportional = polygons.metrics(MetricsPolicy::Proportional { side_bearing: 10.0 });
+        assert_eq!(proportional.left_side_bearing, 10.0);
+        assert_eq!(proportional.x_offset, 10.0 - min_x);
+        assert_eq!(proportional.advance_width, max_x - min_x + 20.0);
+
+        let empty = Polygons::new().metrics(MetricsPolicy::Proportional { side_bearing: 10.0 });
+        assert_eq!(empty.bounds, None);
+        assert_eq!(empty.advance_width, 100.0);
+    }
+
+    #[test]
+    fn test_for_char() {
+        assert_eq!(MetricsPolicy::for_char('A'), MetricsPolicy::HalfWidth);
+        assert_eq!(MetricsPolicy::for_char('ｱ'), MetricsPolicy::HalfWidth);
+        assert_eq!(MetricsPolicy::for_char('ア'), MetricsPolicy::FullWidth);
+        assert_eq!(MetricsPolicy::for_char('一'), MetricsPolicy::FullWidth);
+    }
+}
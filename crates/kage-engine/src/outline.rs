@@ -0,0 +1,589 @@
+use std::collections::HashMap;
+
+use crate::{
+    polygons::Polygons,
+    postscript::number,
+    raster::RasterOptions,
+    utils::{Point, Vector},
+};
+
+/// The largest width or height, in pixels, of the bitmap the polygons are
+/// traced on. Glyphs that would need a larger one are traced at a lower
+/// resolution.
+pub const MAX_OUTLINE_PIXELS: usize = 2048;
+
+/// Options of the outline tracer.
+///
+/// The polygons are rasterized at `resolution` pixels per glyph unit, the
+/// union is optionally grown or shrunk by `offset` glyph units, and the
+/// resulting boundary is traced back into contours simplified to within
+/// `tolerance` glyph units.
+#[derive(Debug, Clone)]
+pub struct OutlineOptions {
+    resolution: f64,
+    offset: f64,
+    tolerance: f64,
+}
+
+impl Default for OutlineOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl OutlineOptions {
+    pub fn new() -> Self {
+        Self {
+            resolution: 4.0,
+            offset: 0.0,
+            tolerance: 0.2,
+        }
+    }
+
+    /// Sets the number of pixels per glyph unit the polygons are traced
+    /// at. Higher values are more accurate and slower.
+    pub fn resolution(mut self, resolution: f64) -> Self {
+        self.resolution = resolution.max(0.25);
+        self
+    }
+
+    /// Moves the contours outward (positive, for a bolder glyph) or inward
+    /// (negative, for a lighter one) by the given number of glyph units.
+    pub fn offset(mut self, offset: f64) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    /// Sets the maximum distance, in glyph units, between the traced
+    /// boundary and the simplified contours.
+    pub fn tolerance(mut self, tolerance: f64) -> Self {
+        self.tolerance = tolerance.max(0.0);
+        self
+    }
+}
+
+/// The boundary of the union of some polygons, as closed contours.
+///
+/// Outer contours and holes wind in opposite directions, so the outline
+/// must be filled as a whole with the non-zero rule.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Outline {
+    contours: Vec<Vec<Point>>,
+}
+
+impl Outline {
+    pub fn contours(&self) -> &[Vec<Point>] {
+        &self.contours
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.contours.is_empty()
+    }
+
+    /// Returns the SVG path data of all contours.
+    pub fn path_data(&self) -> String {
+        let mut data = String::new();
+        for contour in &self.contours {
+            for (index, point) in contour.iter().enumerate() {
+                if !data.is_empty() {
+                    data.push(' ');
+                }
+                data.push_str(if index == 0 { "M " } else { "L " });
+                data.push_str(&format!("{},{}", number(point.x), number(point.y)));
+            }
+            data.push_str(" Z");
+        }
+        data
+    }
+
+    /// Generates an SVG document of the outline. With a `stroke_width`, in
+    /// glyph units, the contours are stroked and left hollow; otherwise
+    /// they are filled.
+    pub fn generate_svg(&self, stroke_width: Option<f64>) -> String {
+        let mut buffer = String::new();
+        buffer.push_str(r#"<svg xmlns="http://www.w3.org/2000/svg" version="1.1" baseProfile="full" viewBox="0 0 200 200" width="200" height="200">"#);
+        buffer.push('\n');
+
+        let data = self.path_data();
+        if !data.is_empty() {
+            let paint = match stroke_width {
+                Some(width) => format!(
+                    r#"fill="none" stroke="black" stroke-width="{}" stroke-linejoin="round""#,
+                    number(width)
+                ),
+                None => r#"fill="black" fill-rule="nonzero""#.to_string(),
+            };
+            buffer.push_str(&format!(r#"<path d="{data}" {paint} />"#));
+            buffer.push('\n');
+        }
+
+        buffer.push_str("</svg>\n");
+        buffer
+    }
+}
+
+impl Polygons {
+    /// Traces the boundary of the union of the polygons, optionally offset
+    /// outward or inward, for hollow or bold/light renderings.
+    pub fn outline(&self, options: &OutlineOptions) -> Outline {
+        let mut bounds: Option<(f64, f64, f64, f64)> = None;
+        for point in self.array().flat_map(|polygon| polygon.points()) {
+            let (min_x, min_y, max_x, max_y) =
+                bounds.unwrap_or((point.x, point.y, point.x, point.y));
+            bounds = Some((
+                min_x.min(point.x),
+                min_y.min(point.y),
+                max_x.max(point.x),
+                max_y.max(point.y),
+            ));
+        }
+        let Some((min_x, min_y, max_x, max_y)) = bounds else {
+            return Outline {
+                contours: Vec::new(),
+            };
+        };
+
+        let margin = options.offset.max(0.0) + 2.0;
+        let extent = (max_x - min_x).max(max_y - min_y) + margin * 2.0;
+        let scale = options.resolution.min(MAX_OUTLINE_PIXELS as f64 / extent);
+        let min_x = min_x - margin;
+        let min_y = min_y - margin;
+        let width = (((max_x - min_x + margin) * scale).ceil() as usize).min(MAX_OUTLINE_PIXELS);
+        let height = (((max_y - min_y + margin) * scale).ceil() as usize).min(MAX_OUTLINE_PIXELS);
+        let bitmap = self.rasterize(&RasterOptions::new(width, height).view_box(
+            min_x,
+            min_y,
+            width as f64 / scale,
+            height as f64 / scale,
+        ));
+
+        // positive inside, negative outside, traced at zero
+        let field: Vec<f64> = if options.offset == 0.0 {
+            bitmap
+                .as_bytes()
+                .iter()
+                .map(|&coverage| coverage as f64 / 255.0 - 0.5)
+                .collect()
+        } else {
+            let inside: Vec<bool> = bitmap
+                .as_bytes()
+                .iter()
+                .map(|&value| value >= 128)
+                .collect();
+            let outside: Vec<bool> = inside.iter().map(|inside| !inside).collect();
+            let to_inside = distance_transform(&inside, width, height);
+            let to_outside = distance_transform(&outside, width, height);
+            let offset = options.offset * scale;
+            inside
+                .iter()
+                .enumerate()
+                .map(|(index, &inside)| {
+                    let distance = if inside {
+                        0.5 - to_outside[index]
+                    } else {
+                        to_inside[index] - 0.5
+                    };
+                    offset - distance
+                })
+                .collect()
+        };
+
+        let to_glyph = |point: (f64, f64)| {
+            Point::new(
+                min_x + (point.0 + 0.5) / scale,
+                min_y + (point.1 + 0.5) / scale,
+                None,
+            )
+        };
+        let contours = trace(&field, width, height)
+            .into_iter()
+            .map(|contour| {
+                let contour: Vec<Point> = contour.into_iter().map(to_glyph).collect();
+                simplify(&contour, options.tolerance)
+            })
+            .filter(|contour| contour.len() >= 3)
+            .collect();
+
+        Outline { contours }
+    }
+}
+
+/// Returns the Euclidean distance, in pixels, from every pixel to the
+/// nearest pixel set in `mask` (Felzenszwalb and Huttenlocher).
+fn distance_transform(mask: &[bool], width: usize, height: usize) -> Vec<f64> {
+    let infinity = ((width + height) * (width + height)) as f64;
+    let mut grid: Vec<f64> = mask
+        .iter()
+        .map(|&set| if set { 0.0 } else { infinity })
+        .collect();
+
+    let mut buffer = Vec::new();
+    for x in 0..width {
+        buffer.clear();
+        buffer.extend((0..height).map(|y| grid[y * width + x]));
+        for (y, value) in distance_transform_1d(&buffer).into_iter().enumerate() {
+            grid[y * width + x] = value;
+        }
+    }
+    for row in grid.chunks_mut(width.max(1)) {
+        let transformed = distance_transform_1d(row);
+        row.copy_from_slice(&transformed);
+    }
+
+    grid.into_iter().map(f64::sqrt).collect()
+}
+
+/// Squared distance transform of a sampled function, by the lower
+/// envelope of parabolas rooted at each sample.
+fn distance_transform_1d(function: &[f64]) -> Vec<f64> {
+    let length = function.len();
+    let mut result = vec![0.0; length];
+    if length == 0 {
+        return result;
+    }
+
+    let mut vertices = vec![0usize; length];
+    let mut boundaries = vec![0.0f64; length + 1];
+    let mut count = 0;
+    boundaries[0] = f64::NEG_INFINITY;
+    boundaries[1] = f64::INFINITY;
+
+    for q in 1..length {
+        let mut s;
+        loop {
+            let v = vertices[count];
+            s = ((function[q] + (q * q) as f64) - (function[v] + (v * v) as f64))
+                / (2.0 * q as f64 - 2.0 * v as f64);
+            // the first boundary is -∞, so this stops at the first parabola
+            if s > boundaries[count] {
+                break;
+            }
+            count -= 1;
+        }
+        count += 1;
+        vertices[count] = q;
+        boundaries[count] = s;
+        boundaries[count + 1] = f64::INFINITY;
+    }
+
+    let mut k = 0;
+    for (q, value) in result.iter_mut().enumerate() {
+        while boundaries[k + 1] < q as f64 {
+            k += 1;
+        }
+        let v = vertices[k];
+        let distance = q as f64 - v as f64;
+        *value = distance * distance + function[v];
+    }
+
+    result
+}
+
+/// Cell edges crossed by the boundary.
+#[derive(Debug, Clone, Copy)]
+enum Side {
+    Top,
+    Right,
+    Bottom,
+    Left,
+}
+
+/// Traces the zero level of `field` with marching squares, returning
+/// closed contours in pixel coordinates with the positive side on their
+/// right (clockwise around filled areas, in glyph space).
+fn trace(field: &[f64], width: usize, height: usize) -> Vec<Vec<(f64, f64)>> {
+    // the field is surrounded by a ring of outside samples, so every
+    // contour closes
+    let padded_width = width + 2;
+    let padded_height = height + 2;
+    let sample = |x: usize, y: usize| -> f64 {
+        if x == 0 || y == 0 || x > width || y > height {
+            -1.0
+        } else {
+            field[(y - 1) * width + (x - 1)]
+        }
+    };
+
+    // edges are numbered by their first sample: horizontal edges are even
+    // and vertical edges are odd
+    let edge_id = |x: usize, y: usize, side: Side| -> usize {
+        match side {
+            Side::Top => (y * padded_width + x) * 2,
+            Side::Bottom => ((y + 1) * padded_width + x) * 2,
+            Side::Left => (y * padded_width + x) * 2 + 1,
+            Side::Right => (y * padded_width + x + 1) * 2 + 1,
+        }
+    };
+    let crossing = |id: usize| -> (f64, f64) {
+        let index = id / 2;
+        let (x, y) = (index % padded_width, index / padded_width);
+        let (x1, y1) = if id.is_multiple_of(2) {
+            (x + 1, y)
+        } else {
+            (x, y + 1)
+        };
+        let (from, to) = (sample(x, y), sample(x1, y1));
+        let t = (from / (from - to)).clamp(0.0, 1.0);
+        (
+            x as f64 - 1.0 + (x1 - x) as f64 * t,
+            y as f64 - 1.0 + (y1 - y) as f64 * t,
+        )
+    };
+
+    let mut next = HashMap::new();
+    for y in 0..padded_height - 1 {
+        for x in 0..padded_width - 1 {
+            let corners = [
+                sample(x, y),
+                sample(x + 1, y),
+                sample(x + 1, y + 1),
+                sample(x, y + 1),
+            ];
+            let case = corners
+                .iter()
+                .fold(0, |case, &value| case << 1 | (value > 0.0) as usize);
+
+            use Side::*;
+            let segments: &[(Side, Side)] = match case {
+                0b0000 | 0b1111 => &[],
+                0b1000 => &[(Top, Left)],
+                0b0100 => &[(Right, Top)],
+                0b0010 => &[(Bottom, Right)],
+                0b0001 => &[(Left, Bottom)],
+                0b0111 => &[(Left, Top)],
+                0b1011 => &[(Top, Right)],
+                0b1101 => &[(Right, Bottom)],
+                0b1110 => &[(Bottom, Left)],
+                0b1100 => &[(Right, Left)],
+                0b0110 => &[(Bottom, Top)],
+                0b0011 => &[(Left, Right)],
+                0b1001 => &[(Top, Bottom)],
+                _ => {
+                    // saddle: the average decides whether the inside
+                    // corners are connected
+                    let connected = corners.iter().sum::<f64>() > 0.0;
+                    match (case, connected) {
+                        (0b1010, true) => &[(Top, Right), (Bottom, Left)],
+                        (0b1010, false) => &[(Top, Left), (Bottom, Right)],
+                        (_, true) => &[(Left, Top), (Right, Bottom)],
+                        (_, false) => &[(Right, Top), (Left, Bottom)],
+                    }
+                }
+            };
+            for &(from, to) in segments {
+                next.insert(edge_id(x, y, from), edge_id(x, y, to));
+            }
+        }
+    }
+
+    let mut starts: Vec<usize> = next.keys().copied().collect();
+    starts.sort_unstable();
+
+    let mut contours = Vec::new();
+    for start in starts {
+        let Some(mut id) = next.remove(&start) else {
+            continue;
+        };
+        let mut contour = vec![crossing(start)];
+        while id != start {
+            contour.push(crossing(id));
+            match next.remove(&id) {
+                Some(following) => id = following,
+                None => break,
+            }
+        }
+        contours.push(contour);
+    }
+
+    contours
+}
+
+/// Simplifies a closed contour with the Ramer–Douglas–Peucker algorithm,
+/// split at its first point and the point farthest from it.
+fn simplify(contour: &[Point], tolerance: f64) -> Vec<Point> {
+    if contour.len() < 4 {
+        return contour.to_vec();
+    }
+
+    let distance = |point: &Point| Vector::from(*point - contour[0]).hypot();
+    let farthest = (1..contour.len())
+        .max_by(|&a, &b| distance(&contour[a]).total_cmp(&distance(&contour[b])))
+        .unwrap_or(1);
+
+    let mut keep = vec![false; contour.len() + 1];
+    keep[0] = true;
+    keep[farthest] = true;
+    let mut closed = contour.to_vec();
+    closed.push(contour[0]);
+
+    let mut stack = vec![(0, farthest), (farthest, contour.len())];
+    while let Some((first, last)) = stack.pop() {
+        let (start, end) = (closed[first], closed[last]);
+        let chord = Vector::from(end - start);
+        let length = chord.hypot();
+
+        let mut worst = (0.0, first);
+        for (index, point) in closed.iter().enumerate().take(last).skip(first + 1) {
+            let offset = Vector::from(*point - start);
+            let deviation = if length > 0.0 {
+                (chord.x * offset.y - chord.y * offset.x).abs() / length
+            } else {
+                offset.hypot()
+            };
+            if deviation > worst.0 {
+                worst = (deviation, index);
+            }
+        }
+
+        if worst.0 > tolerance {
+            keep[worst.1] = true;
+            stack.push((first, worst.1));
+            stack.push((worst.1, last));
+        }
+    }
+
+    contour
+        .iter()
+        .zip(keep)
+        .filter_map(|(point, keep)| keep.then_some(*point))
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::polygon::Polygon;
+
+    fn rectangles(rectangles: &[(f64, f64, f64, f64)]) -> Polygons {
+        let mut polygons = Polygons::new();
+        for &(x1, y1, x2, y2) in rectangles {
+            polygons.push(Polygon::new(
+                vec![(x1, y1), (x2, y1), (x2, y2), (x1, y2)],
+                None,
+            ));
+        }
+        polygons
+    }
+
+    /// Twice the signed area, positive for clockwise contours in glyph
+    /// space.
+    fn area(contour: &[Point]) -> f64 {
+        contour
+            .iter()
+            .zip(contour.iter().cycle().skip(1))
+            .map(|(a, b)| a.x * b.y - b.x * a.y)
+            .sum()
+    }
+
+    fn bounds(contour: &[Point]) -> (f64, f64, f64, f64) {
+        contour.iter().fold(
+            (f64::MAX, f64::MAX, f64::MIN, f64::MIN),
+            |(min_x, min_y, max_x, max_y), point| {
+                (
+                    min_x.min(point.x),
+                    min_y.min(point.y),
+                    max_x.max(point.x),
+                    max_y.max(point.y),
+                )
+            },
+        )
+    }
+
+    fn assert_near(actual: (f64, f64, f64, f64), expected: (f64, f64, f64, f64), error: f64) {
+        assert!(
+            (actual.0 - expected.0).abs() <= error
+                && (actual.1 - expected.1).abs() <= error
+                && (actual.2 - expected.2).abs() <= error
+                && (actual.3 - expected.3).abs() <= error,
+            "{actual:?} != {expected:?}"
+        );
+    }
+
+    #[test]
+    fn test_union() {
+        // two overlapping bars make a single cross-shaped contour
+        let outline = rectangles(&[(20.0, 90.0, 180.0, 110.0), (90.0, 20.0, 110.0, 180.0)])
+            .outline(&OutlineOptions::new());
+
+        assert_eq!(outline.contours().len(), 1);
+        let contour = &outline.contours()[0];
+        assert_eq!(contour.len(), 12);
+        assert!(area(contour) > 0.0);
+        assert_near(bounds(contour), (20.0, 20.0, 180.0, 180.0), 0.3);
+    }
+
+    #[test]
+    fn test_holes() {
+        // a square frame traced as an outer contour and a reversed hole
+        let outline = rectangles(&[
+            (40.0, 40.0, 160.0, 60.0),
+            (40.0, 140.0, 160.0, 160.0),
+            (40.0, 40.0, 60.0, 160.0),
+            (140.0, 40.0, 160.0, 160.0),
+        ])
+        .outline(&OutlineOptions::new());
+
+        assert_eq!(outline.contours().len(), 2);
+        let mut contours = outline.contours().to_vec();
+        contours.sort_by(|a, b| area(a).abs().total_cmp(&area(b).abs()));
+        assert!(area(&contours[0]) < 0.0);
+        assert!(area(&contours[1]) > 0.0);
+        assert_near(bounds(&contours[0]), (60.0, 60.0, 140.0, 140.0), 0.3);
+        assert_near(bounds(&contours[1]), (40.0, 40.0, 160.0, 160.0), 0.3);
+    }
+
+    #[test]
+    fn test_offset() {
+        let polygons = rectangles(&[(50.0, 80.0, 150.0, 120.0)]);
+
+        let bold = polygons.outline(&OutlineOptions::new().offset(5.0));
+        assert_eq!(bold.contours().len(), 1);
+        assert_near(bounds(&bold.contours()[0]), (45.0, 75.0, 155.0, 125.0), 0.4);
+
+        let light = polygons.outline(&OutlineOptions::new().offset(-5.0));
+        assert_eq!(light.contours().len(), 1);
+        assert_near(
+            bounds(&light.contours()[0]),
+            (55.0, 85.0, 145.0, 115.0),
+            0.4,
+        );
+
+        // thinner than twice the offset, the bar disappears
+        assert!(
+            polygons
+                .outline(&OutlineOptions::new().offset(-25.0))
+                .is_empty()
+        );
+        assert!(Polygons::new().outline(&OutlineOptions::new()).is_empty());
+    }
+
+    #[test]
+    fn test_large_bitmaps() {
+        // traced at a lower resolution than asked
+        let polygons = rectangles(&[(50.0, 80.0, 150.0, 120.0)]);
+        let outline = polygons.outline(&OutlineOptions::new().resolution(1e12));
+        assert_eq!(outline.contours().len(), 1);
+        assert_near(
+            bounds(&outline.contours()[0]),
+            (50.0, 80.0, 150.0, 120.0),
+            0.3,
+        );
+
+        let huge = rectangles(&[(0.0, 0.0, 1e9, 1e9)]).outline(&OutlineOptions::new());
+        assert_eq!(huge.contours().len(), 1);
+    }
+
+    #[test]
+    fn test_generate_svg() {
+        let outline = rectangles(&[(50.0, 80.0, 150.0, 120.0)]).outline(&OutlineOptions::new());
+
+        let hollow = outline.generate_svg(Some(2.0));
+        assert!(hollow.contains(r#"fill="none" stroke="black" stroke-width="2""#));
+        assert_eq!(hollow.matches("<path ").count(), 1);
+
+        let filled = outline.generate_svg(None);
+        assert!(filled.contains(r#"fill="black" fill-rule="nonzero""#));
+        assert!(outline.path_data().starts_with("M "));
+        assert!(outline.path_data().ends_with(" Z"));
+    }
+}
@@ -0,0 +1,399 @@
+use crate::{
+    polygons::Polygons,
+    postscript::{PathOperators, number, push_path},
+    utils::{Rgb, round},
+};
+
+const PDF_OPERATORS: PathOperators = PathOperators {
+    move_to: "m",
+    line_to: "l",
+    curve_to: "c",
+};
+
+/// Object numbers of the objects written before the pages.
+const CATALOG: usize = 1;
+const PAGES: usize = 2;
+const FONT: usize = 3;
+const INFO: usize = 4;
+const FIRST_PAGE: usize = 5;
+
+/// A PDF document laying out glyphs on pages in a grid, each glyph
+/// captioned with its name.
+///
+/// Glyphs are written as vector paths: quadratic segments become cubic
+/// Bézier curves. Captions use the standard Helvetica font, so nothing is
+/// embedded.
+///
+/// ```ignore
+/// let mut sheet = PdfSheet::new().columns(8).rows(10);
+/// sheet.push("u6f22", &kage.make_glyph("u6f22").unwrap());
+/// std::fs::write("sheet.pdf", sheet.finish())?;
+/// ```
+#[derive(Debug, Clone)]
+pub struct PdfSheet {
+    page_width: f64,
+    page_height: f64,
+    margin: f64,
+    columns: usize,
+    rows: usize,
+    caption_size: f64,
+    frame: bool,
+    title: Option<String>,
+    glyph_count: usize,
+    pages: Vec<String>,
+}
+
+impl Default for PdfSheet {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PdfSheet {
+    /// Creates an A4 sheet of 6×8 glyphs per page.
+    pub fn new() -> Self {
+        Self {
+            page_width: 595.0,
+            page_height: 842.0,
+            margin: 36.0,
+            columns: 6,
+            rows: 8,
+            caption_size: 8.0,
+            frame: true,
+            title: None,
+            glyph_count: 0,
+            pages: Vec::new(),
+        }
+    }
+
+    /// Sets the page size in points (1/72 inch).
+    pub fn page_size(mut self, width: f64, height: f64) -> Self {
+        self.page_width = width;
+        self.page_height = height;
+        self
+    }
+
+    pub fn margin(mut self, margin: f64) -> Self {
+        self.margin = margin;
+        self
+    }
+
+    pub fn columns(mut self, columns: usize) -> Self {
+        self.columns = columns.max(1);
+        self
+    }
+
+    pub fn rows(mut self, rows: usize) -> Self {
+        self.rows = rows.max(1);
+        self
+    }
+
+    /// Sets the font size of the captions in points.
+    pub fn caption_size(mut self, caption_size: f64) -> Self {
+        self.caption_size = caption_size;
+        self
+    }
+
+    /// Draws a thin grey frame around the 200×200 box of each glyph.
+    pub fn frame(mut self, frame: bool) -> Self {
+        self.frame = frame;
+        self
+    }
+
+    pub fn title<S: Into<String>>(mut self, title: S) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    /// Returns the number of pages written so far.
+    pub fn page_count(&self) -> usize {
+        self.pages.len()
+    }
+
+    /// Adds a glyph to the next cell of the grid, starting a new page when
+    /// the current one is full.
+    pub fn push(&mut self, name: &str, polygons: &Polygons) {
+        let per_page = self.columns * self.rows;
+        let index = self.glyph_count % per_page;
+        if index == 0 {
+            self.pages.push(String::new());
+        }
+        self.glyph_count += 1;
+
+        let cell_width = (self.page_width - self.margin * 2.0) / self.columns as f64;
+        let cell_height = (self.page_height - self.margin * 2.0) / self.rows as f64;
+        let gutter = 4.0;
+        let caption_height = self.caption_size * 1.5;
+        let side = (cell_width - gutter * 2.0)
+            .min(cell_height - caption_height - gutter * 2.0)
+            .max(0.0);
+
+        let column = index % self.columns;
+        let row = index / self.columns;
+        let left = self.margin + cell_width * column as f64 + (cell_width - side) / 2.0;
+        let top = self.page_height - self.margin - cell_height * row as f64 - gutter;
+
+        let mut buffer = String::new();
+
+        if self.frame {
+            buffer.push_str(&format!(
+                "q\n0.75 G\n0.25 w\n{} {} {} {} re\nS\nQ\n",
+                number(left),
+                number(top - side),
+                number(side),
+                number(side)
+            ));
+        }
+
+        // Glyph space has its origin at the top left corner and y pointing
+        // down, so the paths are written untransformed under a flipping
+        // matrix.
+        let scale = round(side / 200.0, 6);
+        buffer.push_str(&format!(
+            "q\n{} 0 0 {} {} {} cm\n",
+            scale,
+            -scale,
+            number(left),
+            number(top)
+        ));
+        let mut current_color = None;
+        for polygon in polygons.array() {
+            let segments = polygon.segments();
+            if segments.is_empty() {
+                continue;
+            }
+
+            if polygon.color() != current_color {
+                current_color = polygon.color();
+                push_color(&mut buffer, current_color);
+            }
+
+            push_path(&mut buffer, &segments, &PDF_OPERATORS, |point| {
+                (point.x, point.y)
+            });
+            buffer.push_str("h\nf\n");
+        }
+        buffer.push_str("Q\n");
+
+        buffer.push_str(&format!(
+            "BT\n/F1 {} Tf\n{} {} Td\n({}) Tj\nET\n",
+            number(self.caption_size),
+            number(left),
+            number(top - side - gutter - self.caption_size),
+            escape(name)
+        ));
+
+        if let Some(page) = self.pages.last_mut() {
+            page.push_str(&buffer);
+        }
+    }
+
+    /// Writes the PDF document. A sheet without glyphs has one blank page.
+    pub fn finish(mut self) -> Vec<u8> {
+        if self.pages.is_empty() {
+            self.pages.push(String::new());
+        }
+
+        let mut objects = Vec::with_capacity(FIRST_PAGE - 1 + self.pages.len() * 2);
+        objects.push(format!("<< /Type /Catalog /Pages {PAGES} 0 R >>"));
+
+        let kids = (0..self.pages.len())
+            .map(|page| format!("{} 0 R", FIRST_PAGE + page * 2))
+            .collect::<Vec<_>>()
+            .join(" ");
+        objects.push(format!(
+            "<< /Type /Pages /Kids [{}] /Count {} >>",
+            kids,
+            self.pages.len()
+        ));
+        objects.push(
+            "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica /Encoding /WinAnsiEncoding >>"
+                .to_string(),
+        );
+
+        let mut info = "<< /Producer (kage-engine)".to_string();
+        if let Some(title) = &self.title {
+            info.push_str(&format!(" /Title ({})", escape(title)));
+        }
+        info.push_str(" >>");
+        objects.push(info);
+
+        for (page, content) in self.pages.iter().enumerate() {
+            objects.push(format!(
+                "<< /Type /Page /Parent {} 0 R /MediaBox [0 0 {} {}] \
+                 /Resources << /Font << /F1 {} 0 R >> >> /Contents {} 0 R >>",
+                PAGES,
+                number(self.page_width),
+                number(self.page_height),
+                FONT,
+                FIRST_PAGE + page * 2 + 1
+            ));
+            objects.push(format!(
+                "<< /Length {} >>\nstream\n{}endstream",
+                content.len(),
+                content
+            ));
+        }
+
+        let mut buffer = b"%PDF-1.4\n%\xe2\xe3\xcf\xd3\n".to_vec();
+        let mut offsets = Vec::with_capacity(objects.len());
+        for (index, object) in objects.iter().enumerate() {
+            offsets.push(buffer.len());
+            buffer.extend(format!("{} 0 obj\n{}\nendobj\n", index + 1, object).as_bytes());
+        }
+
+        let xref = buffer.len();
+        buffer.extend(format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1).as_bytes());
+        for offset in offsets {
+            buffer.extend(format!("{offset:010} 00000 n \n").as_bytes());
+        }
+        buffer.extend(
+            format!(
+                "trailer\n<< /Size {} /Root {CATALOG} 0 R /Info {INFO} 0 R >>\nstartxref\n{xref}\n%%EOF\n",
+                objects.len() + 1
+            )
+            .as_bytes(),
+        );
+
+        buffer
+    }
+}
+
+fn push_color(buffer: &mut String, color: Option<Rgb>) {
+    let (red, green, blue) = color.map(|rgb| rgb.unit()).unwrap_or((0.0, 0.0, 0.0));
+    buffer.push_str(&format!(
+        "{} {} {} rg\n",
+        number(red),
+        number(green),
+        number(blue)
+    ));
+}
+
+/// Escapes a PDF literal string. Characters outside ASCII are replaced,
+/// since the standard fonts cannot show them anyway.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for char in text.chars() {
+        match char {
+            '(' | ')' | '\\' => {
+                escaped.push('\\');
+                escaped.push(char);
+            }
+            ' '..='~' => escaped.push(char),
+            _ => escaped.push('?'),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::polygon::Polygon;
+
+    fn triangle() -> Polygons {
+        let mut polygons = Polygons::new();
+        polygons.push(Polygon::new(
+            vec![(50.0, 50.0), (150.0, 50.0), (100.0, 150.0)],
+            None,
+        ));
+        polygons
+    }
+
+    #[test]
+    fn test_empty_sheet() {
+        let pdf = String::from_utf8_lossy(&PdfSheet::new().finish()).into_owned();
+
+        assert!(pdf.starts_with("%PDF-1.4\n"));
+        assert!(pdf.ends_with("%%EOF\n"));
+        assert!(pdf.contains("/Count 1 >>"));
+        assert!(pdf.contains("<< /Length 0 >>\nstream\nendstream"));
+    }
+
+    #[test]
+    fn test_xref_offsets() {
+        let mut sheet = PdfSheet::new().title("proof");
+        sheet.push("u4e00", &triangle());
+        let pdf = sheet.finish();
+        let text = String::from_utf8_lossy(&pdf);
+
+        let startxref = text.rfind("startxref\n").unwrap();
+        let xref: usize = text[startxref + 10..]
+            .lines()
+            .next()
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert!(pdf[xref..].starts_with(b"xref\n0 7\n"));
+
+        // every entry is 20 bytes long and points at its object
+        let entries = &pdf[xref + 9..];
+        for number in 1..7 {
+            let entry = &entries[number * 20..number * 20 + 20];
+            let offset: usize = std::str::from_utf8(&entry[..10]).unwrap().parse().unwrap();
+            assert!(pdf[offset..].starts_with(format!("{number} 0 obj\n").as_bytes()));
+        }
+    }
+
+    #[test]
+    fn test_paging() {
+        let mut sheet = PdfSheet::new().columns(2).rows(2);
+        for index in 0..5 {
+            sheet.push(&format!("glyph-{index}"), &triangle());
+        }
+
+        assert_eq!(sheet.page_count(), 2);
+        let pdf = String::from_utf8_lossy(&sheet.finish()).into_owned();
+        assert!(pdf.contains("/Kids [5 0 R 7 0 R] /Count 2"));
+        assert_eq!(pdf.matches(" Tj\n").count(), 5);
+    }
+
+    #[test]
+    fn test_glyph_content() {
+        let mut polygons = Polygons::new();
+        polygons.push(Polygon::new(
+            vec![
+                (0.0, 100.0, false),
+                (60.0, 40.0, true),
+                (120.0, 100.0, false),
+            ],
+            None,
+        ));
+        let mut red = Polygon::new(vec![(10.0, 10.0), (20.0, 10.0), (20.0, 20.0)], None);
+        red.set_color(255, 0, 0);
+        polygons.push(red);
+
+        let mut sheet = PdfSheet::new()
+            .page_size(216.0, 216.0)
+            .margin(0.0)
+            .columns(1)
+            .rows(1)
+            .frame(false);
+        sheet.push("a(b)", &polygons);
+        let pdf = String::from_utf8_lossy(&sheet.finish()).into_owned();
+
+        assert!(pdf.contains(
+            "q
+0.98 0 0 -0.98 10 212 cm
+0 100 m
+40 60 80 60 120 100 c
+0 100 l
+h
+f
+1 0 0 rg
+10 10 m
+20 10 l
+20 20 l
+10 10 l
+h
+f
+Q
+BT
+/F1 8 Tf
+10 4 Td
+(a\\(b\\)) Tj
+ET
+"
+        ));
+    }
+}
@@ -0,0 +1,138 @@
+// A minimal PNG encoder: 8-bit grayscale, no filtering, and zlib streams
+// made of stored (uncompressed) deflate blocks. Glyph bitmaps are small, so
+// this keeps the crate free of compression dependencies.
+
+const SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+const MAX_STORED_BLOCK: usize = 65535;
+
+fn crc32_table() -> [u32; 256] {
+    let mut table = [0u32; 256];
+    for (n, entry) in table.iter_mut().enumerate() {
+        let mut c = n as u32;
+        for _ in 0..8 {
+            c = if c & 1 != 0 {
+                0xedb8_8320 ^ (c >> 1)
+            } else {
+                c >> 1
+            };
+        }
+        *entry = c;
+    }
+    table
+}
+
+pub(crate) fn crc32(chunks: &[&[u8]]) -> u32 {
+    let table = crc32_table();
+    let mut crc = 0xffff_ffffu32;
+    for chunk in chunks {
+        for &byte in *chunk {
+            crc = table[((crc ^ byte as u32) & 0xff) as usize] ^ (crc >> 8);
+        }
+    }
+    crc ^ 0xffff_ffff
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    let mut a = 1u32;
+    let mut b = 0u32;
+    for chunk in data.chunks(5552) {
+        for &byte in chunk {
+            a += byte as u32;
+            b += a;
+        }
+        a %= 65521;
+        b %= 65521;
+    }
+    (b << 16) | a
+}
+
+/// Wraps `data` into a zlib stream of stored deflate blocks.
+pub(crate) fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    let block_count = data.len().div_ceil(MAX_STORED_BLOCK).max(1);
+    let mut buffer = Vec::with_capacity(data.len() + block_count * 5 + 6);
+    // CMF: deflate with a 32K window; FLG: no dictionary, fastest level
+    buffer.extend([0x78, 0x01]);
+
+    let mut blocks = data.chunks(MAX_STORED_BLOCK).peekable();
+    if blocks.peek().is_none() {
+        buffer.extend([0x01, 0x00, 0x00, 0xff, 0xff]);
+    }
+    while let Some(block) = blocks.next() {
+        let is_final = blocks.peek().is_none();
+        let length = block.len() as u16;
+        buffer.push(is_final as u8);
+        buffer.extend(length.to_le_bytes());
+        buffer.extend((!length).to_le_bytes());
+        buffer.extend(block);
+    }
+
+    buffer.extend(adler32(data).to_be_bytes());
+    buffer
+}
+
+fn write_chunk(buffer: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    buffer.extend((data.len() as u32).to_be_bytes());
+    buffer.extend(kind);
+    buffer.extend(data);
+    buffer.extend(crc32(&[kind, data]).to_be_bytes());
+}
+
+/// Encodes row-major 8-bit gray levels as a PNG image.
+pub(crate) fn encode_gray(width: usize, height: usize, gray: &[u8]) -> Vec<u8> {
+    let mut header = Vec::with_capacity(13);
+    header.extend((width as u32).to_be_bytes());
+    header.extend((height as u32).to_be_bytes());
+    // bit depth 8, colour type 0 (grayscale), deflate, adaptive filtering, no interlace
+    header.extend([8, 0, 0, 0, 0]);
+
+    let mut scanlines = Vec::with_capacity((width + 1) * height);
+    for row in gray.chunks(width.max(1)).take(height) {
+        // filter type 0 (None)
+        scanlines.push(0);
+        scanlines.extend(row);
+    }
+
+    let mut buffer = SIGNATURE.to_vec();
+    write_chunk(&mut buffer, b"IHDR", &header);
+    write_chunk(&mut buffer, b"IDAT", &zlib_stored(&scanlines));
+    write_chunk(&mut buffer, b"IEND", &[]);
+
+    buffer
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_checksums() {
+        assert_eq!(crc32(&[b"IEND"]), 0xae42_6082);
+        assert_eq!(crc32(&[b"123456789"]), 0xcbf4_3926);
+        assert_eq!(adler32(b"Wikipedia"), 0x11e6_0398);
+    }
+
+    #[test]
+    fn test_zlib_stored() {
+        assert_eq!(
+            zlib_stored(b"abc"),
+            vec![0x78, 0x01, 0x01, 0x03, 0x00, 0xfc, 0xff, b'a', b'b', b'c', 0x02, 0x4d, 0x01, 0x27]
+        );
+
+        let large = vec![7u8; MAX_STORED_BLOCK + 10];
+        let stream = zlib_stored(&large);
+        // two blocks, only the second one is final
+        assert_eq!(stream[2], 0);
+        assert_eq!(stream[2 + 5 + MAX_STORED_BLOCK], 1);
+        assert_eq!(stream.len(), 2 + 5 * 2 + large.len() + 4);
+    }
+
+    #[test]
+    fn test_encode_gray() {
+        let png = encode_gray(2, 1, &[0, 255]);
+
+        assert_eq!(&png[..8], &SIGNATURE);
+        assert_eq!(&png[12..16], b"IHDR");
+        assert_eq!(&png[16..24], &[0, 0, 0, 2, 0, 0, 0, 1]);
+        assert_eq!(&png[png.len() - 8..png.len() - 4], b"IEND");
+    }
+}
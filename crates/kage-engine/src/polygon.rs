@@ -1,7 +1,17 @@
 use crate::utils::{Point, Rgb};
 
+/// Points are floored to multiples of `1 / PRECISION`.
 const PRECISION: f64 = 10.0;
 
+/// A piece of a closed contour, used by the writers that need to walk
+/// outlines segment by segment instead of point by point.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Segment {
+    Line(Point, Point),
+    /// start point, control point, end point
+    Quadratic(Point, Point, Point),
+}
+
 #[derive(Debug, Clone)]
 pub struct Polygon {
     points: Vec<Point>,
@@ -10,17 +20,8 @@ pub struct Polygon {
 
 impl Polygon {
     pub fn new<P: Into<Point>>(points: Vec<P>, color: Option<Rgb>) -> Self {
-        let mut new_points = Vec::with_capacity(points.len());
-        for point in points {
-            let raw_point: Point = point.into();
-            new_points.push(Self::create_internal_point(
-                raw_point.x,
-                raw_point.y,
-                raw_point.off_curve,
-            ));
-        }
         Self {
-            points: new_points,
+            points: points.into_iter().map(Into::into).collect(),
             color,
         }
     }
@@ -47,16 +48,8 @@ impl Polygon {
         self.color = Some(Rgb::new(red, green, blue))
     }
 
-    pub fn points(&self) -> Vec<Point> {
-        let mut new_points = Vec::with_capacity(self.points.len());
-        for point in &self.points {
-            new_points.push(Point::new(
-                point.x / PRECISION,
-                point.y / PRECISION,
-                point.off_curve,
-            ));
-        }
-        new_points
+    pub fn points(&self) -> &[Point] {
+        &self.points
     }
 
     pub fn len(&self) -> usize {
@@ -64,8 +57,7 @@ impl Polygon {
     }
 
     pub fn push(&mut self, x: f64, y: f64, off: Option<bool>) {
-        let point = Self::create_internal_point(x, y, off);
-        self.points.push(point);
+        self.points.push(Point::new(x, y, off));
     }
 
     pub fn push_point<P: Into<Point>>(&mut self, point: P) {
@@ -74,15 +66,7 @@ impl Polygon {
     }
 
     pub fn get(&self, index: usize) -> Option<Point> {
-        if index >= self.points.len() {
-            return None;
-        }
-        let raw_point = unsafe { self.points.get_unchecked(index) };
-        Some(Point::new(
-            raw_point.x / PRECISION,
-            raw_point.y / PRECISION,
-            raw_point.off_curve,
-        ))
+        self.points.get(index).copied()
     }
 
     pub fn set(&mut self, index: usize, x: f64, y: f64, off: Option<bool>) -> Result<(), String> {
@@ -90,8 +74,7 @@ impl Polygon {
             return Err("Index out of bounds.".to_string());
         }
 
-        let point = Self::create_internal_point(x, y, off);
-        self.points[index] = point;
+        self.points[index] = Point::new(x, y, off);
 
         Ok(())
     }
@@ -126,8 +109,7 @@ impl Polygon {
 
     /// Inserts a new point at the beginning of its contour.
     pub fn unshift(&mut self, x: f64, y: f64, off: Option<bool>) {
-        let point = Self::create_internal_point(x, y, off);
-        self.points.insert(0, point);
+        self.points.insert(0, Point::new(x, y, off));
     }
 
     pub fn unshift_point(&mut self, point: Point) {
@@ -145,9 +127,6 @@ impl Polygon {
     ///
     /// A mutable reference to the translated polygon (for chaining).
     pub fn translate(&mut self, dx: f64, dy: f64) -> &mut Self {
-        let dx = dx * PRECISION;
-        let dy = dy * PRECISION;
-
         for point in self.points.iter_mut() {
             point.x += dx;
             point.y += dy;
@@ -223,20 +202,71 @@ impl Polygon {
 
     pub fn floor(&mut self) -> &mut Self {
         for point in self.points.iter_mut() {
-            point.x = point.x.floor();
-            point.y = point.y.floor();
+            point.x = (point.x * PRECISION).floor() / PRECISION;
+            point.y = (point.y * PRECISION).floor() / PRECISION;
         }
 
         self
     }
 
-    fn create_internal_point(x: f64, y: f64, off: Option<bool>) -> Point {
-        Point::new(x * PRECISION, y * PRECISION, off)
-    }
-
     pub fn color(&self) -> Option<Rgb> {
         self.color
     }
+
+    /// Splits the closed contour into line and quadratic Bézier segments.
+    ///
+    /// Two consecutive off-curve points imply an on-curve point at their
+    /// midpoint, as in TrueType outlines. The last segment always ends at
+    /// the point the contour started from.
+    pub(crate) fn segments(&self) -> Vec<Segment> {
+        let points = self.points();
+        let mut segments = Vec::with_capacity(points.len());
+        if points.is_empty() {
+            return segments;
+        }
+
+        let is_off = |point: &Point| point.off_curve.unwrap_or(false);
+        let on_curve = |x: f64, y: f64| Point::new(x, y, Some(false));
+        let midpoint = |a: Point, b: Point| on_curve((a.x + b.x) / 2.0, (a.y + b.y) / 2.0);
+
+        let (start, first_index) = match points.iter().position(|point| !is_off(point)) {
+            Some(index) => (on_curve(points[index].x, points[index].y), index + 1),
+            // contour without any on-curve point
+            None => (midpoint(points[points.len() - 1], points[0]), 0),
+        };
+        let count = if first_index == 0 {
+            points.len()
+        } else {
+            points.len() - 1
+        };
+
+        let mut current = start;
+        let mut control: Option<Point> = None;
+        for point in points.iter().cycle().skip(first_index).take(count) {
+            if is_off(point) {
+                if let Some(previous) = control {
+                    let implied = midpoint(previous, *point);
+                    segments.push(Segment::Quadratic(current, previous, implied));
+                    current = implied;
+                }
+                control = Some(*point);
+            } else {
+                let point = on_curve(point.x, point.y);
+                match control.take() {
+                    Some(previous) => segments.push(Segment::Quadratic(current, previous, point)),
+                    None => segments.push(Segment::Line(current, point)),
+                }
+                current = point;
+            }
+        }
+
+        match control {
+            Some(previous) => segments.push(Segment::Quadratic(current, previous, start)),
+            None => segments.push(Segment::Line(current, start)),
+        }
+
+        segments
+    }
 }
 
 #[cfg(test)]
@@ -395,6 +425,39 @@ mod test {
         assert_eq!(polygon_2.get(1), Some(Point::new(-0.0, -1.0, Some(false))));
     }
 
+    #[test]
+    fn test_polygon_segments() {
+        let polygon = Polygon::new(
+            vec![
+                (10.0, 10.0, true),
+                (20.0, 0.0, false),
+                (30.0, 10.0, true),
+                (30.0, 20.0, true),
+            ],
+            None,
+        );
+
+        let p = |x: f64, y: f64| Point::new(x, y, Some(false));
+        let c = |x: f64, y: f64| Point::new(x, y, Some(true));
+        assert_eq!(
+            polygon.segments(),
+            vec![
+                Segment::Quadratic(p(20.0, 0.0), c(30.0, 10.0), p(30.0, 15.0)),
+                Segment::Quadratic(p(30.0, 15.0), c(30.0, 20.0), p(20.0, 15.0)),
+                Segment::Quadratic(p(20.0, 15.0), c(10.0, 10.0), p(20.0, 0.0)),
+            ]
+        );
+
+        let only_off = Polygon::new(vec![(0.0, 0.0, true), (10.0, 0.0, true)], None);
+        assert_eq!(
+            only_off.segments(),
+            vec![
+                Segment::Quadratic(p(5.0, 0.0), c(0.0, 0.0), p(5.0, 0.0)),
+                Segment::Quadratic(p(5.0, 0.0), c(10.0, 0.0), p(5.0, 0.0)),
+            ]
+        );
+    }
+
     #[test]
     fn test_polygon_floor() {
         let mut polygon = Polygon::new(vec![(1.7, 2.3, false), (3.1, 4.9, true)], None);
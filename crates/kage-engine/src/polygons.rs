@@ -1,21 +1,78 @@
 use core::slice;
 
-use time::{OffsetDateTime, macros::format_description};
-
-use crate::polygon::Polygon;
+use crate::{
+    polygon::Polygon,
+    postscript::{self, PostScriptOptions},
+};
 
 #[derive(Debug)]
 pub struct Polygons {
     array: Vec<Polygon>,
+    /// Keeps the polygons that collapse to a line, so that outlines drawn
+    /// with different parameters have the same contours.
+    compatible: bool,
+    floor_points: bool,
+    /// Number of polygons dropped because they have a point that is not
+    /// finite.
+    non_finite: usize,
 }
 
 impl Polygons {
     pub fn new() -> Self {
-        Self { array: Vec::new() }
+        Self {
+            array: Vec::new(),
+            compatible: false,
+            floor_points: true,
+            non_finite: 0,
+        }
+    }
+
+    /// Creates an empty set of polygons for compatible rendering, where
+    /// `push` only drops polygons that cannot be drawn at all.
+    pub(crate) fn new_compatible() -> Self {
+        Self {
+            array: Vec::new(),
+            compatible: true,
+            floor_points: true,
+            non_finite: 0,
+        }
+    }
+
+    /// Whether the polygons are drawn for compatible rendering, where the
+    /// typefaces draw the same points whatever their widths.
+    pub(crate) fn is_compatible(&self) -> bool {
+        self.compatible
+    }
+
+    pub fn len(&self) -> usize {
+        self.array.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.array.is_empty()
+    }
+
+    /// Sets whether the points are floored to a tenth of a unit as they
+    /// are drawn, as the reference implementation does. On by default;
+    /// turned off, the polygons keep the full precision of the strokes.
+    pub fn set_floor_points(&mut self, floor_points: bool) {
+        self.floor_points = floor_points;
+    }
+
+    pub fn floors_points(&self) -> bool {
+        self.floor_points
+    }
+
+    /// Returns how many polygons were dropped since the last
+    /// [`Polygons::clear`] because one of their points is NaN or infinite,
+    /// which no typeface should draw.
+    pub fn non_finite_count(&self) -> usize {
+        self.non_finite
     }
 
     pub fn clear(&mut self) {
         self.array.clear();
+        self.non_finite = 0;
     }
 
     pub fn array(&self) -> slice::Iter<'_, Polygon> {
@@ -36,7 +93,9 @@ impl Polygons {
             return;
         }
 
-        polygon.floor();
+        if self.floor_points {
+            polygon.floor();
+        }
 
         for point in polygon.points() {
             if point.x < min_x {
@@ -51,12 +110,13 @@ impl Polygons {
             if point.y > max_y {
                 max_y = point.y;
             }
-            if point.x.is_nan() || point.y.is_nan() {
+            if !point.x.is_finite() || !point.y.is_finite() {
+                self.non_finite += 1;
                 return;
             }
         }
 
-        if min_x != max_x && min_y != max_y {
+        if self.compatible || (min_x != max_x && min_y != max_y) {
             self.array.push(polygon);
         }
     }
@@ -135,71 +195,27 @@ impl Polygons {
         buffer
     }
 
+    /// Generates an EPS file stamped with the current time. Use
+    /// [`Polygons::generate_postscript`] for reproducible output.
     pub fn generate_eps(&self) -> String {
-        let mut buffer = format!(
-            r#"%!PS-Adobe-3.0 EPSF-3.0
-%%BoundingBox: 0 -208 1024 816
-%%Pages: 0
-%%Title: Kanji glyph
-%%Creator: GlyphWiki powered by KAGE system
-%%CreationDate: {}
-%%EndComments
-%%EndProlog"#,
-            local_time()
-        );
-        buffer.push('\n');
-
-        for polygon in &self.array {
-            let points_arr = polygon.points();
-            for j in 0..points_arr.len() {
-                buffer.push_str(&format!(
-                    r#"{} {} "#,
-                    points_arr[j].x * 5.0,
-                    1000.0 - points_arr[j].y * 5.0 - 200.0
-                ));
-
-                if j == 0 {
-                    buffer.push_str(r#"newpath"#);
-                    buffer.push('\n');
-                    buffer.push_str(r#"moveto"#);
-                    buffer.push('\n');
-                } else {
-                    buffer.push_str(r#"lineto"#);
-                    buffer.push('\n');
-                }
-            }
-
-            buffer.push_str(r#"closepath"#);
-            buffer.push('\n');
-            buffer.push_str(r#"fill"#);
-            buffer.push('\n');
-        }
-
-        buffer.push_str(r#"%%EOF"#);
-        buffer.push('\n');
-
-        buffer
+        self.generate_postscript(&PostScriptOptions::new().creation_date(Some(postscript::now())))
     }
 }
 
-fn local_time() -> String {
-    let now = OffsetDateTime::now_local().expect("no local offset?");
-    // expected format: Mon Oct 13 2025 12:34:56 GMT+0800
-    let fmt = format_description!(
-        "[weekday repr:short] [month repr:short] [day padding:zero] [year] \
-         [hour]:[minute]:[second] GMT[offset_hour sign:mandatory][offset_minute]"
-    );
-
-    now.format(&fmt)
-        .unwrap_or_else(|_| "Unknown Time".to_string())
-}
-
 #[cfg(test)]
 mod tests {
     use core::f64;
 
+    use time::macros::datetime;
+
     use super::*;
 
+    fn generate_eps(polygons: &Polygons) -> String {
+        polygons.generate_postscript(
+            &PostScriptOptions::new().creation_date(Some(datetime!(2025-10-13 12:34:56 +8))),
+        )
+    }
+
     #[test]
     fn test_generate_svg() {
         // case 1: simple triangle;
@@ -265,26 +281,24 @@ mod tests {
         polygons1.push(triangle);
 
         assert_eq!(
-            polygons1.generate_eps(),
-            format!(
-                "%!PS-Adobe-3.0 EPSF-3.0
-%%BoundingBox: 0 -208 1024 816
+            generate_eps(&polygons1),
+            "%!PS-Adobe-3.0 EPSF-3.0
+%%BoundingBox: 250 50 750 550
 %%Pages: 0
 %%Title: Kanji glyph
 %%Creator: GlyphWiki powered by KAGE system
-%%CreationDate: {}
+%%CreationDate: Mon Oct 13 2025 12:34:56 GMT+0800
 %%EndComments
 %%EndProlog
-250 550 newpath
-moveto
+newpath
+250 550 moveto
 750 550 lineto
 500 50 lineto
+250 550 lineto
 closepath
 fill
 %%EOF
-",
-                local_time()
-            )
+"
         );
 
         // case 2: simple square;
@@ -297,27 +311,25 @@ fill
         polygons2.push(square);
 
         assert_eq!(
-            polygons2.generate_eps(),
-            format!(
-                "%!PS-Adobe-3.0 EPSF-3.0
-%%BoundingBox: 0 -208 1024 816
+            generate_eps(&polygons2),
+            "%!PS-Adobe-3.0 EPSF-3.0
+%%BoundingBox: 150 -50 850 650
 %%Pages: 0
 %%Title: Kanji glyph
 %%Creator: GlyphWiki powered by KAGE system
-%%CreationDate: {}
+%%CreationDate: Mon Oct 13 2025 12:34:56 GMT+0800
 %%EndComments
 %%EndProlog
-150 650 newpath
-moveto
+newpath
+150 650 moveto
 850 650 lineto
 850 -50 lineto
 150 -50 lineto
+150 650 lineto
 closepath
 fill
 %%EOF
-",
-                local_time()
-            )
+"
         );
     }
 
@@ -414,63 +426,80 @@ fill
 "#
         );
         assert_eq!(
-            polygons.generate_eps(),
-            format!(
-                "%!PS-Adobe-3.0 EPSF-3.0
-%%BoundingBox: 0 -208 1024 816
+            generate_eps(&polygons),
+            "%!PS-Adobe-3.0 EPSF-3.0
+%%BoundingBox: 50 -66 900 700
 %%Pages: 0
 %%Title: Kanji glyph
 %%Creator: GlyphWiki powered by KAGE system
-%%CreationDate: {}
+%%CreationDate: Mon Oct 13 2025 12:34:56 GMT+0800
 %%EndComments
 %%EndProlog
-100 700 newpath
-moveto
+newpath
+100 700 moveto
 900 700 lineto
 900 400 lineto
 100 400 lineto
+100 700 lineto
 closepath
 fill
-150 300 newpath
-moveto
+newpath
+150 300 moveto
 300 300 lineto
-375 250 lineto
-450 150 lineto
+350 266.667 400 216.667 450 150 curveto
 450 0 lineto
 300 0 lineto
-225 50 lineto
-150 150 lineto
+250 33.333 200 83.333 150 150 curveto
+150 300 lineto
 closepath
 fill
-850 100 newpath
-moveto
-866 31.5 lineto
-806 -6 lineto
-768.5 -66 lineto
-700 -50 lineto
-631 -66 lineto
-593.5 -6 lineto
-533.5 31.5 lineto
-550 100 lineto
-533.5 169 lineto
-593.5 206.5 lineto
-631 266.5 lineto
-700 250 lineto
-768.5 266.5 lineto
-806 206.5 lineto
-866 169 lineto
+newpath
+850 100 moveto
+860.667 54.333 846 19 806 -6 curveto
+781 -46 745.667 -60.667 700 -50 curveto
+654 -60.667 618.5 -46 593.5 -6 curveto
+553.5 19 539 54.333 550 100 curveto
+539 146 553.5 181.5 593.5 206.5 curveto
+618.5 246.5 654 261 700 250 curveto
+745.667 261 781 246.5 806 206.5 curveto
+846 181.5 860.667 146 850 100 curveto
 closepath
 fill
-200 200 newpath
-moveto
+newpath
+200 200 moveto
 50 100 lineto
 50 300 lineto
+200 200 lineto
 closepath
 fill
 %%EOF
-",
-                local_time()
-            )
+"
         );
     }
+
+    #[test]
+    fn test_non_finite() {
+        let mut polygons = Polygons::new();
+        polygons.push(Polygon::new(
+            vec![
+                (10.0, 10.0, false),
+                (f64::INFINITY, 10.0, false),
+                (10.0, 50.0, false),
+            ],
+            None,
+        ));
+        polygons.push(Polygon::new(
+            vec![
+                (10.0, 10.0, false),
+                (50.0, f64::NAN, false),
+                (10.0, 50.0, false),
+            ],
+            None,
+        ));
+        assert!(polygons.is_empty());
+        assert_eq!(polygons.non_finite_count(), 2);
+
+        polygons.clear();
+        assert_eq!(polygons.non_finite_count(), 0);
+    }
 }
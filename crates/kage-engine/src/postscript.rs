@@ -0,0 +1,370 @@
+use time::{OffsetDateTime, macros::format_description};
+
+use crate::{
+    polygon::Segment,
+    polygons::Polygons,
+    utils::{Point, Rgb, round},
+};
+
+/// The bounding box used when there is nothing to draw: the whole
+/// 200×200 glyph box.
+const EMPTY_BOUNDING_BOX: [f64; 4] = [0.0, -200.0, 1000.0, 800.0];
+
+/// Options of the PostScript writer.
+///
+/// The defaults produce a reproducible EPS file: no `%%CreationDate`
+/// comment is written unless a timestamp is given.
+#[derive(Debug, Clone)]
+pub struct PostScriptOptions {
+    encapsulated: bool,
+    title: String,
+    creation_date: Option<OffsetDateTime>,
+}
+
+impl Default for PostScriptOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PostScriptOptions {
+    pub fn new() -> Self {
+        Self {
+            encapsulated: true,
+            title: "Kanji glyph".to_string(),
+            creation_date: None,
+        }
+    }
+
+    /// Writes an EPS file (`true`, the default) or a printable one-page
+    /// PostScript document (`false`).
+    pub fn encapsulated(mut self, encapsulated: bool) -> Self {
+        self.encapsulated = encapsulated;
+        self
+    }
+
+    pub fn title<S: Into<String>>(mut self, title: S) -> Self {
+        self.title = title.into();
+        self
+    }
+
+    /// Sets the timestamp written to `%%CreationDate`, or omits the comment
+    /// when `None`.
+    pub fn creation_date(mut self, creation_date: Option<OffsetDateTime>) -> Self {
+        self.creation_date = creation_date;
+        self
+    }
+}
+
+/// Returns the current time in the local offset, or in UTC when the local
+/// offset cannot be determined (e.g. in multi-threaded programs on some
+/// platforms).
+pub(crate) fn now() -> OffsetDateTime {
+    OffsetDateTime::now_local().unwrap_or_else(|_| OffsetDateTime::now_utc())
+}
+
+fn format_date(date: OffsetDateTime) -> String {
+    // expected format: Mon Oct 13 2025 12:34:56 GMT+0800
+    let fmt = format_description!(
+        "[weekday repr:short] [month repr:short] [day padding:zero] [year] \
+         [hour]:[minute]:[second] GMT[offset_hour sign:mandatory][offset_minute]"
+    );
+
+    date.format(&fmt)
+        .unwrap_or_else(|_| "Unknown Time".to_string())
+}
+
+/// Formats a coordinate with at most 3 decimals.
+pub(crate) fn number(value: f64) -> String {
+    let value = round(value, 3);
+    if value == 0.0 {
+        "0".to_string()
+    } else {
+        value.to_string()
+    }
+}
+
+/// Maps glyph space (200 units, y down) to PostScript space (1000 units,
+/// y up).
+fn to_page(point: Point) -> (f64, f64) {
+    (point.x * 5.0, 1000.0 - point.y * 5.0 - 200.0)
+}
+
+fn bounding_box(polygons: &Polygons) -> [f64; 4] {
+    let mut bounds = [
+        f64::INFINITY,
+        f64::INFINITY,
+        f64::NEG_INFINITY,
+        f64::NEG_INFINITY,
+    ];
+
+    // Off-curve points bound the curves they control, so the box may be
+    // slightly larger than the ink but never smaller.
+    for polygon in polygons.array() {
+        for point in polygon.points() {
+            let (x, y) = to_page(*point);
+            bounds = [
+                bounds[0].min(x),
+                bounds[1].min(y),
+                bounds[2].max(x),
+                bounds[3].max(y),
+            ];
+        }
+    }
+
+    if bounds.iter().all(|value| value.is_finite()) {
+        [
+            bounds[0].floor(),
+            bounds[1].floor(),
+            bounds[2].ceil(),
+            bounds[3].ceil(),
+        ]
+    } else {
+        EMPTY_BOUNDING_BOX
+    }
+}
+
+fn push_color(buffer: &mut String, color: Option<Rgb>) {
+    let (red, green, blue) = color.map(|rgb| rgb.unit()).unwrap_or((0.0, 0.0, 0.0));
+    buffer.push_str(&format!(
+        "{} {} {} setrgbcolor\n",
+        number(red),
+        number(green),
+        number(blue)
+    ));
+}
+
+/// Names of the path construction operators, which is all that differs
+/// between PostScript and PDF paths.
+pub(crate) struct PathOperators {
+    pub(crate) move_to: &'static str,
+    pub(crate) line_to: &'static str,
+    pub(crate) curve_to: &'static str,
+}
+
+const POSTSCRIPT_OPERATORS: PathOperators = PathOperators {
+    move_to: "moveto",
+    line_to: "lineto",
+    curve_to: "curveto",
+};
+
+/// Appends the path operators of one contour, without any painting
+/// operator. Quadratic segments are raised to cubic curves.
+pub(crate) fn push_path<F>(
+    buffer: &mut String,
+    segments: &[Segment],
+    operators: &PathOperators,
+    transform: F,
+) where
+    F: Fn(Point) -> (f64, f64),
+{
+    let Some(first) = segments.first() else {
+        return;
+    };
+    let (Segment::Line(start, _) | Segment::Quadratic(start, _, _)) = first;
+    let (x, y) = transform(*start);
+    buffer.push_str(&format!(
+        "{} {} {}\n",
+        number(x),
+        number(y),
+        operators.move_to
+    ));
+
+    for segment in segments {
+        match *segment {
+            Segment::Line(_, end) => {
+                let (x, y) = transform(end);
+                buffer.push_str(&format!(
+                    "{} {} {}\n",
+                    number(x),
+                    number(y),
+                    operators.line_to
+                ));
+            }
+            Segment::Quadratic(start, control, end) => {
+                let start = transform(start);
+                let control = transform(control);
+                let end = transform(end);
+                let control_1 = (
+                    start.0 + (control.0 - start.0) * 2.0 / 3.0,
+                    start.1 + (control.1 - start.1) * 2.0 / 3.0,
+                );
+                let control_2 = (
+                    end.0 + (control.0 - end.0) * 2.0 / 3.0,
+                    end.1 + (control.1 - end.1) * 2.0 / 3.0,
+                );
+                buffer.push_str(&format!(
+                    "{} {} {} {} {} {} {}\n",
+                    number(control_1.0),
+                    number(control_1.1),
+                    number(control_2.0),
+                    number(control_2.1),
+                    number(end.0),
+                    number(end.1),
+                    operators.curve_to
+                ));
+            }
+        }
+    }
+}
+
+impl Polygons {
+    /// Generates an EPS or PostScript document of the polygons.
+    ///
+    /// Curves are written with `curveto`, coloured polygons with
+    /// `setrgbcolor`, and the bounding box is computed from the outlines.
+    pub fn generate_postscript(&self, options: &PostScriptOptions) -> String {
+        let mut buffer = String::new();
+
+        if options.encapsulated {
+            buffer.push_str("%!PS-Adobe-3.0 EPSF-3.0\n");
+        } else {
+            buffer.push_str("%!PS-Adobe-3.0\n");
+        }
+
+        let [min_x, min_y, max_x, max_y] = bounding_box(self);
+        buffer.push_str(&format!(
+            "%%BoundingBox: {} {} {} {}\n",
+            number(min_x),
+            number(min_y),
+            number(max_x),
+            number(max_y)
+        ));
+        buffer.push_str(if options.encapsulated {
+            "%%Pages: 0\n"
+        } else {
+            "%%Pages: 1\n"
+        });
+        buffer.push_str(&format!("%%Title: {}\n", options.title));
+        buffer.push_str("%%Creator: GlyphWiki powered by KAGE system\n");
+        if let Some(date) = options.creation_date {
+            buffer.push_str(&format!("%%CreationDate: {}\n", format_date(date)));
+        }
+        buffer.push_str("%%EndComments\n");
+        buffer.push_str("%%EndProlog\n");
+        if !options.encapsulated {
+            buffer.push_str("%%Page: 1 1\n");
+        }
+
+        let mut current_color = None;
+        for polygon in self.array() {
+            let segments = polygon.segments();
+            if segments.is_empty() {
+                continue;
+            }
+
+            if polygon.color() != current_color {
+                current_color = polygon.color();
+                push_color(&mut buffer, current_color);
+            }
+
+            buffer.push_str("newpath\n");
+            push_path(&mut buffer, &segments, &POSTSCRIPT_OPERATORS, to_page);
+            buffer.push_str("closepath\n");
+            buffer.push_str("fill\n");
+        }
+
+        if !options.encapsulated {
+            buffer.push_str("showpage\n");
+            buffer.push_str("%%Trailer\n");
+        }
+        buffer.push_str("%%EOF\n");
+
+        buffer
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use time::macros::datetime;
+
+    use super::*;
+    use crate::polygon::Polygon;
+
+    #[test]
+    fn test_curves_and_colors() {
+        let mut polygons = Polygons::new();
+        polygons.push(Polygon::new(
+            vec![
+                (0.0, 100.0, false),
+                (60.0, 40.0, true),
+                (120.0, 100.0, false),
+            ],
+            None,
+        ));
+        let mut red = Polygon::new(vec![(10.0, 10.0), (20.0, 10.0), (20.0, 20.0)], None);
+        red.set_color(255, 0, 0);
+        polygons.push(red);
+        polygons.push(Polygon::new(
+            vec![(150.0, 150.0), (180.0, 150.0), (180.0, 180.0)],
+            None,
+        ));
+
+        let options = PostScriptOptions::new()
+            .title("test")
+            .creation_date(Some(datetime!(2025-10-13 12:34:56 +8)));
+        assert_eq!(
+            polygons.generate_postscript(&options),
+            "%!PS-Adobe-3.0 EPSF-3.0
+%%BoundingBox: 0 -100 900 750
+%%Pages: 0
+%%Title: test
+%%Creator: GlyphWiki powered by KAGE system
+%%CreationDate: Mon Oct 13 2025 12:34:56 GMT+0800
+%%EndComments
+%%EndProlog
+newpath
+0 300 moveto
+200 500 400 500 600 300 curveto
+0 300 lineto
+closepath
+fill
+1 0 0 setrgbcolor
+newpath
+50 750 moveto
+100 750 lineto
+100 700 lineto
+50 750 lineto
+closepath
+fill
+0 0 0 setrgbcolor
+newpath
+750 50 moveto
+900 50 lineto
+900 -100 lineto
+750 50 lineto
+closepath
+fill
+%%EOF
+"
+        );
+    }
+
+    #[test]
+    fn test_postscript_page() {
+        let polygons = Polygons::new();
+        let options = PostScriptOptions::new().encapsulated(false);
+
+        assert_eq!(
+            polygons.generate_postscript(&options),
+            "%!PS-Adobe-3.0
+%%BoundingBox: 0 -200 1000 800
+%%Pages: 1
+%%Title: Kanji glyph
+%%Creator: GlyphWiki powered by KAGE system
+%%EndComments
+%%EndProlog
+%%Page: 1 1
+showpage
+%%Trailer
+%%EOF
+"
+        );
+    }
+
+    #[test]
+    fn test_now_does_not_panic() {
+        let date = format_date(now());
+        assert!(date.contains("GMT"));
+    }
+}
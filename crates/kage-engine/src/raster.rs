@@ -0,0 +1,468 @@
+use crate::{
+    png,
+    polygon::{Polygon, Segment},
+    polygons::Polygons,
+    utils::Point,
+};
+
+/// The largest number of line pieces a single quadratic segment is
+/// flattened into.
+const MAX_CURVE_PIECES: usize = 64;
+
+/// Options of the scanline rasterizer.
+///
+/// By default the 200×200 glyph box is mapped onto a square bitmap of
+/// the requested size, with 4 sub-scanlines per pixel row for
+/// anti-aliasing.
+#[derive(Debug, Clone)]
+pub struct RasterOptions {
+    width: usize,
+    height: usize,
+    /// (min_x, min_y, width, height) in glyph units.
+    view_box: (f64, f64, f64, f64),
+    samples: usize,
+    anti_aliasing: bool,
+    tolerance: f64,
+}
+
+impl RasterOptions {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            view_box: (0.0, 0.0, 200.0, 200.0),
+            samples: 4,
+            anti_aliasing: true,
+            tolerance: 0.1,
+        }
+    }
+
+    /// Sets the area of the glyph space (in glyph units) that is mapped
+    /// onto the bitmap.
+    pub fn view_box(mut self, min_x: f64, min_y: f64, width: f64, height: f64) -> Self {
+        self.view_box = (min_x, min_y, width, height);
+        self
+    }
+
+    /// Grows the view box by `padding` glyph units on every side.
+    pub fn padding(mut self, padding: f64) -> Self {
+        let (min_x, min_y, width, height) = self.view_box;
+        self.view_box = (
+            min_x - padding,
+            min_y - padding,
+            width + padding * 2.0,
+            height + padding * 2.0,
+        );
+        self
+    }
+
+    /// Sets the number of sub-scanlines sampled per pixel row. `1` turns
+    /// off vertical anti-aliasing; horizontal coverage is always exact.
+    pub fn samples(mut self, samples: usize) -> Self {
+        self.samples = samples.max(1);
+        self
+    }
+
+    /// Turns coverage-based anti-aliasing on or off. Without it, a pixel
+    /// is fully inked when at least half of it is covered, and left blank
+    /// otherwise.
+    pub fn anti_aliasing(mut self, enabled: bool) -> Self {
+        self.anti_aliasing = enabled;
+        if enabled {
+            self.samples(4)
+        } else {
+            self.samples(1)
+        }
+    }
+
+    /// Sets the maximum distance (in pixels) between a quadratic segment
+    /// and the lines it is flattened into.
+    pub fn tolerance(mut self, tolerance: f64) -> Self {
+        self.tolerance = tolerance.max(f64::EPSILON);
+        self
+    }
+
+    fn to_pixel(&self, point: Point) -> (f64, f64) {
+        let (min_x, min_y, width, height) = self.view_box;
+        (
+            (point.x - min_x) * self.width as f64 / width,
+            (point.y - min_y) * self.height as f64 / height,
+        )
+    }
+}
+
+/// An 8-bit coverage bitmap: `0` is untouched paper, `255` is fully
+/// covered by ink.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Bitmap {
+    width: usize,
+    height: usize,
+    coverage: Vec<u8>,
+}
+
+impl Bitmap {
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Returns the coverage of the pixel at (`x`, `y`), or `None` if the
+    /// pixel lies outside the bitmap.
+    pub fn coverage(&self, x: usize, y: usize) -> Option<u8> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        Some(self.coverage[y * self.width + x])
+    }
+
+    /// Row-major coverage values.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.coverage
+    }
+
+    /// Row-major gray levels of black ink on white paper.
+    pub fn to_gray(&self) -> Vec<u8> {
+        self.coverage.iter().map(|value| 255 - value).collect()
+    }
+
+    /// Encodes the bitmap as a binary (P5) PGM image, black on white.
+    pub fn to_pgm(&self) -> Vec<u8> {
+        let mut buffer = format!("P5\n{} {}\n255\n", self.width, self.height).into_bytes();
+        buffer.extend(self.to_gray());
+
+        buffer
+    }
+
+    /// Encodes the bitmap as an 8-bit grayscale PNG image, black on white.
+    pub fn to_png(&self) -> Vec<u8> {
+        png::encode_gray(self.width, self.height, &self.to_gray())
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Edge {
+    x0: f64,
+    y0: f64,
+    x1: f64,
+    y1: f64,
+    /// +1 for downward edges, -1 for upward edges
+    winding: i32,
+}
+
+impl Edge {
+    fn new(from: (f64, f64), to: (f64, f64)) -> Option<Self> {
+        if from.1 == to.1 || !(from.0.is_finite() && from.1.is_finite()) {
+            return None;
+        }
+        if !(to.0.is_finite() && to.1.is_finite()) {
+            return None;
+        }
+
+        Some(if from.1 < to.1 {
+            Edge {
+                x0: from.0,
+                y0: from.1,
+                x1: to.0,
+                y1: to.1,
+                winding: 1,
+            }
+        } else {
+            Edge {
+                x0: to.0,
+                y0: to.1,
+                x1: from.0,
+                y1: from.1,
+                winding: -1,
+            }
+        })
+    }
+
+    fn x_at(&self, y: f64) -> f64 {
+        self.x0 + (y - self.y0) * (self.x1 - self.x0) / (self.y1 - self.y0)
+    }
+}
+
+fn flatten(polygon: &Polygon, options: &RasterOptions) -> Vec<Edge> {
+    let mut edges = Vec::new();
+
+    for segment in polygon.segments() {
+        match segment {
+            Segment::Line(start, end) => {
+                edges.extend(Edge::new(options.to_pixel(start), options.to_pixel(end)));
+            }
+            Segment::Quadratic(start, control, end) => {
+                let (x0, y0) = options.to_pixel(start);
+                let (x1, y1) = options.to_pixel(control);
+                let (x2, y2) = options.to_pixel(end);
+
+                // The distance between a quadratic curve and its chords shrinks
+                // with the square of the number of pieces.
+                let deviation = f64::hypot(x0 - 2.0 * x1 + x2, y0 - 2.0 * y1 + y2);
+                let pieces = ((deviation / (4.0 * options.tolerance)).sqrt().ceil() as usize)
+                    .clamp(1, MAX_CURVE_PIECES);
+
+                let mut previous = (x0, y0);
+                for step in 1..=pieces {
+                    let t = step as f64 / pieces as f64;
+                    let u = 1.0 - t;
+                    let next = (
+                        u * u * x0 + 2.0 * u * t * x1 + t * t * x2,
+                        u * u * y0 + 2.0 * u * t * y1 + t * t * y2,
+                    );
+                    edges.extend(Edge::new(previous, next));
+                    previous = next;
+                }
+            }
+        }
+    }
+
+    edges
+}
+
+/// Adds the horizontal span [`from`, `to`) to the row, weighted by `weight`.
+fn add_span(row: &mut [f32], from: f64, to: f64, weight: f32) {
+    let width = row.len() as f64;
+    let from = from.clamp(0.0, width);
+    let to = to.clamp(0.0, width);
+    if to <= from {
+        return;
+    }
+
+    let first = from.floor() as usize;
+    let last = to.floor() as usize;
+    if first == last {
+        row[first] += (to - from) as f32 * weight;
+        return;
+    }
+
+    row[first] += (first as f64 + 1.0 - from) as f32 * weight;
+    for cell in &mut row[first + 1..last] {
+        *cell += weight;
+    }
+    if last < row.len() {
+        row[last] += (to - last as f64) as f32 * weight;
+    }
+}
+
+/// Fills one polygon with the non-zero rule and composites it onto `canvas`.
+fn fill_polygon(canvas: &mut [f32], polygon: &Polygon, options: &RasterOptions) {
+    let edges = flatten(polygon, options);
+    if edges.is_empty() {
+        return;
+    }
+
+    let top = edges
+        .iter()
+        .map(|edge| edge.y0)
+        .fold(f64::INFINITY, f64::min);
+    let bottom = edges
+        .iter()
+        .map(|edge| edge.y1)
+        .fold(f64::NEG_INFINITY, f64::max);
+    let first_row = top.floor().max(0.0) as usize;
+    let last_row = (bottom.ceil().max(0.0) as usize).min(options.height);
+
+    let weight = 1.0 / options.samples as f32;
+    let mut row = vec![0.0f32; options.width];
+    let mut crossings: Vec<(f64, i32)> = Vec::with_capacity(edges.len());
+
+    for y in first_row..last_row {
+        row.iter_mut().for_each(|cell| *cell = 0.0);
+
+        for sample in 0..options.samples {
+            let scan_y = y as f64 + (sample as f64 + 0.5) / options.samples as f64;
+
+            crossings.clear();
+            crossings.extend(
+                edges
+                    .iter()
+                    .filter(|edge| edge.y0 <= scan_y && scan_y < edge.y1)
+                    .map(|edge| (edge.x_at(scan_y), edge.winding)),
+            );
+            crossings.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+            let mut winding = 0;
+            let mut span_start = 0.0;
+            for &(x, direction) in &crossings {
+                let previous = winding;
+                winding += direction;
+                if previous == 0 && winding != 0 {
+                    span_start = x;
+                } else if previous != 0 && winding == 0 {
+                    add_span(&mut row, span_start, x, weight);
+                }
+            }
+        }
+
+        let line = &mut canvas[y * options.width..(y + 1) * options.width];
+        for (pixel, &value) in line.iter_mut().zip(&row) {
+            let value = value.clamp(0.0, 1.0);
+            // polygons are unioned, each one is opaque ink
+            *pixel = *pixel + value - *pixel * value;
+        }
+    }
+}
+
+impl Polygons {
+    /// Renders the polygons into a coverage bitmap, anti-aliased unless
+    /// the options turn it off.
+    ///
+    /// Every polygon is filled with the non-zero rule on its own and the
+    /// results are unioned, which matches how the SVG output paints one
+    /// element per polygon. Polygon colours are ignored.
+    pub fn rasterize(&self, options: &RasterOptions) -> Bitmap {
+        let mut canvas = vec![0.0f32; options.width * options.height];
+
+        if options.width > 0 && options.height > 0 {
+            for polygon in self.array() {
+                fill_polygon(&mut canvas, polygon, options);
+            }
+        }
+
+        Bitmap {
+            width: options.width,
+            height: options.height,
+            coverage: canvas
+                .into_iter()
+                .map(|value| {
+                    if options.anti_aliasing {
+                        (value.clamp(0.0, 1.0) * 255.0).round() as u8
+                    } else if value >= 0.5 {
+                        255
+                    } else {
+                        0
+                    }
+                })
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn polygons_of(points: Vec<(f64, f64)>) -> Polygons {
+        let mut polygons = Polygons::new();
+        polygons.push(Polygon::new(points, None));
+        polygons
+    }
+
+    #[test]
+    fn test_fill_square() {
+        let polygons = polygons_of(vec![
+            (50.0, 50.0),
+            (150.0, 50.0),
+            (150.0, 150.0),
+            (50.0, 150.0),
+        ]);
+        let bitmap = polygons.rasterize(&RasterOptions::new(20, 20));
+
+        assert_eq!(bitmap.coverage(10, 10), Some(255));
+        assert_eq!(bitmap.coverage(5, 5), Some(255));
+        assert_eq!(bitmap.coverage(4, 10), Some(0));
+        assert_eq!(bitmap.coverage(15, 10), Some(0));
+        assert_eq!(bitmap.coverage(20, 10), None);
+    }
+
+    #[test]
+    fn test_anti_aliased_edge() {
+        // the left edge goes through the middle of the 2nd pixel column
+        let polygons = polygons_of(vec![
+            (30.0, 0.0),
+            (200.0, 0.0),
+            (200.0, 200.0),
+            (30.0, 200.0),
+        ]);
+
+        let bitmap = polygons.rasterize(&RasterOptions::new(10, 10));
+        assert_eq!(bitmap.coverage(0, 5), Some(0));
+        assert_eq!(bitmap.coverage(1, 5), Some(128));
+        assert_eq!(bitmap.coverage(2, 5), Some(255));
+
+        let aliased = polygons.rasterize(&RasterOptions::new(10, 10).anti_aliasing(false));
+        assert_eq!(aliased.coverage(0, 5), Some(0));
+        assert_eq!(aliased.coverage(1, 5), Some(255));
+        assert!(
+            aliased
+                .as_bytes()
+                .iter()
+                .all(|&value| value == 0 || value == 255)
+        );
+        let thinner = polygons_of(vec![
+            (32.0, 0.0),
+            (200.0, 0.0),
+            (200.0, 200.0),
+            (32.0, 200.0),
+        ]);
+        let aliased = thinner.rasterize(&RasterOptions::new(10, 10).anti_aliasing(false));
+        assert_eq!(aliased.coverage(1, 5), Some(0));
+
+        // a diagonal edge gets vertical anti-aliasing from the sub-scanlines
+        let triangle = polygons_of(vec![(0.0, 0.0), (200.0, 0.0), (0.0, 200.0)]);
+        let bitmap = triangle.rasterize(&RasterOptions::new(10, 10));
+        let value = bitmap.coverage(5, 4).unwrap();
+        assert!(0 < value && value < 255);
+    }
+
+    #[test]
+    fn test_overlapping_polygons_are_unioned() {
+        let mut polygons = Polygons::new();
+        polygons.push(Polygon::new(
+            vec![(0.0, 0.0), (100.0, 0.0), (100.0, 100.0), (0.0, 100.0)],
+            None,
+        ));
+        // opposite orientation, overlapping the first one
+        polygons.push(Polygon::new(
+            vec![(50.0, 50.0), (50.0, 150.0), (150.0, 150.0), (150.0, 50.0)],
+            None,
+        ));
+
+        let bitmap = polygons.rasterize(&RasterOptions::new(20, 20));
+        assert_eq!(bitmap.coverage(7, 7), Some(255));
+        assert_eq!(bitmap.coverage(2, 2), Some(255));
+        assert_eq!(bitmap.coverage(12, 12), Some(255));
+        assert_eq!(bitmap.coverage(17, 2), Some(0));
+    }
+
+    #[test]
+    fn test_quadratic_segment() {
+        let mut polygons = Polygons::new();
+        polygons.push(Polygon::new(
+            vec![
+                (0.0, 100.0, false),
+                (100.0, 0.0, true),
+                (200.0, 100.0, false),
+            ],
+            None,
+        ));
+
+        let bitmap = polygons.rasterize(&RasterOptions::new(20, 20));
+        // the curve peaks at y = 50
+        assert_eq!(bitmap.coverage(10, 6), Some(255));
+        // the control triangle would cover this pixel, the curve does not
+        assert_eq!(bitmap.coverage(10, 3), Some(0));
+    }
+
+    #[test]
+    fn test_view_box_and_padding() {
+        let polygons = polygons_of(vec![(0.0, 0.0), (200.0, 0.0), (200.0, 200.0), (0.0, 200.0)]);
+        let bitmap = polygons.rasterize(&RasterOptions::new(40, 40).padding(100.0));
+
+        assert_eq!(bitmap.coverage(9, 20), Some(0));
+        assert_eq!(bitmap.coverage(10, 20), Some(255));
+        assert_eq!(bitmap.coverage(29, 20), Some(255));
+        assert_eq!(bitmap.coverage(30, 20), Some(0));
+    }
+
+    #[test]
+    fn test_pgm() {
+        let polygons = polygons_of(vec![(0.0, 0.0), (100.0, 0.0), (100.0, 200.0), (0.0, 200.0)]);
+        let pgm = polygons.rasterize(&RasterOptions::new(2, 2)).to_pgm();
+
+        assert_eq!(pgm, b"P5\n2 2\n255\n\x00\xff\x00\xff".to_vec());
+    }
+}
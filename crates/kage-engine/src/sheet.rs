@@ -0,0 +1,445 @@
+use crate::{
+    font::{
+        FontWrapper, Typeface,
+        parameters::{ParameterError, Parameters},
+    },
+    kage::Kage,
+    polygons::Polygons,
+    postscript::number,
+    svg::{escape, push_path_data},
+};
+
+const MARGIN: f64 = 10.0;
+const PADDING: f64 = 6.0;
+const PANEL_GAP: f64 = 6.0;
+const LABEL_HEIGHT: f64 = 16.0;
+const FONT_SIZE: f64 = 11.0;
+const FAILURE_FILL: &str = "#ffe0e0";
+const FAILURE_TEXT: &str = "#c00000";
+const GUIDE_STROKE: &str = "#c0c0c0";
+
+/// A proof sheet: glyphs laid out in a grid of labelled cells, rendered
+/// into one SVG document per page.
+///
+/// Glyphs whose data cannot be found, or which reference components that
+/// are not in the store, are highlighted and the missing names are shown
+/// in their label.
+///
+/// ```ignore
+/// let pages = ProofSheet::new().columns(12).compare_typefaces(true).generate(&kage, &names);
+/// ```
+#[derive(Debug, Clone)]
+pub struct ProofSheet {
+    columns: usize,
+    rows: Option<usize>,
+    cell_size: f64,
+    compare_typefaces: bool,
+    /// Parameters of the compared typefaces other than the one of the
+    /// engine.
+    compared_parameters: Vec<Parameters>,
+    guides: bool,
+    highlight_failures: bool,
+}
+
+impl Default for ProofSheet {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ProofSheet {
+    /// Creates a single-page sheet of 10 columns with 100px glyphs.
+    pub fn new() -> Self {
+        Self {
+            columns: 10,
+            rows: None,
+            cell_size: 100.0,
+            compare_typefaces: false,
+            compared_parameters: Vec::new(),
+            guides: false,
+            highlight_failures: true,
+        }
+    }
+
+    pub fn columns(mut self, columns: usize) -> Self {
+        self.columns = columns.max(1);
+        self
+    }
+
+    /// Limits the number of rows per page, or puts every glyph on a single
+    /// page when `None` (the default).
+    pub fn rows(mut self, rows: Option<usize>) -> Self {
+        self.rows = rows.map(|rows| rows.max(1));
+        self
+    }
+
+    /// Sets the side of each glyph box in pixels.
+    pub fn cell_size(mut self, cell_size: f64) -> Self {
+        self.cell_size = cell_size;
+        self
+    }
+
+    /// Shows each glyph in Ming (left) and Gothic (right) side by side,
+    /// instead of with the font of the engine only.
+    ///
+    /// The typeface of the engine is drawn with its font; the other one
+    /// with the parameters given to [`ProofSheet::compare_with`], or its
+    /// default parameters and the curve mode of the engine.
+    pub fn compare_typefaces(mut self, compare_typefaces: bool) -> Self {
+        self.compare_typefaces = compare_typefaces;
+        self
+    }
+
+    /// Sets the parameters a compared typeface is drawn with when it is not
+    /// the typeface of the engine, after checking them.
+    pub fn compare_with(mut self, parameters: Parameters) -> Result<Self, ParameterError> {
+        parameters.validate()?;
+        self.compared_parameters
+            .retain(|other| core::mem::discriminant(other) != core::mem::discriminant(&parameters));
+        self.compared_parameters.push(parameters);
+        Ok(self)
+    }
+
+    /// Draws the 200-unit glyph box and its centre lines behind each glyph.
+    pub fn guides(mut self, guides: bool) -> Self {
+        self.guides = guides;
+        self
+    }
+
+    pub fn highlight_failures(mut self, highlight_failures: bool) -> Self {
+        self.highlight_failures = highlight_failures;
+        self
+    }
+
+    /// Renders the glyphs with the given names, looked up in the components
+    /// of `kage`, and returns the SVG document of each page. There is
+    /// always at least one page.
+    pub fn generate<S: AsRef<str>>(&self, kage: &Kage, names: &[S]) -> Vec<String> {
+        let compared_fonts;
+        let fonts: Vec<&FontWrapper> = if self.compare_typefaces {
+            compared_fonts = [
+                self.compared_font(kage, Typeface::Ming),
+                self.compared_font(kage, Typeface::Gothic),
+            ];
+            compared_fonts
+                .iter()
+                .map(|font| font.as_ref().unwrap_or(&kage.font))
+                .collect()
+        } else {
+            vec![&kage.font]
+        };
+
+        let per_page = self
+            .rows
+            .map(|rows| rows * self.columns)
+            .unwrap_or(names.len().max(1));
+
+        let mut pages: Vec<String> = names
+            .chunks(per_page)
+            .map(|chunk| self.generate_page(kage, &fonts, chunk))
+            .collect();
+        if pages.is_empty() {
+            pages.push(self.generate_page::<S>(kage, &fonts, &[]));
+        }
+
+        pages
+    }
+
+    /// Returns the font a compared typeface is drawn with, or `None` for
+    /// the font of the engine.
+    fn compared_font(&self, kage: &Kage, typeface: Typeface) -> Option<FontWrapper> {
+        let is_typeface = |parameters: &Parameters| match typeface {
+            Typeface::Ming => matches!(parameters, Parameters::Ming(_)),
+            Typeface::Gothic => matches!(parameters, Parameters::Gothic(_)),
+            Typeface::RoundedGothic => matches!(parameters, Parameters::RoundedGothic(_)),
+            Typeface::Textbook => matches!(parameters, Parameters::Textbook(_)),
+        };
+
+        if kage
+            .parameters()
+            .is_some_and(|parameters| is_typeface(&parameters))
+        {
+            return None;
+        }
+        match self
+            .compared_parameters
+            .iter()
+            .find(|parameters| is_typeface(parameters))
+        {
+            Some(parameters) => Some(
+                FontWrapper::from_parameters(parameters.clone()).expect("parameters are checked"),
+            ),
+            None => Some(FontWrapper::new(typeface, kage.font.use_curve())),
+        }
+    }
+
+    fn generate_page<S: AsRef<str>>(
+        &self,
+        kage: &Kage,
+        fonts: &[&FontWrapper],
+        names: &[S],
+    ) -> String {
+        let panel_count = fonts.len() as f64;
+        let cell_width =
+            self.cell_size * panel_count + PANEL_GAP * (panel_count - 1.0) + PADDING * 2.0;
+        let cell_height = self.cell_size + LABEL_HEIGHT + PADDING * 2.0;
+        let rows = names.len().div_ceil(self.columns).max(1);
+        let columns = self.columns.min(names.len()).max(1);
+        let width = cell_width * columns as f64 + MARGIN * 2.0;
+        let height = cell_height * rows as f64 + MARGIN * 2.0;
+
+        let mut buffer = format!(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" version="1.1" viewBox="0 0 {0} {1}" width="{0}" height="{1}">"#,
+            number(width),
+            number(height)
+        );
+        buffer.push('\n');
+        buffer.push_str(r#"<rect width="100%" height="100%" fill="white" />"#);
+        buffer.push('\n');
+
+        let mut polygons = Polygons::new();
+        for (index, name) in names.iter().enumerate() {
+            let name = name.as_ref();
+            let x = MARGIN + cell_width * (index % self.columns) as f64;
+            let y = MARGIN + cell_height * (index / self.columns) as f64;
+
+            let glyph_data = kage.components.search(name);
+            let failure = match glyph_data {
+                None => Some("not found".to_string()),
+                Some(data) => {
+                    let missing = kage.missing_components(data);
+                    (!missing.is_empty()).then(|| format!("missing: {}", missing.join(", ")))
+                }
+            };
+            let failure = failure.filter(|_| self.highlight_failures);
+
+            buffer.push_str(&format!(
+                r#"<g transform="translate({},{})">"#,
+                number(x),
+                number(y)
+            ));
+            buffer.push('\n');
+            if failure.is_some() {
+                buffer.push_str(&format!(
+                    r#"<rect width="{}" height="{}" fill="{FAILURE_FILL}" />"#,
+                    number(cell_width),
+                    number(cell_height)
+                ));
+                buffer.push('\n');
+            }
+
+            for (panel, font) in fonts.iter().enumerate() {
+                polygons.clear();
+                if let Some(data) = glyph_data {
+                    kage.make_glyph_with_font(font, &mut polygons, data);
+                }
+                self.push_panel(
+                    &mut buffer,
+                    PADDING + (self.cell_size + PANEL_GAP) * panel as f64,
+                    &polygons,
+                );
+            }
+
+            let label = match &failure {
+                Some(failure) => format!(
+                    r#"<text x="{}" y="{}" font-size="{FONT_SIZE}" font-family="sans-serif" fill="{FAILURE_TEXT}">{} ({})</text>"#,
+                    number(PADDING),
+                    number(PADDING + self.cell_size + LABEL_HEIGHT - 3.0),
+                    escape(name),
+                    escape(failure)
+                ),
+                None => format!(
+                    r#"<text x="{}" y="{}" font-size="{FONT_SIZE}" font-family="sans-serif">{}</text>"#,
+                    number(PADDING),
+                    number(PADDING + self.cell_size + LABEL_HEIGHT - 3.0),
+                    escape(name)
+                ),
+            };
+            buffer.push_str(&label);
+            buffer.push('\n');
+            buffer.push_str("</g>\n");
+        }
+
+        buffer.push_str("</svg>\n");
+        buffer
+    }
+
+    /// Appends one glyph box, scaled from the 200-unit glyph space.
+    fn push_panel(&self, buffer: &mut String, x: f64, polygons: &Polygons) {
+        buffer.push_str(&format!(
+            r#"<g transform="translate({},{}) scale({})">"#,
+            number(x),
+            number(PADDING),
+            self.cell_size / 200.0
+        ));
+        buffer.push('\n');
+
+        if self.guides {
+            buffer.push_str(&format!(
+                r#"<g fill="none" stroke="{GUIDE_STROKE}" vector-effect="non-scaling-stroke">"#
+            ));
+            buffer.push('\n');
+            buffer.push_str(r#"<rect width="200" height="200" />"#);
+            buffer.push('\n');
+            buffer.push_str(r#"<path d="M 100,0 V 200 M 0,100 H 200" stroke-dasharray="4 4" />"#);
+            buffer.push('\n');
+            buffer.push_str("</g>\n");
+        }
+
+        for polygon in polygons.array() {
+            let mut data = String::new();
+            push_path_data(&mut data, &polygon.segments(), number);
+            if data.is_empty() {
+                continue;
+            }
+            let color = match polygon.color() {
+                Some(rgb) => rgb.hex(),
+                None => "black".to_string(),
+            };
+            buffer.push_str(&format!(r#"<path d="{data}" fill="{color}" />"#));
+            buffer.push('\n');
+        }
+
+        buffer.push_str("</g>\n");
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn kage() -> Kage {
+        let mut kage = Kage::new(Typeface::Ming, false);
+        kage.components.set("u4e00", "1:0:0:20:100:180:100");
+        kage.components
+            .set("u4e8c", "1:0:0:40:60:160:60$1:0:0:20:140:180:140");
+        kage.components.set(
+            "u4e09",
+            "99:0:0:0:0:200:200:u4e00$99:0:0:0:0:200:100:u4e8c-g",
+        );
+        kage
+    }
+
+    #[test]
+    fn test_missing_components() {
+        let kage = kage();
+
+        assert_eq!(
+            kage.missing_components("99:0:0:0:0:200:200:u4e00$99:0:0:0:0:200:100:u4e8c-g"),
+            vec!["u4e8c-g"]
+        );
+        assert_eq!(
+            kage.missing_components("99:0:0:0:0:200:200:u4e09$99:0:0:0:0:100:100:u4e8c-g"),
+            vec!["u4e8c-g"]
+        );
+        assert!(kage.missing_components("1:0:0:20:100:180:100").is_empty());
+    }
+
+    #[test]
+    fn test_grid_and_paging() {
+        let kage = kage();
+        let names = ["u4e00", "u4e8c", "u4e00", "u4e8c", "u4e00"];
+
+        let pages = ProofSheet::new().columns(2).generate(&kage, &names);
+        assert_eq!(pages.len(), 1);
+        // 2 columns × 3 rows of 112 × 128 cells
+        assert!(pages[0].contains(r#"viewBox="0 0 244 404""#));
+        assert!(pages[0].contains(r#"<g transform="translate(122,138)">"#));
+        assert_eq!(pages[0].matches("<text ").count(), 5);
+
+        let pages = ProofSheet::new()
+            .columns(2)
+            .rows(Some(2))
+            .generate(&kage, &names);
+        assert_eq!(pages.len(), 2);
+        assert_eq!(pages[1].matches("<text ").count(), 1);
+
+        let pages = ProofSheet::new().generate::<&str>(&kage, &[]);
+        assert_eq!(pages.len(), 1);
+    }
+
+    #[test]
+    fn test_failures_and_guides() {
+        let kage = kage();
+        let page = ProofSheet::new()
+            .guides(true)
+            .generate(&kage, &["u4e09", "u4e00", "u0000"])
+            .remove(0);
+
+        assert_eq!(page.matches(FAILURE_FILL).count(), 2);
+        assert!(page.contains("u4e09 (missing: u4e8c-g)</text>"));
+        assert!(page.contains("u0000 (not found)</text>"));
+        assert_eq!(
+            page.matches(r#"<rect width="200" height="200" />"#).count(),
+            3
+        );
+    }
+
+    #[test]
+    fn test_compare_typefaces() {
+        let kage = kage();
+        let page = ProofSheet::new()
+            .compare_typefaces(true)
+            .generate(&kage, &["u4e00"])
+            .remove(0);
+
+        assert!(page.contains(r#"viewBox="0 0 238 148""#));
+        assert!(page.contains(r#"<g transform="translate(6,6) scale(0.5)">"#));
+        assert!(page.contains(r#"<g transform="translate(112,6) scale(0.5)">"#));
+    }
+
+    #[test]
+    fn test_compared_parameters() {
+        use crate::font::{gothic::Gothic, ming::Ming};
+
+        let paths = |page: &str| -> Vec<String> {
+            page.lines()
+                .filter(|line| line.starts_with("<path d=\"M"))
+                .map(str::to_string)
+                .collect()
+        };
+        let single = |parameters: Parameters| {
+            let mut kage = Kage::from_parameters(parameters).unwrap();
+            kage.components.set("u4e00", "1:0:0:20:100:180:100");
+            paths(&ProofSheet::new().generate(&kage, &["u4e00"]).remove(0))
+        };
+        let ming = Parameters::Ming(Ming {
+            width: 3.0,
+            ..Ming::default()
+        });
+        let gothic = Parameters::Gothic(Gothic {
+            width: 8.0,
+            ..Gothic::default()
+        });
+
+        let mut kage = Kage::from_parameters(ming.clone()).unwrap();
+        kage.components.set("u4e00", "1:0:0:20:100:180:100");
+        let page = ProofSheet::new()
+            .compare_typefaces(true)
+            .compare_with(gothic.clone())
+            .unwrap()
+            .generate(&kage, &["u4e00"])
+            .remove(0);
+        let ming = single(ming);
+        assert_eq!(paths(&page), [ming.clone(), single(gothic)].concat());
+
+        // the default parameters when none are given
+        let page = ProofSheet::new()
+            .compare_typefaces(true)
+            .generate(&kage, &["u4e00"])
+            .remove(0);
+        assert_eq!(
+            paths(&page)[ming.len()..],
+            single(Parameters::Gothic(Gothic::default()))
+        );
+
+        let invalid = Gothic {
+            width: f64::NAN,
+            ..Gothic::default()
+        };
+        assert!(matches!(
+            ProofSheet::new().compare_with(Parameters::Gothic(invalid)),
+            Err(ParameterError::OutOfRange { name: "width", .. })
+        ));
+    }
+}
@@ -0,0 +1,507 @@
+use crate::{
+    kage::Kage,
+    line::{
+        Line,
+        special_line::SpecialLineType,
+        stroke_line::{EndKind, StrokeKind, StrokeLineType},
+    },
+    postscript::number,
+    utils::{Point, Vector, cubic_bezier, normalize, quadratic_bezier},
+};
+
+/// The largest number of line pieces a single curve is flattened into.
+const MAX_CURVE_PIECES: usize = 64;
+
+/// Options of the skeleton extraction.
+#[derive(Debug, Clone)]
+pub struct SkeletonOptions {
+    curve_size: f64,
+    hooks: bool,
+}
+
+impl Default for SkeletonOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SkeletonOptions {
+    /// Rounds bends with the corner size of the Gothic font and keeps the
+    /// flick hooks.
+    pub fn new() -> Self {
+        Self {
+            curve_size: 10.0,
+            hooks: true,
+        }
+    }
+
+    /// Sets the distance from a bend at which its rounded corner starts.
+    /// `0.0` keeps the corners sharp.
+    pub fn curve_size(mut self, curve_size: f64) -> Self {
+        self.curve_size = curve_size;
+        self
+    }
+
+    /// Whether upward flicks (`tail` 4 and 5) are drawn as hooks at the end
+    /// of the stroke.
+    pub fn hooks(mut self, hooks: bool) -> Self {
+        self.hooks = hooks;
+        self
+    }
+}
+
+/// A piece of the centre line of a stroke.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SkeletonSegment {
+    Line(Point, Point),
+    Quadratic(Point, Point, Point),
+    Cubic(Point, Point, Point, Point),
+}
+
+impl SkeletonSegment {
+    pub fn start(&self) -> Point {
+        match *self {
+            Self::Line(start, _) | Self::Quadratic(start, _, _) | Self::Cubic(start, _, _, _) => {
+                start
+            }
+        }
+    }
+
+    pub fn end(&self) -> Point {
+        match *self {
+            Self::Line(_, end) | Self::Quadratic(_, _, end) | Self::Cubic(_, _, _, end) => end,
+        }
+    }
+
+    fn points(&self) -> Vec<Point> {
+        match *self {
+            Self::Line(p1, p2) => vec![p1, p2],
+            Self::Quadratic(p1, p2, p3) => vec![p1, p2, p3],
+            Self::Cubic(p1, p2, p3, p4) => vec![p1, p2, p3, p4],
+        }
+    }
+
+    fn map<F: Fn(Point) -> Point>(&self, f: F) -> Self {
+        match *self {
+            Self::Line(p1, p2) => Self::Line(f(p1), f(p2)),
+            Self::Quadratic(p1, p2, p3) => Self::Quadratic(f(p1), f(p2), f(p3)),
+            Self::Cubic(p1, p2, p3, p4) => Self::Cubic(f(p1), f(p2), f(p3), f(p4)),
+        }
+    }
+
+    fn is_degenerate(&self) -> bool {
+        let points = self.points();
+        points.iter().all(|point| *point == points[0])
+    }
+
+    /// Appends the points after the start point, with curves flattened so
+    /// that they deviate from the polyline by at most `tolerance`.
+    pub(crate) fn flatten(&self, tolerance: f64, polyline: &mut Vec<Point>) {
+        let deviation = |a: Point, b: Point, c: Point| {
+            Vector::new(a.x - 2.0 * b.x + c.x, a.y - 2.0 * b.y + c.y).hypot()
+        };
+        let pieces = |deviation: f64| {
+            ((deviation / tolerance.max(f64::EPSILON)).sqrt().ceil() as usize)
+                .clamp(1, MAX_CURVE_PIECES)
+        };
+
+        match *self {
+            Self::Line(_, end) => polyline.push(end),
+            Self::Quadratic(p1, p2, p3) => {
+                let count = pieces(deviation(p1, p2, p3) / 4.0);
+                for index in 1..=count {
+                    let point = quadratic_bezier(p1, p2, p3, index as f64 / count as f64);
+                    polyline.push(on_curve(point));
+                }
+            }
+            Self::Cubic(p1, p2, p3, p4) => {
+                let count = pieces(deviation(p1, p2, p3).max(deviation(p2, p3, p4)) * 0.75);
+                for index in 1..=count {
+                    let point = cubic_bezier(p1, p2, p3, p4, index as f64 / count as f64);
+                    polyline.push(on_curve(point));
+                }
+            }
+        }
+    }
+}
+
+/// The centre line of one stroke, from its head to its tail.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StrokeSkeleton {
+    kind: StrokeKind,
+    head: EndKind,
+    tail: EndKind,
+    segments: Vec<SkeletonSegment>,
+}
+
+impl StrokeSkeleton {
+    pub fn kind(&self) -> StrokeKind {
+        self.kind
+    }
+
+    /// The shape of the start of the stroke.
+    pub fn head(&self) -> EndKind {
+        self.head
+    }
+
+    /// The shape of the end of the stroke.
+    pub fn tail(&self) -> EndKind {
+        self.tail
+    }
+
+    /// The pieces of the centre line, in drawing order.
+    pub fn segments(&self) -> &[SkeletonSegment] {
+        &self.segments
+    }
+
+    /// The unit vector the stroke starts in, if it has any length.
+    pub fn start_direction(&self) -> Option<(f64, f64)> {
+        self.segments.iter().find_map(|segment| {
+            let points = segment.points();
+            let start = points[0];
+            points[1..]
+                .iter()
+                .find(|point| **point != start)
+                .map(|point| unit(*point - start))
+        })
+    }
+
+    /// The unit vector the stroke ends in, if it has any length.
+    pub fn end_direction(&self) -> Option<(f64, f64)> {
+        self.segments.iter().rev().find_map(|segment| {
+            let points = segment.points();
+            let end = points[points.len() - 1];
+            points[..points.len() - 1]
+                .iter()
+                .rev()
+                .find(|point| **point != end)
+                .map(|point| unit(end - *point))
+        })
+    }
+
+    /// Samples the centre line into a polyline, which deviates from the
+    /// curves by at most `tolerance` glyph units.
+    pub fn to_polyline(&self, tolerance: f64) -> Vec<Point> {
+        let mut polyline = Vec::new();
+        if let Some(first) = self.segments.first() {
+            polyline.push(on_curve(first.start()));
+        }
+        for segment in &self.segments {
+            segment.flatten(tolerance, &mut polyline);
+        }
+
+        polyline
+    }
+
+    /// Returns the centre line as SVG path data, with `Q` and `C` commands
+    /// for its curves.
+    pub fn path_data(&self) -> String {
+        let point = |point: Point| format!("{},{}", number(point.x), number(point.y));
+
+        let mut data = String::new();
+        let mut current: Option<Point> = None;
+        for segment in &self.segments {
+            if current != Some(segment.start()) {
+                if !data.is_empty() {
+                    data.push(' ');
+                }
+                data.push_str(&format!("M {}", point(segment.start())));
+            }
+            match *segment {
+                SkeletonSegment::Line(_, end) => data.push_str(&format!(" L {}", point(end))),
+                SkeletonSegment::Quadratic(_, control, end) => {
+                    data.push_str(&format!(" Q {} {}", point(control), point(end)))
+                }
+                SkeletonSegment::Cubic(_, control_1, control_2, end) => data.push_str(&format!(
+                    " C {} {} {}",
+                    point(control_1),
+                    point(control_2),
+                    point(end)
+                )),
+            }
+            current = Some(segment.end());
+        }
+
+        data
+    }
+
+    fn points(&self) -> impl Iterator<Item = Point> + '_ {
+        self.segments.iter().flat_map(|segment| segment.points())
+    }
+}
+
+fn on_curve(point: Point) -> Point {
+    Point::new(point.x, point.y, Some(false))
+}
+
+fn unit(vector: Point) -> (f64, f64) {
+    let length = Vector::from(vector).hypot();
+    (vector.x / length, vector.y / length)
+}
+
+/// Returns the point `size` away from `corner` towards `towards`, or along
+/// `fallback` when both points coincide.
+fn joint(corner: Point, towards: Point, size: f64, fallback: (f64, f64)) -> Point {
+    let delta: Vector = if corner == towards {
+        fallback.into()
+    } else {
+        normalize(towards - corner, size)
+    };
+    on_curve(corner + delta)
+}
+
+/// The point `size` before `end` on the way from `control`, where a left
+/// upward flick leaves a curve. Vertical and horizontal curves are assumed
+/// to run downwards and rightwards, as in the Gothic font.
+fn left_flick_joint(control: Point, end: Point, size: f64) -> Point {
+    let delta: Vector = if control.x == end.x {
+        (0.0, -size).into()
+    } else if control.y == end.y {
+        (-size, 0.0).into()
+    } else {
+        normalize(control - end, size)
+    };
+    on_curve(end + delta)
+}
+
+/// The end of a left upward flick drawn from `point`.
+fn left_hook(point: Point, size: f64) -> Point {
+    Point::new(point.x - size * 2.0, point.y - size * 0.5, Some(false))
+}
+
+/// The end of a right upward flick drawn from `point`.
+fn right_hook(point: Point, size: f64) -> Point {
+    Point::new(point.x + size * 0.5, point.y - size * 2.0, Some(false))
+}
+
+/// Decomposes a stroke the way the Gothic font does, without widening its
+/// ends for connections.
+pub(crate) fn stroke_skeleton(
+    stroke: &StrokeLineType,
+    options: &SkeletonOptions,
+) -> StrokeSkeleton {
+    use SkeletonSegment::{Cubic, Line, Quadratic};
+
+    let size = options.curve_size;
+    let tail = stroke.tail_shape;
+    let left_flick = options.hooks && tail.kind == EndKind::LeftUpwardFlick;
+    let right_flick = options.hooks && tail.kind == EndKind::RightUpwardFlick;
+    let [p1, p2, p3, p4] = [
+        stroke.point_1,
+        stroke.point_2,
+        stroke.point_3,
+        stroke.point_4,
+    ]
+    .map(on_curve);
+
+    let segments = match stroke.stroke_type.kind {
+        StrokeKind::StraightLine => {
+            if left_flick {
+                let joint_point = joint(p2, p1, size, (0.0, size));
+                vec![
+                    Line(p1, joint_point),
+                    Quadratic(joint_point, p2, left_hook(p2, size)),
+                ]
+            } else {
+                vec![Line(p1, p2)]
+            }
+        }
+        StrokeKind::Curve => {
+            if left_flick {
+                let joint_point = left_flick_joint(p2, p3, size);
+                vec![
+                    Quadratic(p1, p2, joint_point),
+                    Quadratic(joint_point, p3, left_hook(p3, size)),
+                ]
+            } else if right_flick && tail.opt == 0 {
+                let control = Point::new(p3.x + size, p3.y, Some(false));
+                vec![
+                    Quadratic(p1, p2, p3),
+                    Quadratic(p3, control, right_hook(control, size)),
+                ]
+            } else {
+                vec![Quadratic(p1, p2, p3)]
+            }
+        }
+        StrokeKind::BendLine | StrokeKind::OtsuCurve => {
+            let (size, flick) = if stroke.stroke_type.kind == StrokeKind::BendLine {
+                (size, right_flick && tail.opt_1 == 0)
+            } else {
+                let scale = (Vector::from(p3 - p2).hypot() / 120.0 * 6.0).min(6.0);
+                (size * scale, right_flick && tail.opt == 0)
+            };
+            let joint_point_1 = joint(p2, p1, size, (0.0, size));
+            let joint_point_2 = joint(p2, p3, size, (0.0, -size));
+
+            let mut segments = vec![
+                Line(p1, joint_point_1),
+                Quadratic(joint_point_1, p2, joint_point_2),
+            ];
+            if flick {
+                let joint_point_3 = Point::new(p3.x - options.curve_size, p3.y, Some(false));
+                segments.push(Line(joint_point_2, joint_point_3));
+                segments.push(Quadratic(
+                    joint_point_3,
+                    p3,
+                    right_hook(p3, options.curve_size),
+                ));
+            } else {
+                segments.push(Line(joint_point_2, p3));
+            }
+            segments
+        }
+        StrokeKind::ComplexCurve => {
+            if left_flick {
+                let joint_point = left_flick_joint(p3, p4, size);
+                vec![
+                    Cubic(p1, p2, p3, joint_point),
+                    Quadratic(joint_point, p4, left_hook(p4, size)),
+                ]
+            } else if right_flick && tail.opt == 0 {
+                let joint_point = Point::new(p4.x - size, p4.y, Some(false));
+                vec![
+                    Cubic(p1, p2, p3, joint_point),
+                    Quadratic(joint_point, p4, right_hook(p4, size)),
+                ]
+            } else {
+                vec![Cubic(p1, p2, p3, p4)]
+            }
+        }
+        StrokeKind::VerticalSlash => vec![Line(p1, p2), Quadratic(p2, p3, p4)],
+        StrokeKind::Unknown => vec![],
+    };
+
+    StrokeSkeleton {
+        kind: stroke.stroke_type.kind,
+        head: stroke.head_shape.kind,
+        tail: tail.kind,
+        segments: segments
+            .into_iter()
+            .filter(|segment| !segment.is_degenerate())
+            .collect(),
+    }
+}
+
+/// Applies a special line to the skeletons lying entirely in its box, like
+/// `df_transform` does to polygons.
+fn transform_skeletons(skeletons: &mut [StrokeSkeleton], special_line: SpecialLineType) {
+    for skeleton in skeletons {
+        if skeleton.points().all(|point| special_line.contains(point)) {
+            for segment in &mut skeleton.segments {
+                *segment = segment.map(|point| special_line.transform_point(point));
+            }
+        }
+    }
+}
+
+impl Kage {
+    /// Extracts the centre line of each stroke of the glyph, in stroke
+    /// order. Strokes that draw nothing yield an empty skeleton, so the
+    /// indices match the strokes of the expanded glyph data.
+    pub fn make_skeleton_with_data(
+        &self,
+        data: &str,
+        options: &SkeletonOptions,
+    ) -> Vec<StrokeSkeleton> {
+        let mut skeletons = Vec::new();
+
+        for line in self.get_each_expanded_line(data) {
+            match line {
+                Line::StrokeLine(stroke) => skeletons.push(stroke_skeleton(&stroke, options)),
+                Line::SpecialLine(special_line) => {
+                    transform_skeletons(&mut skeletons, special_line)
+                }
+                Line::ComponentReferenceLine(_) | Line::Unknown => {}
+            }
+        }
+
+        skeletons
+    }
+
+    pub fn make_skeleton_with_component_name(
+        &self,
+        component_name: &str,
+        options: &SkeletonOptions,
+    ) -> Vec<StrokeSkeleton> {
+        match self.components.search(component_name) {
+            Some(data) => self.make_skeleton_with_data(data, options),
+            None => Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Typeface;
+
+    fn skeletons(data: &str, options: &SkeletonOptions) -> Vec<StrokeSkeleton> {
+        Kage::new(Typeface::Ming, false).make_skeleton_with_data(data, options)
+    }
+
+    #[test]
+    fn test_straight_line() {
+        let result = skeletons("1:0:2:32:31:176:31", &SkeletonOptions::new());
+
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].kind(), StrokeKind::StraightLine);
+        assert_eq!(result[0].head(), EndKind::Free);
+        assert_eq!(result[0].tail(), EndKind::HorizontalConnection);
+        assert_eq!(result[0].path_data(), "M 32,31 L 176,31");
+        assert_eq!(result[0].start_direction(), Some((1.0, 0.0)));
+        assert_eq!(result[0].to_polyline(0.1).len(), 2);
+    }
+
+    #[test]
+    fn test_hooks_and_corners() {
+        // a vertical line ending with a left upward flick
+        let result = skeletons("1:0:4:100:20:100:180", &SkeletonOptions::new());
+        assert_eq!(result[0].path_data(), "M 100,20 L 100,170 Q 100,180 80,175");
+        assert_eq!(result[0].start_direction(), Some((0.0, 1.0)));
+
+        let result = skeletons("1:0:4:100:20:100:180", &SkeletonOptions::new().hooks(false));
+        assert_eq!(result[0].path_data(), "M 100,20 L 100,180");
+
+        // a bend with a rounded and a sharp corner
+        let result = skeletons("3:0:0:40:40:40:160:160:160", &SkeletonOptions::new());
+        assert_eq!(
+            result[0].path_data(),
+            "M 40,40 L 40,150 Q 40,160 50,160 L 160,160"
+        );
+        let result = skeletons(
+            "3:0:0:40:40:40:160:160:160",
+            &SkeletonOptions::new().curve_size(0.0),
+        );
+        assert_eq!(result[0].path_data(), "M 40,40 L 40,160 L 160,160");
+        assert_eq!(result[0].end_direction(), Some((1.0, 0.0)));
+    }
+
+    #[test]
+    fn test_polyline() {
+        let result = skeletons("2:7:8:66:12:58:82:20:138", &SkeletonOptions::new());
+        let polyline = result[0].to_polyline(0.1);
+
+        assert!(polyline.len() > 3);
+        assert_eq!(polyline[0], (66.0, 12.0).into());
+        assert_eq!(polyline[polyline.len() - 1], (20.0, 138.0).into());
+        // every sample lies on the curve
+        for point in &polyline {
+            assert!(point.x() >= 20.0 && point.x() <= 66.0);
+            assert!(!point.is_off_curve());
+        }
+        assert!(result[0].to_polyline(0.01).len() > polyline.len());
+    }
+
+    #[test]
+    fn test_special_line() {
+        let result = skeletons(
+            "1:0:0:20:50:80:50$1:0:0:120:50:180:50$0:98:0:0:0:100:200",
+            &SkeletonOptions::new(),
+        );
+
+        // only the stroke inside the box is flipped
+        assert_eq!(result[0].path_data(), "M 80,50 L 20,50");
+        assert_eq!(result[0].start_direction(), Some((-1.0, 0.0)));
+        assert_eq!(result[1].path_data(), "M 120,50 L 180,50");
+    }
+}
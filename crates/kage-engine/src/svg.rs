@@ -0,0 +1,413 @@
+use crate::{
+    polygon::Segment,
+    polygons::Polygons,
+    utils::{Point, round},
+};
+
+/// What [`Polygons::generate_svg_with`] wraps the glyph in.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SvgOutput {
+    /// A standalone `<svg>` document.
+    Document,
+    /// A bare `<g>` element, to be embedded into another document.
+    Fragment,
+    /// A `<symbol>` element with the given id, to be referenced with `<use>`.
+    Symbol(String),
+}
+
+/// Options of the configurable SVG writer.
+///
+/// The defaults produce a 200×200 document of the whole glyph box, filled
+/// in black, with coordinates written at full precision.
+#[derive(Debug, Clone)]
+pub struct SvgOptions {
+    width: f64,
+    height: f64,
+    /// (min_x, min_y, width, height) in glyph units.
+    view_box: (f64, f64, f64, f64),
+    fill: String,
+    stroke: Option<(String, f64)>,
+    precision: Option<i32>,
+    merge_paths: bool,
+    id_prefix: Option<String>,
+    xml_declaration: bool,
+    title: Option<String>,
+    output: SvgOutput,
+}
+
+impl Default for SvgOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SvgOptions {
+    pub fn new() -> Self {
+        Self {
+            width: 200.0,
+            height: 200.0,
+            view_box: (0.0, 0.0, 200.0, 200.0),
+            fill: "black".to_string(),
+            stroke: None,
+            precision: None,
+            merge_paths: false,
+            id_prefix: None,
+            xml_declaration: false,
+            title: None,
+            output: SvgOutput::Document,
+        }
+    }
+
+    /// Sets the `width` and `height` attributes of the document.
+    pub fn size(mut self, width: f64, height: f64) -> Self {
+        self.width = width;
+        self.height = height;
+        self
+    }
+
+    /// Sets the area of the glyph space (in glyph units) that is shown.
+    pub fn view_box(mut self, min_x: f64, min_y: f64, width: f64, height: f64) -> Self {
+        self.view_box = (min_x, min_y, width, height);
+        self
+    }
+
+    /// Grows the view box by `padding` glyph units on every side.
+    pub fn padding(mut self, padding: f64) -> Self {
+        let (min_x, min_y, width, height) = self.view_box;
+        self.view_box = (
+            min_x - padding,
+            min_y - padding,
+            width + padding * 2.0,
+            height + padding * 2.0,
+        );
+        self
+    }
+
+    /// Sets the fill of polygons without a colour of their own.
+    pub fn fill<S: Into<String>>(mut self, fill: S) -> Self {
+        self.fill = fill.into();
+        self
+    }
+
+    /// Outlines the polygons with the given colour and width, in glyph
+    /// units.
+    pub fn stroke<S: Into<String>>(mut self, stroke: S, width: f64) -> Self {
+        self.stroke = Some((stroke.into(), width));
+        self
+    }
+
+    /// Rounds coordinates to the given number of decimals.
+    pub fn precision(mut self, decimals: i32) -> Self {
+        self.precision = Some(decimals);
+        self
+    }
+
+    /// Writes all polygons into a single `<path>`. Polygon colours are then
+    /// ignored, and contours are oriented consistently so that overlapping
+    /// strokes do not cancel out under the non-zero fill rule.
+    pub fn merge_paths(mut self, merge_paths: bool) -> Self {
+        self.merge_paths = merge_paths;
+        self
+    }
+
+    /// Gives each `<path>` an id made of the prefix and its index.
+    pub fn ids<S: Into<String>>(mut self, prefix: S) -> Self {
+        self.id_prefix = Some(prefix.into());
+        self
+    }
+
+    pub fn xml_declaration(mut self, xml_declaration: bool) -> Self {
+        self.xml_declaration = xml_declaration;
+        self
+    }
+
+    pub fn title<S: Into<String>>(mut self, title: S) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    pub fn output(mut self, output: SvgOutput) -> Self {
+        self.output = output;
+        self
+    }
+
+    fn number(&self, value: f64) -> String {
+        let value = match self.precision {
+            Some(decimals) => round(value, decimals),
+            None => value,
+        };
+        if value == 0.0 {
+            "0".to_string()
+        } else {
+            value.to_string()
+        }
+    }
+
+    fn view_box_attribute(&self) -> String {
+        let (min_x, min_y, width, height) = self.view_box;
+        format!(
+            "{} {} {} {}",
+            self.number(min_x),
+            self.number(min_y),
+            self.number(width),
+            self.number(height)
+        )
+    }
+}
+
+/// Appends the SVG path data of one contour, with `Q` commands for its
+/// quadratic segments.
+pub(crate) fn push_path_data<F>(buffer: &mut String, segments: &[Segment], number: F)
+where
+    F: Fn(f64) -> String,
+{
+    let Some(first) = segments.first() else {
+        return;
+    };
+    let (Segment::Line(start, _) | Segment::Quadratic(start, _, _)) = first;
+    let point = |point: Point| format!("{},{}", number(point.x), number(point.y));
+
+    if !buffer.is_empty() {
+        buffer.push(' ');
+    }
+    buffer.push_str(&format!("M {}", point(*start)));
+    for segment in segments {
+        match *segment {
+            Segment::Line(_, end) => buffer.push_str(&format!(" L {}", point(end))),
+            Segment::Quadratic(_, control, end) => {
+                buffer.push_str(&format!(" Q {} {}", point(control), point(end)))
+            }
+        }
+    }
+    buffer.push_str(" Z");
+}
+
+/// Twice the signed area enclosed by the segments, taking the control
+/// polygon of quadratic segments. Positive for clockwise contours in glyph
+/// space (y pointing down).
+fn signed_area(segments: &[Segment]) -> f64 {
+    let cross = |a: Point, b: Point| a.x * b.y - b.x * a.y;
+    segments
+        .iter()
+        .map(|segment| match *segment {
+            Segment::Line(start, end) => cross(start, end),
+            Segment::Quadratic(start, control, end) => cross(start, control) + cross(control, end),
+        })
+        .sum()
+}
+
+fn reverse_segments(segments: &[Segment]) -> Vec<Segment> {
+    segments
+        .iter()
+        .rev()
+        .map(|segment| match *segment {
+            Segment::Line(start, end) => Segment::Line(end, start),
+            Segment::Quadratic(start, control, end) => Segment::Quadratic(end, control, start),
+        })
+        .collect()
+}
+
+pub(crate) fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+impl Polygons {
+    /// Generates an SVG image of the polygons, configured by `options`.
+    ///
+    /// Unlike [`Polygons::generate_svg`], every polygon is written as a
+    /// `<path>`, using `Q` commands wherever it has off-curve points.
+    pub fn generate_svg_with(&self, options: &SvgOptions) -> String {
+        let mut buffer = String::new();
+
+        if options.xml_declaration && options.output == SvgOutput::Document {
+            buffer.push_str(r#"<?xml version="1.0" encoding="UTF-8"?>"#);
+            buffer.push('\n');
+        }
+
+        match &options.output {
+            SvgOutput::Document => {
+                buffer.push_str(&format!(
+                    r#"<svg xmlns="http://www.w3.org/2000/svg" version="1.1" viewBox="{}" width="{}" height="{}">"#,
+                    options.view_box_attribute(),
+                    options.number(options.width),
+                    options.number(options.height)
+                ));
+                buffer.push('\n');
+            }
+            SvgOutput::Symbol(id) => {
+                buffer.push_str(&format!(
+                    r#"<symbol id="{}" viewBox="{}">"#,
+                    escape(id),
+                    options.view_box_attribute()
+                ));
+                buffer.push('\n');
+            }
+            SvgOutput::Fragment => {}
+        }
+
+        if let Some(title) = &options.title {
+            buffer.push_str(&format!("<title>{}</title>\n", escape(title)));
+        }
+
+        let mut group = format!(r#"<g fill="{}""#, escape(&options.fill));
+        if let Some((stroke, width)) = &options.stroke {
+            group.push_str(&format!(
+                r#" stroke="{}" stroke-width="{}" stroke-linejoin="round""#,
+                escape(stroke),
+                width
+            ));
+        }
+        group.push_str(">\n");
+        buffer.push_str(&group);
+
+        let number = |value: f64| options.number(value);
+        let id = |index: usize| match &options.id_prefix {
+            Some(prefix) => format!(r#" id="{}{}""#, escape(prefix), index),
+            None => String::new(),
+        };
+
+        if options.merge_paths {
+            let mut data = String::new();
+            for polygon in self.array() {
+                let segments = polygon.segments();
+                if signed_area(&segments) < 0.0 {
+                    push_path_data(&mut data, &reverse_segments(&segments), number);
+                } else {
+                    push_path_data(&mut data, &segments, number);
+                }
+            }
+            if !data.is_empty() {
+                buffer.push_str(&format!(r#"<path{} d="{}" />"#, id(0), data));
+                buffer.push('\n');
+            }
+        } else {
+            for (index, polygon) in self.array().enumerate() {
+                let mut data = String::new();
+                push_path_data(&mut data, &polygon.segments(), number);
+                if data.is_empty() {
+                    continue;
+                }
+
+                let fill = match polygon.color() {
+                    Some(rgb) => format!(r#" fill="{}""#, rgb.hex()),
+                    None => String::new(),
+                };
+                buffer.push_str(&format!(r#"<path{} d="{}"{} />"#, id(index), data, fill));
+                buffer.push('\n');
+            }
+        }
+
+        buffer.push_str("</g>\n");
+        match options.output {
+            SvgOutput::Document => buffer.push_str("</svg>\n"),
+            SvgOutput::Symbol(_) => buffer.push_str("</symbol>\n"),
+            SvgOutput::Fragment => {}
+        }
+
+        buffer
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::polygon::Polygon;
+
+    fn polygons() -> Polygons {
+        let mut polygons = Polygons::new();
+        polygons.push(Polygon::new(
+            vec![(20.0, 20.0), (180.0, 20.0), (180.0, 80.0), (20.0, 80.0)],
+            None,
+        ));
+        polygons.push(Polygon::new(
+            vec![
+                (30.0, 100.0, false),
+                (60.0, 130.0, true),
+                (90.0, 100.0, false),
+            ],
+            None,
+        ));
+        polygons
+    }
+
+    #[test]
+    fn test_default_document() {
+        assert_eq!(
+            polygons().generate_svg_with(&SvgOptions::new()),
+            r#"<svg xmlns="http://www.w3.org/2000/svg" version="1.1" viewBox="0 0 200 200" width="200" height="200">
+<g fill="black">
+<path d="M 20,20 L 180,20 L 180,80 L 20,80 L 20,20 Z" />
+<path d="M 30,100 Q 60,130 90,100 L 30,100 Z" />
+</g>
+</svg>
+"#
+        );
+    }
+
+    #[test]
+    fn test_document_options() {
+        let mut polygons = Polygons::new();
+        let mut triangle = Polygon::new(vec![(10.0, 10.0), (20.0, 10.0), (15.3, 20.0)], None);
+        triangle.set_color(255, 0, 0);
+        polygons.push(triangle);
+
+        let options = SvgOptions::new()
+            .size(64.0, 64.0)
+            .padding(10.0)
+            .fill("#333")
+            .stroke("blue", 0.5)
+            .precision(0)
+            .ids("glyph-")
+            .xml_declaration(true)
+            .title("a < b");
+        assert_eq!(
+            polygons.generate_svg_with(&options),
+            r##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" version="1.1" viewBox="-10 -10 220 220" width="64" height="64">
+<title>a &lt; b</title>
+<g fill="#333" stroke="blue" stroke-width="0.5" stroke-linejoin="round">
+<path id="glyph-0" d="M 10,10 L 20,10 L 15,20 L 10,10 Z" fill="#FF0000" />
+</g>
+</svg>
+"##
+        );
+    }
+
+    #[test]
+    fn test_merged_path() {
+        let mut polygons = polygons();
+        // the same square as the first polygon, wound the other way
+        polygons.push(Polygon::new(
+            vec![(20.0, 20.0), (20.0, 80.0), (180.0, 80.0), (180.0, 20.0)],
+            None,
+        ));
+
+        let svg = polygons.generate_svg_with(&SvgOptions::new().merge_paths(true));
+        assert_eq!(svg.matches("<path").count(), 1);
+        assert!(svg.contains(
+            r#"d="M 20,20 L 180,20 L 180,80 L 20,80 L 20,20 Z M 30,100 L 90,100 Q 60,130 30,100 Z M 20,20 L 180,20 L 180,80 L 20,80 L 20,20 Z""#
+        ));
+    }
+
+    #[test]
+    fn test_embeddable_output() {
+        let fragment = polygons().generate_svg_with(
+            &SvgOptions::new()
+                .output(SvgOutput::Fragment)
+                .xml_declaration(true),
+        );
+        assert!(fragment.starts_with(r#"<g fill="black">"#));
+        assert!(fragment.ends_with("</g>\n"));
+
+        let symbol = polygons().generate_svg_with(
+            &SvgOptions::new()
+                .view_box(0.0, 0.0, 100.0, 100.0)
+                .output(SvgOutput::Symbol("u4e00".to_string())),
+        );
+        assert!(symbol.starts_with(r#"<symbol id="u4e00" viewBox="0 0 100 100">"#));
+        assert!(symbol.ends_with("</g>\n</symbol>\n"));
+    }
+}
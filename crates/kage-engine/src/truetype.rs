@@ -0,0 +1,1698 @@
+// A TrueType font writer. Glyphs are written as quadratic outlines in the
+// `glyf` table; with several masters the font is a variable font along the
+// weight axis, with the masters as `gvar` deltas from the default one.
+
+use core::fmt;
+
+use time::OffsetDateTime;
+
+use crate::{
+    kage::Kage,
+    masters::{MasterError, Masters},
+    metrics::{EM, MetricsPolicy},
+    polygons::Polygons,
+    postscript::number,
+    vertical::VerticalAlternates,
+};
+
+/// Most glyphs in a font, `.notdef` included.
+pub const MAX_GLYPHS: usize = u16::MAX as usize;
+/// Most points in a glyph, so that the deltas of a master always fit in the
+/// 16-bit sizes of the `gvar` table.
+pub const MAX_GLYPH_POINTS: usize = 15_000;
+/// Most vertical alternates, so that the substitutions fit in the 16-bit
+/// offsets of the `GSUB` table.
+const MAX_VERTICAL_ALTERNATES: usize = (u16::MAX as usize - 6) / 2;
+
+/// Seconds from 1904-01-01, the epoch of OpenType dates, to 1970-01-01.
+const MAC_EPOCH_OFFSET: i64 = 2_082_844_800;
+/// Magic number of the `head` table.
+const HEAD_MAGIC: u32 = 0x5f0f_3cf5;
+/// Name ID of the weight axis; the names of the instances follow.
+const AXIS_NAME_ID: u16 = 256;
+/// Number of phantom points appended to the points of every glyph.
+const PHANTOM_POINTS: usize = 4;
+
+// simple glyph flags
+const ON_CURVE_POINT: u8 = 0x01;
+const X_SHORT_VECTOR: u8 = 0x02;
+const Y_SHORT_VECTOR: u8 = 0x04;
+const X_IS_SAME_OR_POSITIVE: u8 = 0x10;
+const Y_IS_SAME_OR_POSITIVE: u8 = 0x20;
+const OVERLAP_SIMPLE: u8 = 0x40;
+
+// tuple variation flags
+const SHARED_POINT_NUMBERS: u16 = 0x8000;
+const EMBEDDED_PEAK_TUPLE: u16 = 0x8000;
+const INTERMEDIATE_REGION: u16 = 0x4000;
+
+// packed delta run headers
+const DELTAS_ARE_ZERO: u8 = 0x80;
+const DELTAS_ARE_WORDS: u8 = 0x40;
+const MAX_DELTA_RUN: usize = 64;
+
+/// Options of the TrueType writer.
+///
+/// The 200×200 glyph box is mapped to a square em, with the top of the
+/// box at the ascender. The defaults produce a reproducible font: the
+/// `head` dates are zero unless a timestamp is given.
+#[derive(Debug, Clone)]
+pub struct FontOptions {
+    family_name: String,
+    units_per_em: u16,
+    ascender: i16,
+    creation_date: Option<OffsetDateTime>,
+}
+
+impl Default for FontOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FontOptions {
+    pub fn new() -> Self {
+        Self {
+            family_name: "Kage".to_string(),
+            units_per_em: 1000,
+            ascender: 880,
+            creation_date: None,
+        }
+    }
+
+    pub fn family_name<S: Into<String>>(mut self, family_name: S) -> Self {
+        self.family_name = family_name.into();
+        self
+    }
+
+    /// Sets the size of the em, which is the size of the glyph box, in
+    /// font units.
+    pub fn units_per_em(mut self, units_per_em: u16) -> Self {
+        self.units_per_em = units_per_em.clamp(16, 16384);
+        self
+    }
+
+    /// Sets the height of the top of the glyph box above the baseline, in
+    /// font units.
+    pub fn ascender(mut self, ascender: i16) -> Self {
+        self.ascender = ascender;
+        self
+    }
+
+    /// Sets the creation and modification dates of the font, or zero when
+    /// `None`.
+    pub fn creation_date(mut self, creation_date: Option<OffsetDateTime>) -> Self {
+        self.creation_date = creation_date;
+        self
+    }
+
+    fn descender(&self) -> i16 {
+        (self.ascender as i32 - self.units_per_em as i32).clamp(i16::MIN as i32, 0) as i16
+    }
+}
+
+/// A point of a contour in font units.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct FontPoint {
+    x: i32,
+    y: i32,
+    on_curve: bool,
+}
+
+type Contours = Vec<Vec<FontPoint>>;
+
+/// Start, peak and end of a variation region, as normalized coordinates.
+type Region = (f64, f64, f64);
+
+#[derive(Debug, Clone)]
+struct Glyph {
+    name: String,
+    code_points: Vec<char>,
+    advance_width: u16,
+    /// The contours drawn with each master, the default master first.
+    masters: Vec<Contours>,
+}
+
+impl Glyph {
+    fn contours(&self) -> &Contours {
+        &self.masters[0]
+    }
+
+    fn point_count(&self) -> usize {
+        self.contours().iter().map(Vec::len).sum()
+    }
+
+    /// Returns the x min, y min, x max and y max of the default master.
+    fn bounding_box(&self) -> Option<[i32; 4]> {
+        self.contours()
+            .iter()
+            .flatten()
+            .fold(None, |bounds, point| {
+                let [x_min, y_min, x_max, y_max] =
+                    bounds.unwrap_or([point.x, point.y, point.x, point.y]);
+                Some([
+                    x_min.min(point.x),
+                    y_min.min(point.y),
+                    x_max.max(point.x),
+                    y_max.max(point.y),
+                ])
+            })
+    }
+}
+
+/// Why a glyph cannot be added to a font.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FontError {
+    Master(MasterError),
+    /// The font already has [`MAX_GLYPHS`] glyphs, or as many vertical
+    /// alternates as it can substitute.
+    TooManyGlyphs,
+    /// The glyph has this many points, more than [`MAX_GLYPH_POINTS`].
+    TooManyPoints(usize),
+}
+
+impl From<MasterError> for FontError {
+    fn from(error: MasterError) -> Self {
+        FontError::Master(error)
+    }
+}
+
+impl fmt::Display for FontError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FontError::Master(error) => error.fmt(f),
+            FontError::TooManyGlyphs => write!(f, "too many glyphs"),
+            FontError::TooManyPoints(points) => write!(
+                f,
+                "{points} points in a glyph, more than {MAX_GLYPH_POINTS}"
+            ),
+        }
+    }
+}
+
+impl core::error::Error for FontError {}
+
+/// Builds a TrueType font from glyphs drawn with one or more masters.
+///
+/// With a single master the font is static. With several, it is a
+/// variable font with a `wght` axis spanning the weights of the masters,
+/// a named instance at each of them, and the first master as the default
+/// instance. Every glyph is drawn compatibly with
+/// [`Kage::make_compatible_glyphs`].
+///
+/// ```ignore
+/// let regular = Ming::default();
+/// let masters = Masters::new(vec![
+///     (400.0, Parameters::Ming(regular.clone())),
+///     (200.0, Parameters::Ming(regular.at_weight(200.0))),
+///     (900.0, Parameters::Ming(regular.at_weight(900.0))),
+/// ])?;
+/// let mut font = FontBuilder::new(masters, FontOptions::new().family_name("Kage Mincho"));
+/// font.push(&kage, "u4e00", &['一'], "1:0:2:32:100:176:100")?;
+/// std::fs::write("kage.ttf", font.finish())?;
+/// ```
+pub struct FontBuilder {
+    masters: Masters,
+    options: FontOptions,
+    glyphs: Vec<Glyph>,
+    /// Indices in `glyphs` of the glyphs with a vertical alternate, and of
+    /// the alternate.
+    vertical: Vec<(usize, usize)>,
+}
+
+impl FontBuilder {
+    pub fn new(masters: Masters, options: FontOptions) -> Self {
+        Self {
+            masters,
+            options,
+            glyphs: Vec::new(),
+            vertical: Vec::new(),
+        }
+    }
+
+    /// Number of glyphs pushed so far, without `.notdef`.
+    pub fn len(&self) -> usize {
+        self.glyphs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.glyphs.is_empty()
+    }
+
+    /// Draws the glyph with every master and adds it to the font, mapped
+    /// from the given characters. The glyph is not added if the masters
+    /// draw it incompatibly, if it has more than [`MAX_GLYPH_POINTS`]
+    /// points or if the font is full.
+    ///
+    /// The advance width follows [`MetricsPolicy::for_char`] of the first
+    /// character.
+    pub fn push(
+        &mut self,
+        kage: &Kage,
+        name: &str,
+        code_points: &[char],
+        data: &str,
+    ) -> Result<(), FontError> {
+        let policy = code_points
+            .first()
+            .map_or(MetricsPolicy::FullWidth, |&c| MetricsPolicy::for_char(c));
+        self.push_with_metrics(kage, name, code_points, data, policy)
+    }
+
+    /// Like [`FontBuilder::push`], with the given metrics policy.
+    ///
+    /// The metrics are those of the default master; in a variable font
+    /// every instance has the same advance width.
+    pub fn push_with_metrics(
+        &mut self,
+        kage: &Kage,
+        name: &str,
+        code_points: &[char],
+        data: &str,
+        policy: MetricsPolicy,
+    ) -> Result<(), FontError> {
+        // .notdef comes first
+        if self.glyphs.len() + 1 >= MAX_GLYPHS {
+            return Err(FontError::TooManyGlyphs);
+        }
+
+        let glyphs = kage.make_compatible_glyphs(&self.masters, data)?;
+        let metrics = glyphs[0].metrics(policy);
+        let mut masters: Vec<Contours> = glyphs
+            .iter()
+            .map(|polygons| self.contours(polygons, metrics.x_offset))
+            .collect();
+
+        // TrueType fills contours drawn clockwise; the direction of the
+        // default master is kept in every master
+        for index in 0..masters[0].len() {
+            if signed_area(&masters[0][index]) > 0 {
+                for contours in &mut masters {
+                    contours[index].reverse();
+                }
+            }
+        }
+
+        let glyph = Glyph {
+            name: name.to_string(),
+            code_points: code_points.to_vec(),
+            advance_width: (metrics.advance_width * self.scale())
+                .round()
+                .clamp(0.0, u16::MAX as f64) as u16,
+            masters,
+        };
+        if glyph.point_count() > MAX_GLYPH_POINTS {
+            return Err(FontError::TooManyPoints(glyph.point_count()));
+        }
+        self.glyphs.push(glyph);
+        Ok(())
+    }
+
+    /// Adds a vertical alternate, named `{name}.vert`, for each glyph pushed
+    /// so far whose first character has a form in `alternates`, and
+    /// substitutes it with the `vert` feature. Returns the number of
+    /// alternates added.
+    ///
+    /// The alternates are drawn from the components of `kage` named after
+    /// the glyphs; glyphs that are not in its store are skipped.
+    pub fn push_vertical_alternates(
+        &mut self,
+        kage: &Kage,
+        alternates: &VerticalAlternates,
+    ) -> Result<usize, FontError> {
+        let mut count = 0;
+        for index in 0..self.glyphs.len() {
+            let glyph = &self.glyphs[index];
+            let Some(form) = glyph.code_points.first().and_then(|&c| alternates.get(c)) else {
+                continue;
+            };
+            if kage.components.search(&glyph.name).is_none()
+                || self.vertical.iter().any(|&(base, _)| base == index)
+            {
+                continue;
+            }
+
+            if self.vertical.len() == MAX_VERTICAL_ALTERNATES {
+                return Err(FontError::TooManyGlyphs);
+            }
+
+            let name = format!("{}.vert", glyph.name);
+            let data = form.glyph_data(&glyph.name);
+            self.push_with_metrics(kage, &name, &[], &data, MetricsPolicy::FullWidth)?;
+            self.vertical.push((index, self.glyphs.len() - 1));
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    /// Font units per glyph unit.
+    fn scale(&self) -> f64 {
+        self.options.units_per_em as f64 / EM
+    }
+
+    /// Converts polygons to contours in font units, with the y axis up,
+    /// moving them right by `x_offset` glyph units.
+    fn contours(&self, polygons: &Polygons, x_offset: f64) -> Contours {
+        let scale = self.scale();
+        let ascender = self.options.ascender as f64;
+
+        polygons
+            .array()
+            .map(|polygon| {
+                polygon
+                    .points()
+                    .iter()
+                    .map(|point| FontPoint {
+                        x: clamp_i16(((point.x + x_offset) * scale).round()),
+                        y: clamp_i16((ascender - point.y * scale).round()),
+                        on_curve: !point.is_off_curve(),
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Returns the `.notdef` glyph: a hollow box, the same in every master.
+    fn notdef(&self) -> Glyph {
+        let em = self.options.units_per_em as i32;
+        let margin = em / 20;
+        let rectangle = |x_min: i32, y_min: i32, x_max: i32, y_max: i32, clockwise: bool| {
+            let mut corners = vec![
+                (x_min, y_min),
+                (x_min, y_max),
+                (x_max, y_max),
+                (x_max, y_min),
+            ];
+            if !clockwise {
+                corners.reverse();
+            }
+            corners
+                .into_iter()
+                .map(|(x, y)| FontPoint {
+                    x,
+                    y,
+                    on_curve: true,
+                })
+                .collect::<Vec<_>>()
+        };
+        let top = self.options.ascender as i32 - margin;
+        let bottom = self.options.descender() as i32 + margin;
+        let contours = vec![
+            rectangle(margin, bottom, em - margin, top, true),
+            rectangle(
+                2 * margin,
+                bottom + margin,
+                em - 2 * margin,
+                top - margin,
+                false,
+            ),
+        ];
+
+        Glyph {
+            name: ".notdef".to_string(),
+            code_points: Vec::new(),
+            advance_width: self.options.units_per_em,
+            masters: vec![contours; self.masters.len()],
+        }
+    }
+
+    /// Writes the font file.
+    pub fn finish(&self) -> Vec<u8> {
+        let notdef = self.notdef();
+        let glyphs: Vec<&Glyph> = core::iter::once(&notdef).chain(&self.glyphs).collect();
+
+        let (glyf, loca) = glyf_and_loca(&glyphs);
+        let mut tables = vec![
+            (*b"OS/2", self.os2(&glyphs)),
+            (*b"cmap", cmap(&glyphs)),
+            (*b"glyf", glyf),
+            (*b"head", self.head(&glyphs)),
+            (*b"hhea", self.hhea(&glyphs)),
+            (*b"hmtx", self.hmtx(&glyphs)),
+            (*b"loca", loca),
+            (*b"maxp", maxp(&glyphs)),
+            (*b"name", self.name()),
+            (*b"post", self.post(&glyphs)),
+            (*b"vhea", self.vhea(&glyphs)),
+            (*b"vmtx", self.vmtx(&glyphs)),
+        ];
+        if !self.vertical.is_empty() {
+            tables.push((*b"GSUB", self.gsub()));
+        }
+        if self.masters.len() > 1 {
+            tables.push((*b"STAT", stat()));
+            tables.push((*b"fvar", self.fvar()));
+            tables.push((*b"gvar", self.gvar(&glyphs)));
+        }
+
+        assemble(tables)
+    }
+
+    fn head(&self, glyphs: &[&Glyph]) -> Vec<u8> {
+        let [x_min, y_min, x_max, y_max] = font_bounding_box(glyphs);
+        let date = self
+            .options
+            .creation_date
+            .map_or(0, |date| date.unix_timestamp() + MAC_EPOCH_OFFSET);
+
+        let mut table = Vec::with_capacity(54);
+        table.extend(1u16.to_be_bytes());
+        table.extend(0u16.to_be_bytes());
+        // font revision 1.0
+        table.extend(0x0001_0000u32.to_be_bytes());
+        // checksum adjustment, set once the font is assembled
+        table.extend(0u32.to_be_bytes());
+        table.extend(HEAD_MAGIC.to_be_bytes());
+        // baseline at y = 0, integer scaling
+        table.extend(0x0009u16.to_be_bytes());
+        table.extend(self.options.units_per_em.to_be_bytes());
+        table.extend(date.to_be_bytes());
+        table.extend(date.to_be_bytes());
+        for value in [x_min, y_min, x_max, y_max] {
+            table.extend((value as i16).to_be_bytes());
+        }
+        // mac style, lowest readable size
+        table.extend(0u16.to_be_bytes());
+        table.extend(8u16.to_be_bytes());
+        // font direction hint, long loca offsets, glyph data format
+        table.extend(2i16.to_be_bytes());
+        table.extend(1i16.to_be_bytes());
+        table.extend(0i16.to_be_bytes());
+        table
+    }
+
+    fn hhea(&self, glyphs: &[&Glyph]) -> Vec<u8> {
+        let max_advance = glyphs.iter().map(|glyph| glyph.advance_width).max();
+        let boxes: Vec<(u16, [i32; 4])> = glyphs
+            .iter()
+            .filter_map(|glyph| Some((glyph.advance_width, glyph.bounding_box()?)))
+            .collect();
+        let min_left = boxes.iter().map(|(_, bounds)| bounds[0]).min().unwrap_or(0);
+        let min_right = boxes
+            .iter()
+            .map(|(advance, bounds)| *advance as i32 - bounds[2])
+            .min()
+            .unwrap_or(0);
+        let max_extent = boxes.iter().map(|(_, bounds)| bounds[2]).max().unwrap_or(0);
+
+        let mut table = Vec::with_capacity(36);
+        table.extend(1u16.to_be_bytes());
+        table.extend(0u16.to_be_bytes());
+        table.extend(self.options.ascender.to_be_bytes());
+        table.extend(self.options.descender().to_be_bytes());
+        // line gap
+        table.extend(0i16.to_be_bytes());
+        table.extend(max_advance.unwrap_or(0).to_be_bytes());
+        for value in [min_left, min_right, max_extent] {
+            table.extend((value as i16).to_be_bytes());
+        }
+        // caret slope rise and run, caret offset, reserved
+        table.extend(1i16.to_be_bytes());
+        table.extend([0; 12]);
+        // metric data format
+        table.extend(0i16.to_be_bytes());
+        table.extend((glyphs.len() as u16).to_be_bytes());
+        table
+    }
+
+    fn hmtx(&self, glyphs: &[&Glyph]) -> Vec<u8> {
+        let mut table = Vec::with_capacity(glyphs.len() * 4);
+        for glyph in glyphs {
+            let left_side_bearing = glyph.bounding_box().map_or(0, |bounds| bounds[0]);
+            table.extend(glyph.advance_width.to_be_bytes());
+            table.extend((left_side_bearing as i16).to_be_bytes());
+        }
+        table
+    }
+
+    /// Returns the vertical header. Vertical text is set one em per glyph,
+    /// with the origin at the top of the glyph box, i.e. at the ascender;
+    /// a TrueType font needs no `VORG` table for it.
+    fn vhea(&self, glyphs: &[&Glyph]) -> Vec<u8> {
+        let em = self.options.units_per_em as i32;
+        let ascender = self.options.ascender as i32;
+        let boxes: Vec<[i32; 4]> = glyphs
+            .iter()
+            .filter_map(|glyph| glyph.bounding_box())
+            .collect();
+        let min_top = boxes
+            .iter()
+            .map(|bounds| ascender - bounds[3])
+            .min()
+            .unwrap_or(0);
+        let min_bottom = boxes
+            .iter()
+            .map(|bounds| em - ascender + bounds[1])
+            .min()
+            .unwrap_or(0);
+        let max_extent = boxes
+            .iter()
+            .map(|bounds| ascender - bounds[1])
+            .max()
+            .unwrap_or(0);
+
+        let mut table = Vec::with_capacity(36);
+        table.extend(0x0001_1000u32.to_be_bytes());
+        // typographic ascender and descender from the center line, line gap
+        table.extend(((em / 2) as i16).to_be_bytes());
+        table.extend((-(em / 2) as i16).to_be_bytes());
+        table.extend(0i16.to_be_bytes());
+        table.extend(self.options.units_per_em.to_be_bytes());
+        for value in [min_top, min_bottom, max_extent] {
+            table.extend((value as i16).to_be_bytes());
+        }
+        // caret slope rise and run, caret offset, reserved
+        table.extend(0i16.to_be_bytes());
+        table.extend(1i16.to_be_bytes());
+        table.extend([0; 10]);
+        // metric data format
+        table.extend(0i16.to_be_bytes());
+        table.extend((glyphs.len() as u16).to_be_bytes());
+        table
+    }
+
+    fn vmtx(&self, glyphs: &[&Glyph]) -> Vec<u8> {
+        let ascender = self.options.ascender as i32;
+
+        let mut table = Vec::with_capacity(glyphs.len() * 4);
+        for glyph in glyphs {
+            let top_side_bearing = glyph
+                .bounding_box()
+                .map_or(0, |bounds| ascender - bounds[3]);
+            table.extend(self.options.units_per_em.to_be_bytes());
+            table.extend((top_side_bearing as i16).to_be_bytes());
+        }
+        table
+    }
+
+    fn os2(&self, glyphs: &[&Glyph]) -> Vec<u8> {
+        let em = self.options.units_per_em as i32;
+        let scaled = |ratio: f64| (em as f64 * ratio).round() as i16;
+        let code_points: Vec<u32> = glyphs
+            .iter()
+            .flat_map(|glyph| glyph.code_points.iter().map(|&c| c as u32))
+            .collect();
+        let first = code_points.iter().min().map_or(0, |&c| c.min(0xffff));
+        let last = code_points.iter().max().map_or(0, |&c| c.min(0xffff));
+        let [_, y_min, _, y_max] = font_bounding_box(glyphs);
+        let weight = self.masters.weights().next().unwrap_or(400.0);
+
+        let mut table = Vec::with_capacity(96);
+        table.extend(4u16.to_be_bytes());
+        // average advance width, weight and width classes, embedding
+        table.extend(average_advance_width(glyphs).to_be_bytes());
+        table.extend((weight.round().clamp(1.0, 1000.0) as u16).to_be_bytes());
+        table.extend(5u16.to_be_bytes());
+        table.extend(0u16.to_be_bytes());
+        // subscript and superscript sizes and offsets
+        for value in [
+            scaled(0.65),
+            scaled(0.6),
+            0,
+            scaled(0.075),
+            scaled(0.65),
+            scaled(0.6),
+            0,
+            scaled(0.35),
+        ] {
+            table.extend(value.to_be_bytes());
+        }
+        // strikeout size and position
+        table.extend(scaled(0.05).to_be_bytes());
+        table.extend(scaled(0.25).to_be_bytes());
+        // family class, PANOSE
+        table.extend([0; 12]);
+        for range in unicode_ranges(&code_points) {
+            table.extend(range.to_be_bytes());
+        }
+        table.extend(b"NONE");
+        // REGULAR | USE_TYPO_METRICS
+        table.extend(0x00c0u16.to_be_bytes());
+        table.extend((first as u16).to_be_bytes());
+        table.extend((last as u16).to_be_bytes());
+        table.extend(self.options.ascender.to_be_bytes());
+        table.extend(self.options.descender().to_be_bytes());
+        table.extend(0i16.to_be_bytes());
+        let win_ascent = y_max.max(self.options.ascender as i32).max(0);
+        let win_descent = (-y_min).max(-(self.options.descender() as i32)).max(0);
+        table.extend((win_ascent.min(u16::MAX as i32) as u16).to_be_bytes());
+        table.extend((win_descent.min(u16::MAX as i32) as u16).to_be_bytes());
+        // code page ranges
+        table.extend([0; 8]);
+        // x height, cap height, default and break characters, max context
+        table.extend(scaled(0.5).to_be_bytes());
+        table.extend(scaled(0.7).to_be_bytes());
+        table.extend(0u16.to_be_bytes());
+        table.extend(0x20u16.to_be_bytes());
+        // single substitutions look at one glyph
+        table.extend(u16::from(!self.vertical.is_empty()).to_be_bytes());
+        table
+    }
+
+    /// Returns the glyph substitutions: the `vert` feature for every
+    /// script, with a single substitution lookup.
+    fn gsub(&self) -> Vec<u8> {
+        // glyph IDs, after .notdef
+        let mut substitutions: Vec<(u16, u16)> = self
+            .vertical
+            .iter()
+            .map(|&(base, alternate)| (base as u16 + 1, alternate as u16 + 1))
+            .collect();
+        substitutions.sort();
+
+        let mut script_list = Vec::new();
+        let scripts = [b"DFLT", b"hani", b"kana"];
+        script_list.extend((scripts.len() as u16).to_be_bytes());
+        for (index, tag) in scripts.iter().enumerate() {
+            script_list.extend(*tag);
+            script_list.extend((2 + 6 * scripts.len() as u16 + 12 * index as u16).to_be_bytes());
+        }
+        for _ in scripts {
+            // default language system at offset 4, no other one
+            script_list.extend(4u16.to_be_bytes());
+            script_list.extend(0u16.to_be_bytes());
+            // no reordering, no required feature, feature 0
+            script_list.extend(0u16.to_be_bytes());
+            script_list.extend(0xffffu16.to_be_bytes());
+            script_list.extend(1u16.to_be_bytes());
+            script_list.extend(0u16.to_be_bytes());
+        }
+
+        let mut feature_list = Vec::new();
+        feature_list.extend(1u16.to_be_bytes());
+        feature_list.extend(b"vert");
+        feature_list.extend(8u16.to_be_bytes());
+        // no parameters, lookup 0
+        feature_list.extend(0u16.to_be_bytes());
+        feature_list.extend(1u16.to_be_bytes());
+        feature_list.extend(0u16.to_be_bytes());
+
+        let mut lookup_list = Vec::new();
+        lookup_list.extend(1u16.to_be_bytes());
+        lookup_list.extend(4u16.to_be_bytes());
+        // single substitution, no flags, one subtable at offset 8
+        lookup_list.extend(1u16.to_be_bytes());
+        lookup_list.extend(0u16.to_be_bytes());
+        lookup_list.extend(1u16.to_be_bytes());
+        lookup_list.extend(8u16.to_be_bytes());
+        // format 2, listing the substitutes in coverage order
+        let coverage_offset = 6 + 2 * substitutions.len() as u16;
+        lookup_list.extend(2u16.to_be_bytes());
+        lookup_list.extend(coverage_offset.to_be_bytes());
+        lookup_list.extend((substitutions.len() as u16).to_be_bytes());
+        for (_, alternate) in &substitutions {
+            lookup_list.extend(alternate.to_be_bytes());
+        }
+        lookup_list.extend(1u16.to_be_bytes());
+        lookup_list.extend((substitutions.len() as u16).to_be_bytes());
+        for (base, _) in &substitutions {
+            lookup_list.extend(base.to_be_bytes());
+        }
+
+        let mut table = Vec::new();
+        table.extend(1u16.to_be_bytes());
+        table.extend(0u16.to_be_bytes());
+        let script_list_offset = 10;
+        let feature_list_offset = script_list_offset + script_list.len();
+        let lookup_list_offset = feature_list_offset + feature_list.len();
+        for offset in [script_list_offset, feature_list_offset, lookup_list_offset] {
+            table.extend((offset as u16).to_be_bytes());
+        }
+        table.extend(script_list);
+        table.extend(feature_list);
+        table.extend(lookup_list);
+        table
+    }
+
+    fn name(&self) -> Vec<u8> {
+        let family = &self.options.family_name;
+        let postscript_family: String = family
+            .chars()
+            .filter(|c| c.is_ascii_alphanumeric())
+            .collect();
+        let postscript_family = if postscript_family.is_empty() {
+            "Kage".to_string()
+        } else {
+            postscript_family
+        };
+
+        let mut records: Vec<(u16, String)> = vec![
+            (1, family.clone()),
+            (2, "Regular".to_string()),
+            (3, format!("{postscript_family}-Regular;1.000")),
+            (4, format!("{family} Regular")),
+            (5, "Version 1.000".to_string()),
+            (6, format!("{postscript_family}-Regular")),
+        ];
+        if self.masters.len() > 1 {
+            records.push((AXIS_NAME_ID, "Weight".to_string()));
+            for (index, weight) in self.instance_weights().into_iter().enumerate() {
+                records.push((AXIS_NAME_ID + 1 + index as u16, weight_name(weight)));
+            }
+        }
+
+        let strings: Vec<Vec<u8>> = records
+            .iter()
+            .map(|(_, string)| string.encode_utf16().flat_map(u16::to_be_bytes).collect())
+            .collect();
+
+        let mut table = Vec::new();
+        table.extend(0u16.to_be_bytes());
+        table.extend((records.len() as u16).to_be_bytes());
+        table.extend((6 + 12 * records.len() as u16).to_be_bytes());
+        let mut offset = 0;
+        for ((name_id, _), string) in records.iter().zip(&strings) {
+            // Windows, Unicode BMP, English (United States)
+            table.extend(3u16.to_be_bytes());
+            table.extend(1u16.to_be_bytes());
+            table.extend(0x0409u16.to_be_bytes());
+            table.extend(name_id.to_be_bytes());
+            table.extend((string.len() as u16).to_be_bytes());
+            table.extend((offset as u16).to_be_bytes());
+            offset += string.len();
+        }
+        for string in strings {
+            table.extend(string);
+        }
+        table
+    }
+
+    fn post(&self, glyphs: &[&Glyph]) -> Vec<u8> {
+        let em = self.options.units_per_em as i32;
+
+        // glyph names are indexed from 258 on, so a font with more glyphs
+        // than indices has none
+        let has_names = glyphs.len() + 257 <= u16::MAX as usize;
+
+        let mut table = Vec::new();
+        table.extend(
+            if has_names {
+                0x0002_0000u32
+            } else {
+                0x0003_0000
+            }
+            .to_be_bytes(),
+        );
+        // italic angle
+        table.extend(0u32.to_be_bytes());
+        table.extend((-(em / 10) as i16).to_be_bytes());
+        table.extend(((em / 20) as i16).to_be_bytes());
+        // fixed pitch, memory usage
+        table.extend([0; 20]);
+        if !has_names {
+            return table;
+        }
+
+        table.extend((glyphs.len() as u16).to_be_bytes());
+        let mut names: Vec<String> = Vec::with_capacity(glyphs.len());
+        for (index, glyph) in glyphs.iter().enumerate() {
+            if index == 0 {
+                // `.notdef` is the first standard Macintosh name
+                table.extend(0u16.to_be_bytes());
+                continue;
+            }
+            let valid = !glyph.name.is_empty()
+                && glyph.name.len() <= 63
+                && glyph
+                    .name
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '_' || c == '-')
+                && !names.contains(&glyph.name);
+            let name = if valid {
+                glyph.name.clone()
+            } else {
+                format!("glyph{index}")
+            };
+            table.extend((258 + names.len() as u16).to_be_bytes());
+            names.push(name);
+        }
+        for name in names {
+            table.push(name.len() as u8);
+            table.extend(name.as_bytes());
+        }
+        table
+    }
+
+    /// Returns the weights of the named instances, in increasing order.
+    fn instance_weights(&self) -> Vec<f64> {
+        let mut weights: Vec<f64> = self.masters.weights().collect();
+        weights.sort_by(f64::total_cmp);
+        weights
+    }
+
+    /// Returns the minimum, default and maximum weights.
+    fn axis(&self) -> (f64, f64, f64) {
+        let weights = self.instance_weights();
+        let default = self.masters.weights().next().unwrap_or(400.0);
+        (weights[0], default, weights[weights.len() - 1])
+    }
+
+    /// Returns the normalized coordinate of a weight on the axis.
+    fn normalize(&self, weight: f64) -> f64 {
+        let (min, default, max) = self.axis();
+        if weight < default {
+            (weight - default) / (default - min)
+        } else if weight > default {
+            (weight - default) / (max - default)
+        } else {
+            0.0
+        }
+    }
+
+    fn fvar(&self) -> Vec<u8> {
+        let (min, default, max) = self.axis();
+        let instances = self.instance_weights();
+
+        let mut table = Vec::new();
+        table.extend(1u16.to_be_bytes());
+        table.extend(0u16.to_be_bytes());
+        // axes array offset, reserved, axis count and size
+        table.extend(16u16.to_be_bytes());
+        table.extend(2u16.to_be_bytes());
+        table.extend(1u16.to_be_bytes());
+        table.extend(20u16.to_be_bytes());
+        table.extend((instances.len() as u16).to_be_bytes());
+        table.extend(8u16.to_be_bytes());
+
+        table.extend(b"wght");
+        for value in [min, default, max] {
+            table.extend(fixed(value).to_be_bytes());
+        }
+        table.extend(0u16.to_be_bytes());
+        table.extend(AXIS_NAME_ID.to_be_bytes());
+
+        for (index, weight) in instances.into_iter().enumerate() {
+            table.extend((AXIS_NAME_ID + 1 + index as u16).to_be_bytes());
+            table.extend(0u16.to_be_bytes());
+            table.extend(fixed(weight).to_be_bytes());
+        }
+        table
+    }
+
+    /// Returns the start, peak and end of the region of each master but
+    /// the default one, as normalized coordinates.
+    ///
+    /// The region of a master spans up to the neighbouring masters on the
+    /// same side of the default, so that at the weight of a master only
+    /// its own deltas apply.
+    fn regions(&self) -> Vec<Region> {
+        let peaks: Vec<f64> = self
+            .masters
+            .weights()
+            .skip(1)
+            .map(|weight| self.normalize(weight))
+            .collect();
+
+        peaks
+            .iter()
+            .map(|&peak| {
+                let same_side = peaks.iter().filter(|other| other.signum() == peak.signum());
+                let inner = same_side
+                    .clone()
+                    .filter(|other| other.abs() < peak.abs())
+                    .max_by(|a, b| a.abs().total_cmp(&b.abs()))
+                    .copied()
+                    .unwrap_or(0.0);
+                let outer = same_side
+                    .filter(|other| other.abs() > peak.abs())
+                    .min_by(|a, b| a.abs().total_cmp(&b.abs()))
+                    .copied()
+                    .unwrap_or(peak);
+                if peak > 0.0 {
+                    (inner, peak, outer)
+                } else {
+                    (outer, peak, inner)
+                }
+            })
+            .collect()
+    }
+
+    fn gvar(&self, glyphs: &[&Glyph]) -> Vec<u8> {
+        let regions = self.regions();
+        let header_size = 20 + 4 * (glyphs.len() + 1);
+
+        let mut data = Vec::new();
+        let mut offsets = Vec::with_capacity(glyphs.len() + 1);
+        for glyph in glyphs {
+            offsets.push(data.len() as u32);
+            data.extend(glyph_variations(glyph, &regions));
+            if data.len() % 2 == 1 {
+                data.push(0);
+            }
+        }
+        offsets.push(data.len() as u32);
+
+        let mut table = Vec::with_capacity(header_size + data.len());
+        table.extend(1u16.to_be_bytes());
+        table.extend(0u16.to_be_bytes());
+        // axis count, no shared tuples
+        table.extend(1u16.to_be_bytes());
+        table.extend(0u16.to_be_bytes());
+        table.extend((header_size as u32).to_be_bytes());
+        table.extend((glyphs.len() as u16).to_be_bytes());
+        // long offsets
+        table.extend(1u16.to_be_bytes());
+        table.extend((header_size as u32).to_be_bytes());
+        for offset in offsets {
+            table.extend(offset.to_be_bytes());
+        }
+        table.extend(data);
+        table
+    }
+}
+
+fn clamp_i16(value: f64) -> i32 {
+    value.clamp(i16::MIN as f64, i16::MAX as f64) as i32
+}
+
+/// Converts to a 16.16 fixed-point number.
+fn fixed(value: f64) -> i32 {
+    (value * 65536.0).round() as i32
+}
+
+/// Converts to a 2.14 fixed-point number.
+fn f2dot14(value: f64) -> i16 {
+    (value.clamp(-2.0, 1.999_939) * 16384.0).round() as i16
+}
+
+/// Twice the signed area of a contour, positive when counterclockwise.
+fn signed_area(contour: &[FontPoint]) -> i64 {
+    let mut area = 0;
+    for (index, point) in contour.iter().enumerate() {
+        let next = contour[(index + 1) % contour.len()];
+        area += point.x as i64 * next.y as i64 - next.x as i64 * point.y as i64;
+    }
+    area
+}
+
+/// Returns the average of the advance widths, without empty ones.
+fn average_advance_width(glyphs: &[&Glyph]) -> i16 {
+    let advances: Vec<u64> = glyphs
+        .iter()
+        .map(|glyph| glyph.advance_width as u64)
+        .filter(|&advance| advance > 0)
+        .collect();
+    if advances.is_empty() {
+        return 0;
+    }
+    (advances.iter().sum::<u64>() as f64 / advances.len() as f64).round() as i16
+}
+
+fn font_bounding_box(glyphs: &[&Glyph]) -> [i32; 4] {
+    glyphs
+        .iter()
+        .filter_map(|glyph| glyph.bounding_box())
+        .reduce(|a, b| {
+            [
+                a[0].min(b[0]),
+                a[1].min(b[1]),
+                a[2].max(b[2]),
+                a[3].max(b[3]),
+            ]
+        })
+        .unwrap_or([0; 4])
+}
+
+/// Returns the OS/2 Unicode range bits of the characters of a CJK font.
+fn unicode_ranges(code_points: &[u32]) -> [u32; 4] {
+    const RANGES: [(u32, u32, usize); 7] = [
+        (0x3000, 0x303f, 48),
+        (0x3040, 0x309f, 49),
+        (0x30a0, 0x30ff, 50),
+        (0x3400, 0x4dbf, 59),
+        (0x4e00, 0x9fff, 59),
+        (0xf900, 0xfaff, 61),
+        (0x10000, 0x10ffff, 57),
+    ];
+
+    let mut bits = [0u32; 4];
+    for &code_point in code_points {
+        if let Some(&(_, _, bit)) = RANGES
+            .iter()
+            .find(|(start, end, _)| (*start..=*end).contains(&code_point))
+        {
+            bits[bit / 32] |= 1 << (bit % 32);
+        }
+        if code_point >= 0x20000 {
+            // CJK extensions beyond the BMP
+            bits[59 / 32] |= 1 << (59 % 32);
+        }
+    }
+    bits
+}
+
+fn maxp(glyphs: &[&Glyph]) -> Vec<u8> {
+    let max_points = glyphs.iter().map(|glyph| glyph.point_count()).max();
+    let max_contours = glyphs.iter().map(|glyph| glyph.contours().len()).max();
+
+    let mut table = Vec::with_capacity(32);
+    table.extend(0x0001_0000u32.to_be_bytes());
+    table.extend((glyphs.len() as u16).to_be_bytes());
+    table.extend((max_points.unwrap_or(0) as u16).to_be_bytes());
+    table.extend((max_contours.unwrap_or(0) as u16).to_be_bytes());
+    // composite glyphs
+    table.extend([0; 4]);
+    // zones
+    table.extend(2u16.to_be_bytes());
+    // no instructions nor components
+    table.extend([0; 16]);
+    table
+}
+
+/// Returns the `glyf` and `loca` tables.
+fn glyf_and_loca(glyphs: &[&Glyph]) -> (Vec<u8>, Vec<u8>) {
+    let mut glyf = Vec::new();
+    let mut loca = Vec::with_capacity((glyphs.len() + 1) * 4);
+    for glyph in glyphs {
+        loca.extend((glyf.len() as u32).to_be_bytes());
+        write_simple_glyph(&mut glyf, glyph);
+        while glyf.len() % 4 != 0 {
+            glyf.push(0);
+        }
+    }
+    loca.extend((glyf.len() as u32).to_be_bytes());
+
+    (glyf, loca)
+}
+
+fn write_simple_glyph(buffer: &mut Vec<u8>, glyph: &Glyph) {
+    let Some(bounds) = glyph.bounding_box() else {
+        // empty glyphs have no data
+        return;
+    };
+    let contours = glyph.contours();
+
+    buffer.extend((contours.len() as i16).to_be_bytes());
+    for value in bounds {
+        buffer.extend((value as i16).to_be_bytes());
+    }
+    let mut end = 0;
+    for contour in contours {
+        end += contour.len();
+        buffer.extend(((end - 1) as u16).to_be_bytes());
+    }
+    // no instructions
+    buffer.extend(0u16.to_be_bytes());
+
+    let mut flags = Vec::with_capacity(end);
+    let mut xs = Vec::new();
+    let mut ys = Vec::new();
+    let (mut last_x, mut last_y) = (0, 0);
+    for point in contours.iter().flatten() {
+        let mut flag = if point.on_curve { ON_CURVE_POINT } else { 0 };
+        if flags.is_empty() {
+            flag |= OVERLAP_SIMPLE;
+        }
+        flag |= push_coordinate(
+            &mut xs,
+            point.x - last_x,
+            X_SHORT_VECTOR,
+            X_IS_SAME_OR_POSITIVE,
+        );
+        flag |= push_coordinate(
+            &mut ys,
+            point.y - last_y,
+            Y_SHORT_VECTOR,
+            Y_IS_SAME_OR_POSITIVE,
+        );
+        flags.push(flag);
+        (last_x, last_y) = (point.x, point.y);
+    }
+    buffer.extend(flags);
+    buffer.extend(xs);
+    buffer.extend(ys);
+}
+
+/// Writes a coordinate delta with the shortest encoding, and returns its
+/// flags.
+fn push_coordinate(buffer: &mut Vec<u8>, delta: i32, short: u8, same_or_positive: u8) -> u8 {
+    if delta == 0 {
+        same_or_positive
+    } else if delta.abs() <= 0xff {
+        buffer.push(delta.unsigned_abs() as u8);
+        if delta > 0 {
+            short | same_or_positive
+        } else {
+            short
+        }
+    } else {
+        buffer.extend((delta as i16).to_be_bytes());
+        0
+    }
+}
+
+/// Returns the character map, with a format 4 subtable for the BMP and a
+/// format 12 subtable for every character.
+fn cmap(glyphs: &[&Glyph]) -> Vec<u8> {
+    let mut mapping: Vec<(u32, u16)> = Vec::new();
+    for (index, glyph) in glyphs.iter().enumerate() {
+        for &code_point in &glyph.code_points {
+            mapping.push((code_point as u32, index as u16));
+        }
+    }
+    // the first glyph mapped from a character wins
+    mapping.sort_by_key(|&(code_point, _)| code_point);
+    mapping.dedup_by_key(|&mut (code_point, _)| code_point);
+
+    // runs of consecutive characters mapped to consecutive glyphs
+    let mut groups: Vec<(u32, u32, u16)> = Vec::new();
+    for &(code_point, glyph) in &mapping {
+        match groups.last_mut() {
+            Some((start, end, start_glyph))
+                if *end + 1 == code_point
+                    && u32::from(*start_glyph) + (code_point - *start) == u32::from(glyph) =>
+            {
+                *end = code_point;
+            }
+            _ => groups.push((code_point, code_point, glyph)),
+        }
+    }
+
+    let format_4 = cmap_format_4(&groups);
+    let mut format_12 = Vec::with_capacity(16 + groups.len() * 12);
+    format_12.extend(12u16.to_be_bytes());
+    format_12.extend(0u16.to_be_bytes());
+    format_12.extend((16 + groups.len() as u32 * 12).to_be_bytes());
+    format_12.extend(0u32.to_be_bytes());
+    format_12.extend((groups.len() as u32).to_be_bytes());
+    for &(start, end, glyph) in &groups {
+        format_12.extend(start.to_be_bytes());
+        format_12.extend(end.to_be_bytes());
+        format_12.extend(u32::from(glyph).to_be_bytes());
+    }
+
+    let format_4_offset = 4 + 8 * 4;
+    let format_12_offset = format_4_offset + format_4.len() as u32;
+    let mut table = Vec::new();
+    table.extend(0u16.to_be_bytes());
+    table.extend(4u16.to_be_bytes());
+    for (platform, encoding, offset) in [
+        (0u16, 3u16, format_4_offset),
+        (0, 4, format_12_offset),
+        (3, 1, format_4_offset),
+        (3, 10, format_12_offset),
+    ] {
+        table.extend(platform.to_be_bytes());
+        table.extend(encoding.to_be_bytes());
+        table.extend(offset.to_be_bytes());
+    }
+    table.extend(format_4);
+    table.extend(format_12);
+    table
+}
+
+fn cmap_format_4(groups: &[(u32, u32, u16)]) -> Vec<u8> {
+    // the subtable length is 16 bits; the characters that do not fit are
+    // only in the format 12 subtable
+    let max_segments = (u16::MAX as usize - 16) / 8;
+    let mut segments: Vec<(u16, u16, i16)> = groups
+        .iter()
+        .filter(|(start, _, _)| *start < 0xffff)
+        .map(|&(start, end, glyph)| {
+            let delta = (u32::from(glyph).wrapping_sub(start) & 0xffff) as u16 as i16;
+            (start as u16, end.min(0xfffe) as u16, delta)
+        })
+        .take(max_segments - 1)
+        .collect();
+    segments.push((0xffff, 0xffff, 1));
+
+    let segment_count = segments.len() as u16;
+    let entry_selector = 15 - segment_count.leading_zeros() as u16;
+    let search_range: u16 = 2 << entry_selector;
+
+    let mut table = Vec::with_capacity(16 + segments.len() * 8);
+    table.extend(4u16.to_be_bytes());
+    table.extend((16 + segment_count * 8).to_be_bytes());
+    table.extend(0u16.to_be_bytes());
+    table.extend((segment_count * 2).to_be_bytes());
+    table.extend(search_range.to_be_bytes());
+    table.extend(entry_selector.to_be_bytes());
+    table.extend((segment_count * 2 - search_range).to_be_bytes());
+    for (_, end, _) in &segments {
+        table.extend(end.to_be_bytes());
+    }
+    table.extend(0u16.to_be_bytes());
+    for (start, _, _) in &segments {
+        table.extend(start.to_be_bytes());
+    }
+    for (_, _, delta) in &segments {
+        table.extend(delta.to_be_bytes());
+    }
+    table.extend(vec![0; segments.len() * 2]);
+    table
+}
+
+fn stat() -> Vec<u8> {
+    let mut table = Vec::with_capacity(28);
+    table.extend(1u16.to_be_bytes());
+    table.extend(1u16.to_be_bytes());
+    // design axis size, count and offset
+    table.extend(8u16.to_be_bytes());
+    table.extend(1u16.to_be_bytes());
+    table.extend(20u32.to_be_bytes());
+    // no axis values
+    table.extend(0u16.to_be_bytes());
+    table.extend(0u32.to_be_bytes());
+    // elided fallback name: "Regular"
+    table.extend(2u16.to_be_bytes());
+
+    table.extend(b"wght");
+    table.extend(AXIS_NAME_ID.to_be_bytes());
+    table.extend(0u16.to_be_bytes());
+    table
+}
+
+/// Returns the name of a named instance.
+fn weight_name(weight: f64) -> String {
+    let name = match weight {
+        100.0 => "Thin",
+        200.0 => "ExtraLight",
+        300.0 => "Light",
+        400.0 => "Regular",
+        500.0 => "Medium",
+        600.0 => "SemiBold",
+        700.0 => "Bold",
+        800.0 => "ExtraBold",
+        900.0 => "Black",
+        _ => return format!("Weight {}", number(weight)),
+    };
+    name.to_string()
+}
+
+/// Returns the glyph variation data of a glyph, empty when no master moves
+/// any of its points.
+fn glyph_variations(glyph: &Glyph, regions: &[Region]) -> Vec<u8> {
+    let default: Vec<&FontPoint> = glyph.contours().iter().flatten().collect();
+    // deltas of each master, the phantom points staying in place
+    let tuples: Vec<(&Region, Vec<i32>, Vec<i32>)> = glyph.masters[1..]
+        .iter()
+        .zip(regions)
+        .map(|(contours, region)| {
+            let points = contours.iter().flatten();
+            let (mut x_deltas, mut y_deltas): (Vec<i32>, Vec<i32>) = points
+                .zip(&default)
+                .map(|(point, origin)| (point.x - origin.x, point.y - origin.y))
+                .unzip();
+            x_deltas.extend([0; PHANTOM_POINTS]);
+            y_deltas.extend([0; PHANTOM_POINTS]);
+            (region, x_deltas, y_deltas)
+        })
+        .filter(|(_, x_deltas, y_deltas)| x_deltas.iter().chain(y_deltas).any(|&delta| delta != 0))
+        .collect();
+    if tuples.is_empty() {
+        return Vec::new();
+    }
+
+    let mut headers = Vec::new();
+    // all points
+    let mut serialized = vec![0];
+    for ((start, peak, end), x_deltas, y_deltas) in &tuples {
+        let before = serialized.len();
+        push_packed_deltas(&mut serialized, x_deltas);
+        push_packed_deltas(&mut serialized, y_deltas);
+
+        headers.extend(((serialized.len() - before) as u16).to_be_bytes());
+        headers.extend((EMBEDDED_PEAK_TUPLE | INTERMEDIATE_REGION).to_be_bytes());
+        for value in [peak, start, end] {
+            headers.extend(f2dot14(*value).to_be_bytes());
+        }
+    }
+
+    let mut data = Vec::with_capacity(4 + headers.len() + serialized.len());
+    data.extend((SHARED_POINT_NUMBERS | tuples.len() as u16).to_be_bytes());
+    data.extend(((4 + headers.len()) as u16).to_be_bytes());
+    data.extend(headers);
+    data.extend(serialized);
+    data
+}
+
+/// Writes deltas as runs of zeros, of bytes or of words.
+fn push_packed_deltas(buffer: &mut Vec<u8>, deltas: &[i32]) {
+    let fits_byte = |delta: i32| (-128..=127).contains(&delta);
+
+    let mut index = 0;
+    while index < deltas.len() {
+        let rest = &deltas[index..deltas.len().min(index + MAX_DELTA_RUN)];
+        if rest[0] == 0 {
+            let count = rest.iter().take_while(|&&delta| delta == 0).count();
+            buffer.push(DELTAS_ARE_ZERO | (count - 1) as u8);
+            index += count;
+        } else if fits_byte(rest[0]) {
+            let count = rest
+                .iter()
+                .take_while(|&&delta| delta != 0 && fits_byte(delta))
+                .count();
+            buffer.push((count - 1) as u8);
+            buffer.extend(rest[..count].iter().map(|&delta| delta as i8 as u8));
+            index += count;
+        } else {
+            let count = rest.iter().take_while(|&&delta| !fits_byte(delta)).count();
+            buffer.push(DELTAS_ARE_WORDS | (count - 1) as u8);
+            for &delta in &rest[..count] {
+                buffer.extend((delta.clamp(i16::MIN as i32, i16::MAX as i32) as i16).to_be_bytes());
+            }
+            index += count;
+        }
+    }
+}
+
+fn checksum(data: &[u8]) -> u32 {
+    data.chunks(4).fold(0u32, |sum, chunk| {
+        let mut word = [0; 4];
+        word[..chunk.len()].copy_from_slice(chunk);
+        sum.wrapping_add(u32::from_be_bytes(word))
+    })
+}
+
+/// Writes the table directory followed by the tables, sorted by tag, and
+/// sets the checksum adjustment of the `head` table.
+fn assemble(mut tables: Vec<([u8; 4], Vec<u8>)>) -> Vec<u8> {
+    tables.sort_by_key(|(tag, _)| *tag);
+
+    let count = tables.len() as u16;
+    let entry_selector = 15 - count.leading_zeros() as u16;
+    let search_range: u16 = 16 << entry_selector;
+
+    let mut font = Vec::new();
+    font.extend(0x0001_0000u32.to_be_bytes());
+    font.extend(count.to_be_bytes());
+    font.extend(search_range.to_be_bytes());
+    font.extend(entry_selector.to_be_bytes());
+    font.extend((count * 16 - search_range).to_be_bytes());
+
+    let mut offset = 12 + 16 * tables.len();
+    let mut head_offset = None;
+    for (tag, table) in &tables {
+        if tag == b"head" {
+            head_offset = Some(offset);
+        }
+        font.extend(tag);
+        font.extend(checksum(table).to_be_bytes());
+        font.extend((offset as u32).to_be_bytes());
+        font.extend((table.len() as u32).to_be_bytes());
+        offset += table.len().next_multiple_of(4);
+    }
+    for (_, table) in &tables {
+        font.extend(table);
+        font.resize(font.len().next_multiple_of(4), 0);
+    }
+
+    if let Some(head_offset) = head_offset {
+        let adjustment = 0xb1b0_afbau32.wrapping_sub(checksum(&font));
+        font[head_offset + 8..head_offset + 12].copy_from_slice(&adjustment.to_be_bytes());
+    }
+    font
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        Parameters, Typeface,
+        font::{gothic::Gothic, weight::Interpolate},
+    };
+
+    fn read_u16(data: &[u8], offset: usize) -> u16 {
+        u16::from_be_bytes([data[offset], data[offset + 1]])
+    }
+
+    fn read_u32(data: &[u8], offset: usize) -> u32 {
+        u32::from_be_bytes(data[offset..offset + 4].try_into().unwrap())
+    }
+
+    /// Returns the tags of the tables, and the table with the given tag.
+    fn table<'a>(font: &'a [u8], tag: &[u8; 4]) -> (Vec<[u8; 4]>, Option<&'a [u8]>) {
+        let count = read_u16(font, 4) as usize;
+        let mut tags = Vec::new();
+        let mut found = None;
+        for index in 0..count {
+            let record = 12 + 16 * index;
+            let record_tag: [u8; 4] = font[record..record + 4].try_into().unwrap();
+            if &record_tag == tag {
+                let offset = read_u32(font, record + 8) as usize;
+                let length = read_u32(font, record + 12) as usize;
+                found = Some(&font[offset..offset + length]);
+            }
+            tags.push(record_tag);
+        }
+        (tags, found)
+    }
+
+    fn builder(weights: &[f64]) -> FontBuilder {
+        let regular = Gothic::default();
+        let masters = Masters::new(
+            weights
+                .iter()
+                .map(|&weight| (weight, Parameters::Gothic(regular.at_weight(weight))))
+                .collect(),
+        )
+        .unwrap();
+        let kage = Kage::new(Typeface::Gothic, false);
+
+        let mut builder = FontBuilder::new(masters, FontOptions::new().family_name("Kage Test"));
+        builder
+            .push(&kage, "u4e00", &['一'], "1:0:0:20:100:180:100")
+            .unwrap();
+        builder
+            .push(&kage, "u20000", &['\u{20000}'], "1:0:0:100:20:100:180")
+            .unwrap();
+        builder
+    }
+
+    #[test]
+    fn test_static_font() {
+        let font = builder(&[400.0]).finish();
+
+        let (tags, head) = table(&font, b"head");
+        assert_eq!(
+            tags,
+            vec![
+                *b"OS/2", *b"cmap", *b"glyf", *b"head", *b"hhea", *b"hmtx", *b"loca", *b"maxp",
+                *b"name", *b"post", *b"vhea", *b"vmtx"
+            ]
+        );
+        assert_eq!(checksum(&font), 0xb1b0_afba);
+        assert_eq!(read_u16(head.unwrap(), 18), 1000);
+        for (tag, length) in [
+            (b"OS/2", 96),
+            (b"head", 54),
+            (b"hhea", 36),
+            (b"maxp", 32),
+            (b"vhea", 36),
+        ] {
+            assert_eq!(table(&font, tag).1.unwrap().len(), length);
+        }
+
+        // .notdef and both glyphs
+        let (_, maxp) = table(&font, b"maxp");
+        assert_eq!(read_u16(maxp.unwrap(), 4), 3);
+
+        // a bar of 4 points, drawn clockwise
+        let (_, glyf) = table(&font, b"glyf");
+        let (_, loca) = table(&font, b"loca");
+        let glyf = glyf.unwrap();
+        let offset = read_u32(loca.unwrap(), 4) as usize;
+        assert_eq!(read_u16(glyf, offset), 1);
+        assert_eq!(
+            [2, 4, 6, 8].map(|index| read_u16(glyf, offset + index) as i16),
+            [100, 355, 900, 405]
+        );
+    }
+
+    #[test]
+    fn test_metrics() {
+        let mut builder = builder(&[400.0]);
+        let kage = Kage::new(Typeface::Gothic, false);
+        builder
+            .push(&kage, "A", &['A'], "1:0:0:20:100:80:100")
+            .unwrap();
+        builder
+            .push_with_metrics(
+                &kage,
+                "bar",
+                &[],
+                "1:0:0:20:100:80:100",
+                MetricsPolicy::Proportional { side_bearing: 10.0 },
+            )
+            .unwrap();
+        let font = builder.finish();
+
+        // advance width and left side bearing of each glyph
+        let (_, hmtx) = table(&font, b"hmtx");
+        let hmtx = hmtx.unwrap();
+        let metrics: Vec<(u16, i16)> = (0..5)
+            .map(|index| {
+                (
+                    read_u16(hmtx, index * 4),
+                    read_u16(hmtx, index * 4 + 2) as i16,
+                )
+            })
+            .collect();
+        assert_eq!(
+            metrics,
+            vec![(1000, 50), (1000, 100), (1000, 475), (500, 100), (400, 50)]
+        );
+        let (_, hhea) = table(&font, b"hhea");
+        assert_eq!(read_u16(hhea.unwrap(), 10), 1000);
+
+        // one em down, from the ascender to the top of the ink
+        let (_, vmtx) = table(&font, b"vmtx");
+        let vmtx = vmtx.unwrap();
+        assert_eq!(read_u16(vmtx, 4), 1000);
+        assert_eq!(read_u16(vmtx, 6) as i16, 880 - 405);
+        let (_, vhea) = table(&font, b"vhea");
+        assert_eq!(read_u16(vhea.unwrap(), 34), 5);
+    }
+
+    #[test]
+    fn test_cmap() {
+        let font = builder(&[400.0]).finish();
+        let (_, cmap) = table(&font, b"cmap");
+        let cmap = cmap.unwrap();
+
+        // format 4: a segment for 一 and the final one
+        let format_4 = &cmap[read_u32(cmap, 8) as usize..];
+        assert_eq!(read_u16(format_4, 0), 4);
+        assert_eq!(read_u16(format_4, 6), 4);
+        assert_eq!(read_u16(format_4, 14), 0x4e00);
+        assert_eq!(read_u16(format_4, 16), 0xffff);
+
+        // format 12: both characters
+        let format_12 = &cmap[read_u32(cmap, 16) as usize..];
+        assert_eq!(read_u16(format_12, 0), 12);
+        assert_eq!(read_u32(format_12, 12), 2);
+        assert_eq!(
+            [16, 20, 24, 28, 32, 36].map(|offset| read_u32(format_12, offset)),
+            [0x4e00, 0x4e00, 1, 0x20000, 0x20000, 2]
+        );
+    }
+
+    #[test]
+    fn test_variable_font() {
+        let builder = builder(&[400.0, 100.0, 700.0, 900.0]);
+        assert_eq!(
+            builder.regions(),
+            vec![(-1.0, -1.0, 0.0), (0.0, 0.6, 1.0), (0.6, 1.0, 1.0)]
+        );
+
+        let font = builder.finish();
+        assert_eq!(checksum(&font), 0xb1b0_afba);
+
+        let (tags, fvar) = table(&font, b"fvar");
+        assert!(tags.contains(b"STAT") && tags.contains(b"gvar"));
+        let fvar = fvar.unwrap();
+        assert_eq!(&fvar[16..20], b"wght");
+        assert_eq!(
+            [20, 24, 28].map(|offset| read_u32(fvar, offset) >> 16),
+            [100, 400, 900]
+        );
+        // 4 instances, from Thin to Black
+        assert_eq!(read_u16(fvar, 12), 4);
+        assert_eq!(read_u32(fvar, 36 + 4) >> 16, 100);
+
+        let (_, gvar) = table(&font, b"gvar");
+        let gvar = gvar.unwrap();
+        assert_eq!(read_u16(gvar, 12), 3);
+        let data = read_u32(gvar, 16) as usize;
+        // .notdef does not vary
+        assert_eq!(read_u32(gvar, 20), read_u32(gvar, 24));
+        let glyph = data + read_u32(gvar, 24) as usize;
+        assert_eq!(read_u16(gvar, glyph), SHARED_POINT_NUMBERS | 3);
+        // the first tuple peaks at the Thin instance
+        assert_eq!(
+            read_u16(gvar, glyph + 6),
+            EMBEDDED_PEAK_TUPLE | INTERMEDIATE_REGION
+        );
+        assert_eq!(read_u16(gvar, glyph + 8) as i16, -16384);
+    }
+
+    #[test]
+    fn test_vertical_alternates() {
+        let mut builder = builder(&[400.0, 700.0]);
+        let mut kage = Kage::new(Typeface::Gothic, false);
+        kage.components.push("u30fc", "1:0:0:20:100:180:100");
+        kage.components.push("u3001", "1:0:0:30:160:50:180");
+        for (name, c) in [("u30fc", 'ー'), ("u3001", '、')] {
+            let data = kage.components.search(name).unwrap().to_string();
+            builder.push(&kage, name, &[c], &data).unwrap();
+        }
+        // not in the store of the engine
+        builder
+            .push(&kage, "u300c", &['「'], "1:0:0:20:20:20:100")
+            .unwrap();
+
+        let alternates = VerticalAlternates::japanese();
+        assert_eq!(builder.push_vertical_alternates(&kage, &alternates), Ok(2));
+        assert_eq!(builder.push_vertical_alternates(&kage, &alternates), Ok(0));
+        assert_eq!(builder.glyphs[5].name, "u30fc.vert");
+
+        let font = builder.finish();
+        assert_eq!(checksum(&font), 0xb1b0_afba);
+        let (_, os2) = table(&font, b"OS/2");
+        assert_eq!(read_u16(os2.unwrap(), 94), 1);
+
+        let (_, gsub) = table(&font, b"GSUB");
+        let gsub = gsub.unwrap();
+        let feature_list = read_u16(gsub, 6) as usize;
+        assert_eq!(&gsub[feature_list + 2..feature_list + 6], b"vert");
+        // the coverage lists ー and 、, substituted in the same order
+        let lookup = read_u16(gsub, 8) as usize + 4;
+        let subtable = lookup + 8;
+        assert_eq!(read_u16(gsub, lookup), 1);
+        assert_eq!(read_u16(gsub, subtable), 2);
+        assert_eq!(
+            [6, 8].map(|offset| read_u16(gsub, subtable + offset)),
+            [6, 7]
+        );
+        let coverage = subtable + read_u16(gsub, subtable + 2) as usize;
+        assert_eq!(
+            [0, 2, 4, 6].map(|offset| read_u16(gsub, coverage + offset)),
+            [1, 2, 3, 4]
+        );
+    }
+
+    #[test]
+    fn test_limits() {
+        let mut builder = builder(&[400.0]);
+        let kage = Kage::new(Typeface::Gothic, false);
+
+        let data = vec!["1:0:0:20:100:180:100"; MAX_GLYPH_POINTS / 4 + 1].join("$");
+        assert_eq!(
+            builder.push(&kage, "many", &[], &data),
+            Err(FontError::TooManyPoints((MAX_GLYPH_POINTS / 4 + 1) * 4))
+        );
+        assert_eq!(builder.len(), 2);
+
+        for index in 2..MAX_GLYPHS - 1 {
+            builder.push(&kage, &format!("g{index}"), &[], "").unwrap();
+        }
+        assert_eq!(
+            builder.push(&kage, "full", &[], ""),
+            Err(FontError::TooManyGlyphs)
+        );
+
+        // too many glyphs to name them
+        let font = builder.finish();
+        let (_, maxp) = table(&font, b"maxp");
+        assert_eq!(read_u16(maxp.unwrap(), 4), u16::MAX);
+        let (_, post) = table(&font, b"post");
+        assert_eq!(read_u32(post.unwrap(), 0), 0x0003_0000);
+    }
+
+    #[test]
+    fn test_packed_deltas() {
+        let mut buffer = Vec::new();
+        push_packed_deltas(&mut buffer, &[0, 0, 0, 1, -2, 300, 0]);
+        assert_eq!(buffer, vec![0x82, 0x01, 0x01, 0xfe, 0x40, 0x01, 0x2c, 0x80]);
+
+        buffer.clear();
+        push_packed_deltas(&mut buffer, &[0; 70]);
+        assert_eq!(buffer, vec![0xbf, 0x85]);
+    }
+
+    #[test]
+    fn test_weight_names() {
+        assert_eq!(weight_name(700.0), "Bold");
+        assert_eq!(weight_name(450.0), "Weight 450");
+    }
+}
@@ -144,10 +144,25 @@ impl Point {
         off_curve: None,
     };
 
-    pub(crate) fn new(x: f64, y: f64, off_curve: Option<bool>) -> Self {
+    /// A point; `off_curve` tells whether it is a control point of a
+    /// quadratic Bézier curve, if that is known.
+    pub fn new(x: f64, y: f64, off_curve: Option<bool>) -> Self {
         Self { x, y, off_curve }
     }
 
+    pub fn x(&self) -> f64 {
+        self.x
+    }
+
+    pub fn y(&self) -> f64 {
+        self.y
+    }
+
+    /// Whether the point is a control point of a quadratic Bézier curve.
+    pub fn is_off_curve(&self) -> bool {
+        self.off_curve.unwrap_or(false)
+    }
+
     pub(crate) fn min(&self, other: Point) -> Point {
         Point::new(
             self.x.min(other.x),
@@ -561,6 +576,15 @@ impl Rgb {
     pub(crate) fn hex(&self) -> String {
         format!("#{:02X}{:02X}{:02X}", self.0, self.1, self.2,)
     }
+
+    /// Returns the red, green and blue channels scaled to `0.0..=1.0`.
+    pub(crate) fn unit(&self) -> (f64, f64, f64) {
+        (
+            self.0 as f64 / 255.0,
+            self.1 as f64 / 255.0,
+            self.2 as f64 / 255.0,
+        )
+    }
 }
 
 #[cfg(test)]
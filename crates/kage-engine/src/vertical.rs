@@ -0,0 +1,189 @@
+use crate::{kage::Kage, polygons::Polygons, postscript::number};
+
+/// How a glyph is changed for vertical writing.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum VerticalForm {
+    /// Rotated clockwise, as brackets and the long vowel mark.
+    Rotate90,
+    /// Rotated counterclockwise.
+    Rotate270,
+    /// Moved by `dx` and `dy` glyph units (y pointing down), as
+    /// punctuation and small kana.
+    Shift { dx: f64, dy: f64 },
+}
+
+impl VerticalForm {
+    /// Returns the glyph data drawing the component `name` in this form.
+    ///
+    /// The component is referenced over the whole glyph box, then rotated
+    /// with a special line (`0:99:1` or `0:99:3`) or shifted by moving the
+    /// box of the reference.
+    pub fn glyph_data(&self, name: &str) -> String {
+        match *self {
+            VerticalForm::Rotate90 => format!("99:0:0:0:0:200:200:{name}$0:99:1:0:0:200:200"),
+            VerticalForm::Rotate270 => format!("99:0:0:0:0:200:200:{name}$0:99:3:0:0:200:200"),
+            VerticalForm::Shift { dx, dy } => format!(
+                "99:0:0:{}:{}:{}:{}:{name}",
+                number(dx),
+                number(dy),
+                number(200.0 + dx),
+                number(200.0 + dy)
+            ),
+        }
+    }
+}
+
+/// Characters with a vertical form, looked up by [`VerticalAlternates::get`].
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct VerticalAlternates {
+    forms: Vec<(char, VerticalForm)>,
+}
+
+/// Punctuation drawn in the lower left quarter of the glyph box.
+const PUNCTUATION: &str = "、。，．";
+/// Small kana, drawn low and left.
+const SMALL_KANA: &str = "ぁぃぅぇぉっゃゅょゎゕゖァィゥェォッャュョヮヵヶ";
+/// Brackets, dashes and other horizontal marks.
+const ROTATED: &str = "（）「」『』【】〈〉《》〔〕［］｛｝〖〗ー―－～〜…‥＝：";
+
+impl VerticalAlternates {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the usual forms of Japanese text: punctuation moved to the
+    /// upper right quarter, small kana moved up and right by a tenth of the
+    /// em, and brackets, dashes and ellipses rotated clockwise.
+    pub fn japanese() -> Self {
+        let shift = |dx: f64, dy: f64| VerticalForm::Shift { dx, dy };
+
+        let mut alternates = Self::new();
+        for c in PUNCTUATION.chars() {
+            alternates = alternates.insert(c, shift(120.0, -120.0));
+        }
+        for c in SMALL_KANA.chars() {
+            alternates = alternates.insert(c, shift(20.0, -20.0));
+        }
+        for c in ROTATED.chars() {
+            alternates = alternates.insert(c, VerticalForm::Rotate90);
+        }
+        alternates
+    }
+
+    /// Sets the vertical form of a character, replacing the previous one.
+    pub fn insert(mut self, c: char, form: VerticalForm) -> Self {
+        match self.forms.iter_mut().find(|(other, _)| *other == c) {
+            Some((_, previous)) => *previous = form,
+            None => self.forms.push((c, form)),
+        }
+        self
+    }
+
+    pub fn get(&self, c: char) -> Option<VerticalForm> {
+        self.forms
+            .iter()
+            .find(|(other, _)| *other == c)
+            .map(|(_, form)| *form)
+    }
+
+    pub fn len(&self) -> usize {
+        self.forms.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.forms.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (char, VerticalForm)> + '_ {
+        self.forms.iter().copied()
+    }
+}
+
+impl Kage {
+    /// Draws the component of the store in the given vertical form.
+    pub fn make_vertical_glyph_with_component_name(
+        &self,
+        polygons: &mut Polygons,
+        component_name: &str,
+        form: VerticalForm,
+    ) {
+        if self.components.search(component_name).is_none() {
+            return;
+        }
+
+        self.make_glyph_with_data(polygons, &form.glyph_data(component_name));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Typeface;
+
+    #[test]
+    fn test_glyph_data() {
+        assert_eq!(
+            VerticalForm::Rotate90.glyph_data("u30fc"),
+            "99:0:0:0:0:200:200:u30fc$0:99:1:0:0:200:200"
+        );
+        assert_eq!(
+            VerticalForm::Shift {
+                dx: 120.0,
+                dy: -120.0
+            }
+            .glyph_data("u3001"),
+            "99:0:0:120:-120:320:80:u3001"
+        );
+    }
+
+    #[test]
+    fn test_vertical_glyph() {
+        let mut kage = Kage::new(Typeface::Gothic, false);
+        kage.components.push("u30fc", "1:0:0:20:100:180:100");
+
+        // the horizontal bar becomes vertical
+        let mut polygons = Polygons::new();
+        kage.make_vertical_glyph_with_component_name(
+            &mut polygons,
+            "u30fc",
+            VerticalForm::Rotate90,
+        );
+        let [min_x, min_y, max_x, max_y] = polygons.bounds().unwrap();
+        assert!(max_x - min_x < 20.0 && max_y - min_y > 150.0);
+
+        // and moves along
+        polygons.clear();
+        kage.make_vertical_glyph_with_component_name(
+            &mut polygons,
+            "u30fc",
+            VerticalForm::Shift { dx: 10.0, dy: 0.0 },
+        );
+        assert_eq!(polygons.bounds().unwrap()[0], 30.0);
+
+        polygons.clear();
+        kage.make_vertical_glyph_with_component_name(
+            &mut polygons,
+            "missing",
+            VerticalForm::Rotate90,
+        );
+        assert!(polygons.is_empty());
+    }
+
+    #[test]
+    fn test_alternates() {
+        let alternates = VerticalAlternates::japanese();
+        assert_eq!(alternates.get('「'), Some(VerticalForm::Rotate90));
+        assert_eq!(
+            alternates.get('っ'),
+            Some(VerticalForm::Shift {
+                dx: 20.0,
+                dy: -20.0
+            })
+        );
+        assert_eq!(alternates.get('一'), None);
+
+        let alternates = alternates.insert('「', VerticalForm::Rotate270);
+        assert_eq!(alternates.get('「'), Some(VerticalForm::Rotate270));
+        assert_eq!(alternates.len(), VerticalAlternates::japanese().len());
+    }
+}
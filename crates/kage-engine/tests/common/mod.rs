@@ -0,0 +1,19 @@
+//! The glyph corpus of `tests/corpus/glyphs.txt`, shared by the integration
+//! tests and the benchmarks.
+
+use kage_engine::{Typeface, kage::Kage};
+
+const GLYPHS: &str = include_str!("../corpus/glyphs.txt");
+
+/// Returns an engine with every entry of the corpus in its store, and the
+/// names of the entries in the order of the corpus.
+pub fn corpus_kage(typeface: Typeface, use_curve: bool) -> (Kage, Vec<&'static str>) {
+    let mut kage = Kage::new(typeface, use_curve);
+    let mut names = Vec::new();
+    for line in GLYPHS.lines().filter(|line| !line.starts_with('#')) {
+        let (name, data) = line.split_once('\t').expect("name and data");
+        kage.components.push(name, data);
+        names.push(name);
+    }
+    (kage, names)
+}
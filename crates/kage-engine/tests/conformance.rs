@@ -0,0 +1,272 @@
+//! Compares the polygons drawn for `tests/corpus/glyphs.txt` with the ones
+//! drawn by the reference JavaScript implementation, stored in
+//! `tests/conformance/{ming,ming-curve,gothic,gothic-curve}.json` by
+//! `tests/conformance/dump.mjs`.
+//!
+//! A glyph conforms if it has the same polygons, with the same points in
+//! the same order, each within `TOLERANCE` of the reference. Otherwise the
+//! first polygon that differs is traced back to the stroke that drew it,
+//! and the failure reports how many glyphs diverge for each stroke type
+//! and end shapes. Divergences in the stroke kinds listed as known for a
+//! typeface are counted in the report but do not fail the test.
+//!
+//! The fixtures are not checked in yet, so the tests comparing them are
+//! ignored; run them with `cargo test --test conformance -- --ignored`
+//! after generating the fixtures.
+
+use std::{collections::BTreeMap, fs, ops::Range, path::PathBuf};
+
+use kage_engine::{
+    Typeface,
+    kage::Kage,
+    line::{
+        Line,
+        stroke_line::{StrokeKind, StrokeLineType},
+    },
+    polygons::Polygons,
+};
+use serde_json::Value;
+
+mod common;
+
+/// Both implementations floor coordinates to a tenth of a unit, so a
+/// rounding difference before the floor moves a point by one step.
+const TOLERANCE: f64 = 0.15;
+
+/// The kinds of stroke the Ming renderer is known to draw differently from
+/// the reference. The glyphs diverging only in them do not fail the comparison,
+/// but are still counted by stroke in its report.
+const MING_DIVERGENCES: &[StrokeKind] = &[];
+
+/// The kinds of stroke the Gothic renderer is known to draw differently
+/// from the reference.
+const GOTHIC_DIVERGENCES: &[StrokeKind] = &[];
+
+/// The polygons of a glyph as lists of `(x, y, off_curve)` points.
+type Reference = Vec<Vec<(f64, f64, bool)>>;
+
+fn parse_fixture(json: &str) -> BTreeMap<String, Reference> {
+    let value: Value = serde_json::from_str(json).expect("fixture is JSON");
+    let glyphs = value.as_object().expect("fixture maps names to glyphs");
+    glyphs
+        .iter()
+        .map(|(name, polygons)| {
+            let polygons = polygons
+                .as_array()
+                .expect("glyph is a list of polygons")
+                .iter()
+                .map(|polygon| {
+                    polygon
+                        .as_array()
+                        .expect("polygon is a list of points")
+                        .iter()
+                        .map(|point| match point.as_array().map(Vec::as_slice) {
+                            Some([x, y, off]) => (
+                                x.as_f64().expect("x is a number"),
+                                y.as_f64().expect("y is a number"),
+                                off.as_bool().unwrap_or(false),
+                            ),
+                            _ => panic!("point of {name} is not [x, y, off]"),
+                        })
+                        .collect()
+                })
+                .collect();
+            (name.clone(), polygons)
+        })
+        .collect()
+}
+
+/// Returns the index of the first polygon that differs from the reference,
+/// or the number of polygons of the shorter glyph if one has more polygons.
+fn first_difference(reference: &Reference, polygons: &Polygons) -> Option<usize> {
+    let position = reference
+        .iter()
+        .zip(polygons.array())
+        .position(|(expected, polygon)| {
+            expected.len() != polygon.len()
+                || expected
+                    .iter()
+                    .zip(polygon.points())
+                    .any(|(&(x, y, off_curve), point)| {
+                        (x - point.x()).abs() > TOLERANCE
+                            || (y - point.y()).abs() > TOLERANCE
+                            || off_curve != point.is_off_curve()
+                    })
+        });
+
+    match position {
+        Some(index) => Some(index),
+        None if reference.len() != polygons.len() => Some(reference.len().min(polygons.len())),
+        None => None,
+    }
+}
+
+/// Returns the stroke of the glyph data that drew the polygon at `index`,
+/// or the last stroke before it if it drew none.
+fn divergent_stroke(
+    kage: &Kage,
+    data: &str,
+    ranges: &[Range<usize>],
+    index: usize,
+) -> Option<StrokeLineType> {
+    let strokes: Vec<_> = kage
+        .get_each_expanded_line(data)
+        .into_iter()
+        .filter_map(|line| match line {
+            Line::StrokeLine(stroke) => Some(stroke),
+            _ => None,
+        })
+        .collect();
+
+    let stroke = ranges
+        .iter()
+        .position(|range| range.contains(&index))
+        .or_else(|| ranges.iter().rposition(|range| range.start <= index))?;
+    strokes.into_iter().nth(stroke)
+}
+
+/// Compares every glyph of the fixture and returns the report of the
+/// divergent ones, or `None` if they all conform or only diverge in a
+/// stroke of a `known` kind.
+fn compare(
+    kage: &Kage,
+    fixture: &BTreeMap<String, Reference>,
+    known: &[StrokeKind],
+) -> Option<String> {
+    let mut glyphs = Vec::new();
+    let mut strokes: BTreeMap<String, (usize, bool)> = BTreeMap::new();
+    for (name, reference) in fixture {
+        let Some(data) = kage.components.search(name) else {
+            glyphs.push(format!("{name}: not in the corpus"));
+            continue;
+        };
+
+        let mut polygons = Polygons::new();
+        let ranges = kage.make_strokes_with_data(&mut polygons, data);
+        if let Some(index) = first_difference(reference, &polygons) {
+            let (stroke, is_known) = match divergent_stroke(kage, data, &ranges, index) {
+                Some(stroke) => (
+                    format!(
+                        "{:?} from {:?} to {:?}",
+                        stroke.kind(),
+                        stroke.head(),
+                        stroke.tail()
+                    ),
+                    known.contains(&stroke.kind()),
+                ),
+                None => ("no stroke".to_string(), false),
+            };
+            if !is_known {
+                glyphs.push(format!("{name}: polygon {index}, drawn by {stroke}"));
+            }
+            let entry = strokes.entry(stroke).or_insert((0, is_known));
+            entry.0 += 1;
+        }
+    }
+
+    if glyphs.is_empty() {
+        return None;
+    }
+
+    let mut report = format!("{} of {} glyphs diverge:\n", glyphs.len(), fixture.len());
+    for glyph in glyphs {
+        report += &format!("  {glyph}\n");
+    }
+    report += "by stroke:\n";
+    for (stroke, (count, is_known)) in strokes {
+        let known = if is_known { " (known)" } else { "" };
+        report += &format!("  {stroke}: {count}{known}\n");
+    }
+    Some(report)
+}
+
+fn check(typeface: Typeface, use_curve: bool, fixture: &str, known: &[StrokeKind]) {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/conformance")
+        .join(format!("{fixture}.json"));
+    let json = fs::read_to_string(&path).unwrap_or_else(|error| {
+        panic!(
+            "cannot read {} ({error}); generate it with tests/conformance/dump.mjs",
+            path.display()
+        )
+    });
+
+    let (kage, _) = common::corpus_kage(typeface, use_curve);
+    if let Some(report) = compare(&kage, &parse_fixture(&json), known) {
+        panic!("{fixture} differs from the reference: {report}");
+    }
+}
+
+#[test]
+#[ignore = "needs the fixtures generated by tests/conformance/dump.mjs"]
+fn test_ming() {
+    check(Typeface::Ming, false, "ming", MING_DIVERGENCES);
+}
+
+#[test]
+#[ignore = "needs the fixtures generated by tests/conformance/dump.mjs"]
+fn test_ming_curve() {
+    check(Typeface::Ming, true, "ming-curve", MING_DIVERGENCES);
+}
+
+#[test]
+#[ignore = "needs the fixtures generated by tests/conformance/dump.mjs"]
+fn test_gothic() {
+    check(Typeface::Gothic, false, "gothic", GOTHIC_DIVERGENCES);
+}
+
+#[test]
+#[ignore = "needs the fixtures generated by tests/conformance/dump.mjs"]
+fn test_gothic_curve() {
+    check(Typeface::Gothic, true, "gothic-curve", GOTHIC_DIVERGENCES);
+}
+
+/// Checks the harness itself on a fixture dumped from this implementation.
+#[test]
+fn test_compare() {
+    let (kage, _) = common::corpus_kage(Typeface::Ming, false);
+    let dump = |name: &str| {
+        let mut polygons = Polygons::new();
+        kage.make_glyph_with_component_name(&mut polygons, name);
+        let polygons: Vec<Value> = polygons
+            .array()
+            .map(|polygon| {
+                polygon
+                    .points()
+                    .iter()
+                    .map(|point| serde_json::json!([point.x(), point.y(), point.is_off_curve()]))
+                    .collect()
+            })
+            .collect();
+        (name.to_string(), Value::from(polygons))
+    };
+    let json = Value::Object(["u53e3-j", "u6728-01@8"].map(dump).into_iter().collect());
+    let mut fixture = parse_fixture(&json.to_string());
+    assert_eq!(compare(&kage, &fixture, &[]), None);
+
+    // within the tolerance
+    fixture.get_mut("u53e3-j").unwrap()[0][0].0 += 0.1;
+    assert_eq!(compare(&kage, &fixture, &[]), None);
+
+    // the tail of the third stroke of 木 moved
+    let polygons = fixture.get_mut("u6728-01@8").unwrap();
+    let mut rendered = Polygons::new();
+    let ranges =
+        kage.make_strokes_with_data(&mut rendered, kage.components.search("u6728-01@8").unwrap());
+    polygons[ranges[2].start][1].1 += 1.0;
+    let report = compare(&kage, &fixture, &[]).unwrap();
+    assert!(report.starts_with("1 of 2 glyphs diverge"), "{report}");
+    assert!(
+        report.contains(&format!("u6728-01@8: polygon {}", ranges[2].start)),
+        "{report}"
+    );
+
+    // unless the stroke is of a kind known to diverge
+    let data = kage.components.search("u6728-01@8").unwrap();
+    let stroke = divergent_stroke(&kage, data, &ranges, ranges[2].start).unwrap();
+    assert_eq!(compare(&kage, &fixture, &[stroke.kind()]), None);
+
+    // a missing polygon
+    fixture.get_mut("u53e3-j").unwrap().pop();
+    assert!(compare(&kage, &fixture, &[]).unwrap().starts_with("2 of 2"));
+}
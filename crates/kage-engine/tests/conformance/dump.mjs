@@ -0,0 +1,44 @@
+// Renders the glyphs of ../corpus/glyphs.txt with the reference JavaScript
+// implementation of KAGE and writes their polygons to the fixtures read by
+// tests/conformance.rs, one file per typeface and curve mode.
+//
+//     npm install @kurgm/kage-engine
+//     node tests/conformance/dump.mjs
+//
+// Each fixture maps a glyph name to its polygons, each polygon being a list
+// of [x, y, off] points as returned by `Polygon#array`.
+
+import { readFileSync, writeFileSync } from "node:fs";
+import { Kage, Polygons } from "@kurgm/kage-engine";
+
+const here = new URL(".", import.meta.url);
+const glyphs = readFileSync(new URL("../corpus/glyphs.txt", here), "utf8")
+  .split("\n")
+  .filter((line) => line !== "" && !line.startsWith("#"))
+  .map((line) => line.split("\t"));
+
+const modes = [
+  ["ming", "kMincho", false],
+  ["ming-curve", "kMincho", true],
+  ["gothic", "kGothic", false],
+  ["gothic-curve", "kGothic", true],
+];
+
+for (const [file, typeface, useCurve] of modes) {
+  const kage = new Kage();
+  kage.kShotai = kage[typeface];
+  kage.kUseCurve = useCurve;
+  for (const [name, data] of glyphs) {
+    kage.kBuhin.push(name, data);
+  }
+
+  const fixture = {};
+  for (const [name] of glyphs) {
+    const polygons = new Polygons();
+    kage.makeGlyph(polygons, name);
+    fixture[name] = polygons.array.map((polygon) =>
+      polygon.array.map(({ x, y, off }) => [x, y, off]),
+    );
+  }
+  writeFileSync(new URL(`${file}.json`, here), JSON.stringify(fixture) + "\n");
+}
@@ -0,0 +1,65 @@
+//! Renders the glyphs of `tests/corpus/glyphs.txt` end to end and compares
+//! them with the SVG files in `tests/golden`, for each typeface and curve
+//! mode.
+//!
+//! After an intended change of the output, run the tests with
+//! `KAGE_BLESS=1` to rewrite the golden files, and review their diff.
+
+use std::{env, fs, path::PathBuf};
+
+use kage_engine::{Typeface, polygons::Polygons};
+
+mod common;
+
+fn check(typeface: Typeface, use_curve: bool, directory: &str) {
+    let (kage, names) = common::corpus_kage(typeface, use_curve);
+
+    let directory = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/golden")
+        .join(directory);
+    let bless = env::var_os("KAGE_BLESS").is_some();
+    if bless {
+        fs::create_dir_all(&directory).unwrap();
+    }
+
+    let mut mismatches = Vec::new();
+    for name in names {
+        let mut polygons = Polygons::new();
+        kage.make_glyph_with_component_name(&mut polygons, name);
+        assert!(!polygons.is_empty(), "{name} draws nothing");
+        let svg = polygons.generate_svg(use_curve);
+
+        let path = directory.join(format!("{name}.svg"));
+        if bless {
+            fs::write(&path, svg).unwrap();
+        } else if fs::read_to_string(&path).ok().as_deref() != Some(svg.as_str()) {
+            mismatches.push(path.display().to_string());
+        }
+    }
+
+    assert!(
+        mismatches.is_empty(),
+        "rendering differs from the golden files (rerun with KAGE_BLESS=1 if intended):\n{}",
+        mismatches.join("\n")
+    );
+}
+
+#[test]
+fn test_ming() {
+    check(Typeface::Ming, false, "ming");
+}
+
+#[test]
+fn test_ming_curve() {
+    check(Typeface::Ming, true, "ming-curve");
+}
+
+#[test]
+fn test_gothic() {
+    check(Typeface::Gothic, false, "gothic");
+}
+
+#[test]
+fn test_gothic_curve() {
+    check(Typeface::Gothic, true, "gothic-curve");
+}
@@ -0,0 +1,60 @@
+//! Draws every glyph of the corpus with light, regular and heavy masters,
+//! which must stay point compatible for interpolation.
+
+use kage_engine::{
+    Interpolate, Parameters, Typeface,
+    font::{gothic::Gothic, ming::Ming},
+    masters::Masters,
+};
+
+mod common;
+
+fn masters(typeface: &Typeface, use_curve: bool) -> Masters {
+    let weights = [400.0, 100.0, 900.0];
+    let masters = match typeface {
+        Typeface::Ming => {
+            let regular = Ming::new(use_curve);
+            weights.map(|weight| (weight, Parameters::Ming(regular.at_weight(weight))))
+        }
+        _ => {
+            let regular = Gothic::new(use_curve);
+            weights.map(|weight| (weight, Parameters::Gothic(regular.at_weight(weight))))
+        }
+    };
+    Masters::new(masters.into()).unwrap()
+}
+
+fn check(typeface: Typeface, use_curve: bool) {
+    let masters = masters(&typeface, use_curve);
+    let (kage, names) = common::corpus_kage(typeface, use_curve);
+    let failures: Vec<String> = names
+        .iter()
+        .filter_map(|&name| {
+            let data = kage.components.search(name).unwrap();
+            kage.make_compatible_glyphs(&masters, data)
+                .err()
+                .map(|error| format!("{name}: {error}"))
+        })
+        .collect();
+    assert!(failures.is_empty(), "{}", failures.join("\n"));
+}
+
+#[test]
+fn test_ming() {
+    check(Typeface::Ming, false);
+}
+
+#[test]
+fn test_ming_curve() {
+    check(Typeface::Ming, true);
+}
+
+#[test]
+fn test_gothic() {
+    check(Typeface::Gothic, false);
+}
+
+#[test]
+fn test_gothic_curve() {
+    check(Typeface::Gothic, true);
+}
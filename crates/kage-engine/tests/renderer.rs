@@ -0,0 +1,172 @@
+//! Implements [`StrokeRenderer`] outside of the crate, overriding every
+//! method, to check that the public API is enough to write a typeface.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use kage_engine::{
+    AdjustedStroke, Point, Polygon, StrokeLineType, StrokeRenderer,
+    font::stroke_adjustment::initial_adjustments,
+    kage::Kage,
+    line::special_line::{SpecialLineType, TransformType},
+    polygons::Polygons,
+};
+
+/// Draws every stroke as a bar between its first two points, as wide as
+/// its foot adjustment plus one, and mirrors horizontal flips itself.
+#[derive(Default)]
+struct Bars {
+    use_curve: AtomicBool,
+}
+
+impl StrokeRenderer for Bars {
+    fn adjust_strokes<'a>(
+        &self,
+        strokes: &'a [&'a StrokeLineType],
+    ) -> Vec<(&'a StrokeLineType, AdjustedStroke)> {
+        let mut adjusted = initial_adjustments(strokes);
+        if let Some((_, adjustment)) = adjusted.last_mut() {
+            adjustment.set_foot_adjustment(adjustment.foot_adjustment() + 2);
+        }
+        adjusted
+    }
+
+    fn draw_stroke(
+        &self,
+        polygons: &mut Polygons,
+        stroke: StrokeLineType,
+        adjustment: AdjustedStroke,
+    ) {
+        let [start, end, ..] = stroke.points();
+        let half = (adjustment.foot_adjustment() + 1) as f64 / 2.0;
+        polygons.push(Polygon::new(
+            vec![
+                Point::new(start.x(), start.y() - half, None),
+                Point::new(end.x(), end.y() - half, None),
+                Point::new(end.x(), end.y() + half, None),
+                Point::new(start.x(), start.y() + half, None),
+            ],
+            stroke.color(),
+        ));
+    }
+
+    fn transform(&self, polygons: &mut Polygons, special_line: SpecialLineType) {
+        if special_line.transform_type() != TransformType::HorizontalFlip {
+            special_line.apply(polygons);
+            return;
+        }
+
+        for polygon in polygons.array_mut() {
+            if !polygon
+                .points()
+                .iter()
+                .all(|&point| special_line.contains(point))
+            {
+                continue;
+            }
+            for index in 0..polygon.len() {
+                let point = polygon.get(index).unwrap();
+                polygon
+                    .set_point(index, special_line.transform_point(point))
+                    .unwrap();
+            }
+        }
+    }
+
+    fn use_curve(&self) -> bool {
+        self.use_curve.load(Ordering::Relaxed)
+    }
+
+    fn set_use_curve(&mut self, use_curve: bool) {
+        self.use_curve.store(use_curve, Ordering::Relaxed);
+    }
+}
+
+fn points(polygons: &Polygons) -> Vec<Vec<(f64, f64)>> {
+    polygons
+        .array()
+        .map(|polygon| {
+            polygon
+                .points()
+                .iter()
+                .map(|point| (point.x(), point.y()))
+                .collect()
+        })
+        .collect()
+}
+
+#[test]
+fn test_external_renderer() {
+    let mut kage = Kage::with_renderer(Bars::default());
+    let mut polygons = Polygons::new();
+    kage.make_glyph_with_data(&mut polygons, "1:0:0:20:50:100:50$1:0:0:20:100:180:100");
+    assert_eq!(
+        points(&polygons),
+        vec![
+            vec![(20.0, 49.5), (100.0, 49.5), (100.0, 50.5), (20.0, 50.5)],
+            vec![(20.0, 98.5), (180.0, 98.5), (180.0, 101.5), (20.0, 101.5)],
+        ]
+    );
+
+    // the flip moves only the polygons in its box
+    polygons.clear();
+    kage.make_glyph_with_data(
+        &mut polygons,
+        "1:0:0:20:50:100:50$1:0:0:20:100:180:100$0:98:0:0:0:120:60",
+    );
+    assert_eq!(
+        points(&polygons)[0],
+        vec![(100.0, 49.5), (20.0, 49.5), (20.0, 50.5), (100.0, 50.5)]
+    );
+    assert_eq!(points(&polygons)[1][0], (20.0, 98.5));
+
+    // other special lines fall back to the built-in transforms
+    polygons.clear();
+    kage.make_glyph_with_data(&mut polygons, "1:0:0:20:100:180:100$0:99:2:0:0:200:200");
+    assert_eq!(points(&polygons)[0][0], (180.0, 101.5));
+
+    kage.set_use_curve(true);
+    assert!(kage.renderer().use_curve());
+}
+
+/// Only adjusts the last stroke, like [`Bars`].
+struct LastOnly;
+
+impl StrokeRenderer for LastOnly {
+    fn adjust_strokes<'a>(
+        &self,
+        strokes: &'a [&'a StrokeLineType],
+    ) -> Vec<(&'a StrokeLineType, AdjustedStroke)> {
+        let mut adjusted = initial_adjustments(strokes);
+        adjusted.drain(..adjusted.len().saturating_sub(1));
+        if let Some((_, adjustment)) = adjusted.last_mut() {
+            adjustment.set_foot_adjustment(adjustment.foot_adjustment() + 2);
+        }
+        adjusted
+    }
+
+    fn draw_stroke(
+        &self,
+        polygons: &mut Polygons,
+        stroke: StrokeLineType,
+        adjustment: AdjustedStroke,
+    ) {
+        Bars::default().draw_stroke(polygons, stroke, adjustment);
+    }
+}
+
+#[test]
+fn test_missing_adjustments() {
+    let kage = Kage::with_renderer(LastOnly);
+    let mut polygons = Polygons::new();
+    kage.make_glyph_with_data(&mut polygons, "1:0:0:20:50:100:50$1:0:0:20:100:180:100");
+
+    // the first stroke keeps the adjustments of the glyph data, the last
+    // one gets its own
+    assert_eq!(
+        points(&polygons),
+        vec![
+            vec![(20.0, 49.5), (100.0, 49.5), (100.0, 50.5), (20.0, 50.5)],
+            vec![(20.0, 98.5), (180.0, 98.5), (180.0, 101.5), (20.0, 101.5)],
+        ]
+    );
+}
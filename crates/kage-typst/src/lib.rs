@@ -16,6 +16,8 @@ pub fn kage_to_svg(
         let typeface_str = core::str::from_utf8_unchecked(typeface).to_lowercase();
         match &typeface_str[..] {
             "gothic" => Typeface::Gothic,
+            "rounded-gothic" => Typeface::RoundedGothic,
+            "textbook" => Typeface::Textbook,
             "ming" => Typeface::Ming,
             _ => Typeface::Ming,
         }